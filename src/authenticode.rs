@@ -0,0 +1,278 @@
+use sha2::Digest;
+
+use crate::enums::ImageDataDirectoryIndex;
+use crate::structures::{OptionalHeader, Pe};
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+mod der;
+pub use der::Oid;
+
+mod signed_data;
+pub use signed_data::{
+    Attribute, Certificate, Countersignature, DistinguishedName, SignedData, SignerIdentifier,
+    SignerInfo, SpcIndirectDataContent,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub fn from_oid(oid: &Oid<'_>) -> Option<Self> {
+        if *oid == "1.3.14.3.2.26" {
+            Some(Self::Sha1)
+        } else if *oid == "2.16.840.1.101.3.4.2.1" {
+            Some(Self::Sha256)
+        } else {
+            None
+        }
+    }
+
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => sha1::Sha1::digest(data).to_vec(),
+            Self::Sha256 => sha2::Sha256::digest(data).to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha1 => f.write_str("sha1"),
+            Self::Sha256 => f.write_str("sha256"),
+        }
+    }
+}
+
+impl<'a> Pe<'a> {
+    /// File offset of the optional header.
+    pub(crate) fn optional_header_offset(&self) -> usize {
+        // Signature + file header
+        self.dos_header.e_lfanew as usize + 4 + 20
+    }
+
+    /// File offset of the `check_sum` field of the optional header.
+    pub(crate) fn check_sum_offset(&self) -> usize {
+        self.optional_header_offset() + 64
+    }
+
+    /// File offset of the `EntrySecurity` data directory entry.
+    pub(crate) fn security_directory_offset(&self) -> usize {
+        let data_directory_offset = match self.pe_header.optional_header {
+            OptionalHeader::I386(_) => 96,
+            OptionalHeader::AMD64(_) => 112,
+        };
+        self.optional_header_offset()
+            + data_directory_offset
+            + ImageDataDirectoryIndex::EntrySecurity as usize * 8
+    }
+
+    /// Computes the Authenticode digest of the image, as signed in its PKCS#7 `SignedData`.
+    pub fn authentihash(&self, algorithm: HashAlgorithm) -> Vec<u8> {
+        match algorithm {
+            HashAlgorithm::Sha1 => self.authentihash_with::<sha1::Sha1>(),
+            HashAlgorithm::Sha256 => self.authentihash_with::<sha2::Sha256>(),
+        }
+    }
+
+    /// Parses the PKCS#7 signatures of the certificate table.
+    pub fn signatures<E>(&self) -> Result<Vec<SignedData<'a>>, nom::Err<E>>
+    where
+        E: NomError<'a>,
+    {
+        let certificate_table = match self.certificate_table() {
+            Some(certificate_table) => certificate_table,
+            None => return Ok(Vec::new()),
+        };
+        certificate_table
+            .signatures()
+            .map(|signature| SignedData::parse(signature).map(|(_, signed_data)| signed_data))
+            .collect()
+    }
+
+    fn authentihash_with<D: Digest>(&self) -> Vec<u8> {
+        let file = self.file;
+        let mut hasher = D::new();
+
+        // Headers, without the checksum and the security directory entry when there is one
+        let size_of_headers = self
+            .pe_header
+            .optional_header
+            .size_of_headers()
+            .min(file.len());
+        let number_of_data_directories = match self.pe_header.optional_header {
+            OptionalHeader::I386(ref oh32) => oh32.data_directory.len(),
+            OptionalHeader::AMD64(ref oh64) => oh64.data_directory.len(),
+        };
+        let mut skipped = vec![(self.check_sum_offset(), 4)];
+        if number_of_data_directories > ImageDataDirectoryIndex::EntrySecurity as usize {
+            skipped.push((self.security_directory_offset(), 8));
+        }
+        let mut start = 0;
+        for (offset, size) in skipped {
+            let offset = offset.min(size_of_headers);
+            hasher.update(&file[start.min(offset)..offset]);
+            start = offset + size;
+        }
+        hasher.update(&file[start.min(size_of_headers)..size_of_headers]);
+
+        // Sections, in file order
+        let mut sections: Vec<_> = self
+            .pe_header
+            .sections
+            .iter()
+            .filter(|s| s.size_of_raw_data != 0)
+            .collect();
+        sections.sort_by_key(|s| s.pointer_to_raw_data);
+
+        let mut sum_of_bytes_hashed = size_of_headers;
+        for section in sections {
+            let start = (section.pointer_to_raw_data as usize).min(file.len());
+            let end = (start + section.size_of_raw_data as usize).min(file.len());
+            hasher.update(&file[start..end]);
+            sum_of_bytes_hashed = sum_of_bytes_hashed.max(end);
+        }
+
+        // Trailing data, without the certificate table
+        let end_of_data = match self
+            .pe_header
+            .optional_header
+            .get_data_directory(ImageDataDirectoryIndex::EntrySecurity)
+        {
+            Some(data_dir) => (data_dir.virtual_address as usize).min(file.len()),
+            None => file.len(),
+        };
+        if end_of_data > sum_of_bytes_hashed {
+            hasher.update(&file[sum_of_bytes_hashed..end_of_data]);
+        }
+
+        hasher.finalize().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    const CHECK_SUM_OFFSET: usize = 0x58 + 64;
+    const SECURITY_DIRECTORY_OFFSET: usize = 0x58 + 112 + 4 * 8;
+
+    /// PE32+ image with a single 0x200-byte section at 0x200, followed by `trailing_data` and
+    /// an 8-byte certificate table.
+    fn image(number_of_rva_and_sizes: u32, size_of_headers: u32, trailing_data: &[u8]) -> Vec<u8> {
+        let mut file = vec![0u8; 0x400];
+        file[..2].copy_from_slice(b"MZ");
+        file[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        file[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        // File header
+        let size_of_optional_header = 112 + 8 * number_of_rva_and_sizes as u16;
+        file[0x44..0x46].copy_from_slice(&0x8664u16.to_le_bytes());
+        file[0x46..0x48].copy_from_slice(&1u16.to_le_bytes());
+        file[0x54..0x56].copy_from_slice(&size_of_optional_header.to_le_bytes());
+        file[0x56..0x58].copy_from_slice(&0x22u16.to_le_bytes());
+
+        // Optional header
+        let oh = 0x58;
+        file[oh..oh + 2].copy_from_slice(&0x20bu16.to_le_bytes());
+        file[oh + 32..oh + 36].copy_from_slice(&0x1000u32.to_le_bytes());
+        file[oh + 36..oh + 40].copy_from_slice(&0x200u32.to_le_bytes());
+        file[oh + 56..oh + 60].copy_from_slice(&0x2000u32.to_le_bytes());
+        file[oh + 60..oh + 64].copy_from_slice(&size_of_headers.to_le_bytes());
+        file[oh + 64..oh + 68].copy_from_slice(&0xdeadbeefu32.to_le_bytes());
+        file[oh + 68..oh + 70].copy_from_slice(&3u16.to_le_bytes());
+        file[oh + 108..oh + 112].copy_from_slice(&number_of_rva_and_sizes.to_le_bytes());
+
+        // Section header
+        let sh = oh + size_of_optional_header as usize;
+        file[sh..sh + 8].copy_from_slice(b".text\0\0\0");
+        file[sh + 8..sh + 12].copy_from_slice(&0x100u32.to_le_bytes());
+        file[sh + 12..sh + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        file[sh + 16..sh + 20].copy_from_slice(&0x200u32.to_le_bytes());
+        file[sh + 20..sh + 24].copy_from_slice(&0x200u32.to_le_bytes());
+        file[sh + 36..sh + 40].copy_from_slice(&0x6000_0020u32.to_le_bytes());
+
+        for (idx, b) in file[0x200..].iter_mut().enumerate() {
+            *b = idx as u8;
+        }
+        file.extend_from_slice(trailing_data);
+
+        // Certificate table
+        if number_of_rva_and_sizes > 4 {
+            let certificate_table = file.len() as u32;
+            file[SECURITY_DIRECTORY_OFFSET..SECURITY_DIRECTORY_OFFSET + 4]
+                .copy_from_slice(&certificate_table.to_le_bytes());
+            file[SECURITY_DIRECTORY_OFFSET + 4..SECURITY_DIRECTORY_OFFSET + 8]
+                .copy_from_slice(&8u32.to_le_bytes());
+            file.extend_from_slice(&8u32.to_le_bytes());
+            file.extend_from_slice(&0x0200u16.to_le_bytes());
+            file.extend_from_slice(&0x0002u16.to_le_bytes());
+        }
+        file
+    }
+
+    fn sha256(parts: &[&[u8]]) -> Vec<u8> {
+        HashAlgorithm::Sha256.digest(&parts.concat())
+    }
+
+    #[test]
+    fn skips_checksum_security_entry_and_certificate_table() {
+        let file = image(16, 0x200, b"appended");
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert_eq!(pe.certificate_table().unwrap().certificates.len(), 1);
+
+        let expected = sha256(&[
+            &file[..CHECK_SUM_OFFSET],
+            &file[CHECK_SUM_OFFSET + 4..SECURITY_DIRECTORY_OFFSET],
+            &file[SECURITY_DIRECTORY_OFFSET + 8..0x400],
+            b"appended",
+        ]);
+        assert_eq!(pe.authentihash(HashAlgorithm::Sha256), expected);
+    }
+
+    #[test]
+    fn hashes_everything_without_security_entry() {
+        // Only 4 data directories: the would-be security entry is part of the section headers
+        let file = image(4, 0x200, b"");
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.certificate_table().is_none());
+
+        let expected = sha256(&[&file[..CHECK_SUM_OFFSET], &file[CHECK_SUM_OFFSET + 4..]]);
+        assert_eq!(pe.authentihash(HashAlgorithm::Sha256), expected);
+    }
+
+    #[test]
+    fn headers_smaller_than_security_entry() {
+        let file = image(16, 0xe0, b"");
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+
+        let expected = sha256(&[
+            &file[..CHECK_SUM_OFFSET],
+            &file[CHECK_SUM_OFFSET + 4..0xe0],
+            &file[0x200..0x400],
+        ]);
+        assert_eq!(pe.authentihash(HashAlgorithm::Sha256), expected);
+    }
+
+    #[test]
+    fn hash_algorithm_from_oid() {
+        let sha1 = Oid(&[0x2b, 0x0e, 0x03, 0x02, 0x1a]);
+        let sha256 = Oid(&[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01]);
+        assert_eq!(HashAlgorithm::from_oid(&sha1), Some(HashAlgorithm::Sha1));
+        assert_eq!(
+            HashAlgorithm::from_oid(&sha256),
+            Some(HashAlgorithm::Sha256)
+        );
+        assert_eq!(
+            HashAlgorithm::from_oid(&Oid(&[0x2b, 0x0e, 0x03, 0x02])),
+            None
+        );
+    }
+}
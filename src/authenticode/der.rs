@@ -0,0 +1,295 @@
+use chrono::TimeZone;
+use nom::bytes::complete::take;
+use nom::combinator::{map, map_opt, verify};
+use nom::error::context;
+use nom::number::complete::le_u8;
+
+use crate::NomError;
+
+use std::fmt;
+
+pub(crate) const INTEGER: u8 = 0x02;
+pub(crate) const OCTET_STRING: u8 = 0x04;
+pub(crate) const OBJECT_IDENTIFIER: u8 = 0x06;
+pub(crate) const UTF8_STRING: u8 = 0x0c;
+pub(crate) const PRINTABLE_STRING: u8 = 0x13;
+pub(crate) const T61_STRING: u8 = 0x14;
+pub(crate) const IA5_STRING: u8 = 0x16;
+pub(crate) const UTC_TIME: u8 = 0x17;
+pub(crate) const GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const BMP_STRING: u8 = 0x1e;
+pub(crate) const SEQUENCE: u8 = 0x30;
+pub(crate) const SET: u8 = 0x31;
+
+/// Constructed, context-specific tag `[n]`.
+pub(crate) const fn context_specific(n: u8) -> u8 {
+    0xa0 | n
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tlv<'a> {
+    pub tag: u8,
+    pub value: &'a [u8],
+
+    /// Whole encoding, tag and length included.
+    pub raw: &'a [u8],
+}
+
+pub(crate) fn tlv<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Tlv<'a>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, tag) = context("DER tag", verify(le_u8, |tag: &u8| tag & 0x1f != 0x1f))(input)?;
+    let (rest, first) = context("DER length", le_u8)(rest)?;
+    let (rest, length) = match first {
+        0..=0x7f => (rest, first as usize),
+        0x81..=0x84 => {
+            let (rest, bytes) = take((first & 0x7f) as usize)(rest)?;
+            let length = bytes.iter().fold(0usize, |acc, b| acc << 8 | *b as usize);
+            (rest, length)
+        }
+        _ => {
+            return Err(nom::Err::Error(E::add_context(
+                rest,
+                "Unsupported DER length",
+                E::from_error_kind(rest, nom::error::ErrorKind::LengthValue),
+            )))
+        }
+    };
+    let (rest, value) = context("DER value", take(length))(rest)?;
+    let raw = &input[..input.len() - rest.len()];
+
+    Ok((rest, Tlv { tag, value, raw }))
+}
+
+/// Parses an element with the given tag and returns it.
+pub(crate) fn element<'a, E>(tag: u8) -> impl FnMut(&'a [u8]) -> nom::IResult<&'a [u8], Tlv<'a>, E>
+where
+    E: NomError<'a>,
+{
+    verify(tlv, move |t: &Tlv| t.tag == tag)
+}
+
+/// Parses an element with the given tag and returns its value.
+pub(crate) fn tagged<'a, E>(tag: u8) -> impl FnMut(&'a [u8]) -> nom::IResult<&'a [u8], &'a [u8], E>
+where
+    E: NomError<'a>,
+{
+    map(element(tag), |t| t.value)
+}
+
+/// Applies `f` on every element of `input`, which must be entirely consumed.
+pub(crate) fn all<'a, O, E, F>(mut f: F, input: &'a [u8]) -> Result<Vec<O>, nom::Err<E>>
+where
+    E: NomError<'a>,
+    F: FnMut(&'a [u8]) -> nom::IResult<&'a [u8], O, E>,
+{
+    let mut items = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (r, item) = f(rest)?;
+        items.push(item);
+        rest = r;
+    }
+    Ok(items)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oid<'a>(pub &'a [u8]);
+
+impl<'a> Oid<'a> {
+    fn arcs(&self) -> Vec<u64> {
+        let mut arcs = Vec::new();
+        let mut value = 0u64;
+        for b in self.0 {
+            value = value << 7 | (b & 0x7f) as u64;
+            if b & 0x80 == 0 {
+                if arcs.is_empty() {
+                    let first = (value / 40).min(2);
+                    arcs.push(first);
+                    arcs.push(value - first * 40);
+                } else {
+                    arcs.push(value);
+                }
+                value = 0;
+            }
+        }
+        arcs
+    }
+}
+
+impl<'a> fmt::Display for Oid<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dot = "";
+        for arc in self.arcs() {
+            write!(f, "{}{}", dot, arc)?;
+            dot = ".";
+        }
+        Ok(())
+    }
+}
+
+impl<'a> PartialEq<&str> for Oid<'a> {
+    /// Compares with a dotted OID, encoded arc by arc against the DER bytes.
+    fn eq(&self, other: &&str) -> bool {
+        let mut arcs = other.split('.').map(|arc| arc.parse::<u64>().ok());
+        let first = match (arcs.next().flatten(), arcs.next().flatten()) {
+            (Some(first), Some(second)) if (first < 2 && second < 40) || first == 2 => {
+                match (first * 40).checked_add(second) {
+                    Some(arc) => arc,
+                    None => return false,
+                }
+            }
+            _ => return false,
+        };
+
+        let mut bytes = self.0.iter();
+        for arc in std::iter::once(Some(first)).chain(arcs) {
+            let arc = match arc {
+                Some(arc) => arc,
+                None => return false,
+            };
+            let groups = (u64::BITS - arc.leading_zeros()).max(1).div_ceil(7);
+            for idx in (0..groups).rev() {
+                let more = if idx == 0 { 0 } else { 0x80 };
+                if bytes.next() != Some(&((arc >> (idx * 7)) as u8 & 0x7f | more)) {
+                    return false;
+                }
+            }
+        }
+        bytes.next().is_none()
+    }
+}
+
+pub(crate) fn oid<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Oid<'a>, E>
+where
+    E: NomError<'a>,
+{
+    context("OID", map(tagged(OBJECT_IDENTIFIER), Oid))(input)
+}
+
+/// Parses an `AlgorithmIdentifier`, ignoring its parameters.
+pub(crate) fn algorithm<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Oid<'a>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, value) = context("Algorithm identifier", tagged(SEQUENCE))(input)?;
+    let (_, algorithm) = oid(value)?;
+    Ok((rest, algorithm))
+}
+
+/// Parses a small INTEGER.
+pub(crate) fn small_integer<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], u32, E>
+where
+    E: NomError<'a>,
+{
+    context(
+        "Integer",
+        map(
+            verify(tagged(INTEGER), |value: &[u8]| value.len() <= 4),
+            |value| value.iter().fold(0u32, |acc, b| acc << 8 | *b as u32),
+        ),
+    )(input)
+}
+
+fn digits(s: &[u8]) -> Option<u32> {
+    s.iter().try_fold(0u32, |acc, b| {
+        if b.is_ascii_digit() {
+            Some(acc * 10 + (b - b'0') as u32)
+        } else {
+            None
+        }
+    })
+}
+
+fn decode_time(tag: u8, value: &[u8]) -> Option<chrono::DateTime<chrono::Utc>> {
+    let (year, value) = if tag == UTC_TIME {
+        let year = digits(value.get(..2)?)?;
+        (
+            if year >= 50 { 1900 + year } else { 2000 + year },
+            &value[2..],
+        )
+    } else {
+        (digits(value.get(..4)?)?, &value[4..])
+    };
+    let field = |idx: usize| digits(value.get(idx * 2..idx * 2 + 2)?);
+
+    let time = chrono::NaiveDate::from_ymd_opt(year as i32, field(0)?, field(1)?)?.and_hms_opt(
+        field(2)?,
+        field(3)?,
+        field(4)?,
+    )?;
+    Some(chrono::Utc.from_utc_datetime(&time))
+}
+
+/// Parses an `UTCTime` or a `GeneralizedTime`.
+pub(crate) fn time<'a, E>(
+    input: &'a [u8],
+) -> nom::IResult<&'a [u8], chrono::DateTime<chrono::Utc>, E>
+where
+    E: NomError<'a>,
+{
+    context(
+        "Time",
+        map_opt(
+            verify(tlv, |t: &Tlv| {
+                t.tag == UTC_TIME || t.tag == GENERALIZED_TIME
+            }),
+            |t| decode_time(t.tag, t.value),
+        ),
+    )(input)
+}
+
+/// Decodes any of the string types found in distinguished names.
+pub(crate) fn decode_string(t: &Tlv<'_>) -> String {
+    match t.tag {
+        BMP_STRING => {
+            let units: Vec<u16> = t
+                .value
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16_lossy(&units[..])
+        }
+        UTF8_STRING | PRINTABLE_STRING | T61_STRING | IA5_STRING => {
+            String::from_utf8_lossy(t.value).into_owned()
+        }
+        _ => t.value.iter().map(|b| format!("{:02x}", b)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    #[test]
+    fn oid_compares_with_dotted_string() {
+        // 1.2.840.113549.1.7.2
+        let oid = Oid(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02]);
+        assert_eq!(oid.to_string(), "1.2.840.113549.1.7.2");
+        assert!(oid == "1.2.840.113549.1.7.2");
+        assert!(oid != "1.2.840.113549.1.7");
+        assert!(oid != "1.2.840.113549.1.7.2.0");
+        assert!(oid != "1.2.840.113549.1.7.3");
+        assert!(oid != "1.2.840.x.1.7.2");
+        assert!(oid != "1");
+        assert!(Oid(&[0x00]) == "0.0");
+        assert!(Oid(&[0x88, 0x37]) == "2.999");
+    }
+
+    #[test]
+    fn long_form_length() {
+        let mut input = vec![OCTET_STRING, 0x82, 0x01, 0x00];
+        input.extend_from_slice(&[0xaa; 0x100]);
+        input.push(0xff);
+
+        let (rest, t) = tlv::<Error>(&input).unwrap();
+        assert_eq!(rest, &[0xff]);
+        assert_eq!(t.tag, OCTET_STRING);
+        assert_eq!(t.value.len(), 0x100);
+        assert_eq!(t.raw.len(), 0x104);
+        assert!(tlv::<Error>(&input[..0x100]).is_err());
+    }
+}
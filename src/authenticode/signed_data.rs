@@ -0,0 +1,655 @@
+use nom::combinator::opt;
+use nom::error::context;
+use nom::sequence::tuple;
+
+use crate::authenticode::der::{self, Oid, Tlv};
+use crate::authenticode::HashAlgorithm;
+use crate::structures::Pe;
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const OID_SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+const OID_COUNTERSIGNATURE: &str = "1.2.840.113549.1.9.6";
+const OID_SPC_INDIRECT_DATA: &str = "1.3.6.1.4.1.311.2.1.4";
+const OID_SPC_NESTED_SIGNATURE: &str = "1.3.6.1.4.1.311.2.4.1";
+const OID_RFC3161_TIMESTAMP: &str = "1.3.6.1.4.1.311.3.3.1";
+const OID_TST_INFO: &str = "1.2.840.113549.1.9.16.1.4";
+
+/// How deep signatures may nest through countersignatures and nested signatures. Signed files
+/// go no further than a timestamped signature nested in another one.
+const MAX_NESTING: usize = 8;
+
+fn hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for b in bytes {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct DistinguishedName<'a> {
+    /// DER encoding of the name, used to match issuers against subjects.
+    pub raw: &'a [u8],
+    pub attributes: Vec<(Oid<'a>, String)>,
+}
+
+impl<'a> DistinguishedName<'a> {
+    /// Value of the first attribute of the given type, e.g. `2.5.4.3` for the common name.
+    pub fn get(&self, oid: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == oid)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn common_name(&self) -> Option<&str> {
+        self.get("2.5.4.3")
+    }
+}
+
+impl<'a> PartialEq for DistinguishedName<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+/// Short names of the usual distinguished name attributes.
+const ATTRIBUTE_NAMES: [(&str, &str); 8] = [
+    ("2.5.4.3", "CN"),
+    ("2.5.4.5", "serialNumber"),
+    ("2.5.4.6", "C"),
+    ("2.5.4.7", "L"),
+    ("2.5.4.8", "ST"),
+    ("2.5.4.10", "O"),
+    ("2.5.4.11", "OU"),
+    ("1.2.840.113549.1.9.1", "E"),
+];
+
+impl<'a> fmt::Display for DistinguishedName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut comma = "";
+        for (oid, value) in &self.attributes {
+            match ATTRIBUTE_NAMES.iter().find(|(dotted, _)| *oid == *dotted) {
+                Some((_, name)) => write!(f, "{}{}={}", comma, name, value)?,
+                None => write!(f, "{}{}={}", comma, oid, value)?,
+            }
+            comma = ", ";
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for DistinguishedName<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, name) = context("Distinguished name", der::element(der::SEQUENCE))(input)?;
+
+        let mut attributes = Vec::new();
+        for rdn in der::all(der::tagged(der::SET), name.value)? {
+            for attribute in der::all(der::tagged(der::SEQUENCE), rdn)? {
+                let (_, (oid, value)) = tuple((der::oid, der::tlv))(attribute)?;
+                attributes.push((oid, der::decode_string(&value)));
+            }
+        }
+
+        Ok((
+            rest,
+            Self {
+                raw: name.raw,
+                attributes,
+            },
+        ))
+    }
+}
+
+/// X.509 certificate embedded in a signature.
+#[derive(Debug)]
+pub struct Certificate<'a> {
+    /// DER encoding of the certificate.
+    pub raw: &'a [u8],
+    pub serial_number: &'a [u8],
+    pub issuer: DistinguishedName<'a>,
+    pub subject: DistinguishedName<'a>,
+    pub not_before: chrono::DateTime<chrono::Utc>,
+    pub not_after: chrono::DateTime<chrono::Utc>,
+}
+
+impl<'a> Certificate<'a> {
+    pub fn is_self_signed(&self) -> bool {
+        self.issuer == self.subject
+    }
+
+    pub fn is_valid_at(&self, time: chrono::DateTime<chrono::Utc>) -> bool {
+        self.not_before <= time && time <= self.not_after
+    }
+}
+
+impl<'a> fmt::Display for Certificate<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}subject: {}\n", self.subject)?;
+        write!(f, "{offset}issuer: {}\n", self.issuer)?;
+        write!(f, "{offset}serial_number: ")?;
+        hex(f, self.serial_number)?;
+        write!(f, "\n{offset}not_before: {}\n", self.not_before)?;
+        write!(f, "{offset}not_after: {}\n", self.not_after)
+    }
+}
+
+impl<'a> Parse<'a> for Certificate<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, certificate) = context("Certificate", der::element(der::SEQUENCE))(input)?;
+        let (_, tbs_certificate) = der::tagged(der::SEQUENCE)(certificate.value)?;
+        let (_, (_version, serial_number, _signature, issuer, validity, subject)) =
+            context(
+                "TBS certificate",
+                tuple((
+                    opt(der::element(der::context_specific(0))),
+                    der::tagged(der::INTEGER),
+                    der::algorithm,
+                    DistinguishedName::parse,
+                    der::tagged(der::SEQUENCE),
+                    DistinguishedName::parse,
+                )),
+            )(tbs_certificate)?;
+        let (_, (not_before, not_after)) =
+            context("Validity", tuple((der::time, der::time)))(validity)?;
+
+        Ok((
+            rest,
+            Self {
+                raw: certificate.raw,
+                serial_number,
+                issuer,
+                subject,
+                not_before,
+                not_after,
+            },
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct Attribute<'a> {
+    pub oid: Oid<'a>,
+
+    /// DER encoding of each value.
+    pub values: Vec<&'a [u8]>,
+}
+
+impl<'a> Parse<'a> for Attribute<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, attribute) = context("Attribute", der::tagged(der::SEQUENCE))(input)?;
+        let (_, (oid, values)) = tuple((der::oid, der::tagged(der::SET)))(attribute)?;
+        let values = der::all(der::tlv, values)?
+            .into_iter()
+            .map(|t: Tlv<'a>| t.raw)
+            .collect();
+
+        Ok((rest, Self { oid, values }))
+    }
+}
+
+#[derive(Debug)]
+pub enum SignerIdentifier<'a> {
+    IssuerAndSerialNumber {
+        issuer: DistinguishedName<'a>,
+        serial_number: &'a [u8],
+    },
+    SubjectKeyIdentifier(&'a [u8]),
+}
+
+#[derive(Debug)]
+pub enum Countersignature<'a> {
+    /// PKCS#9 countersignature (`1.2.840.113549.1.9.6`).
+    Pkcs9(SignerInfo<'a>),
+
+    /// RFC 3161 timestamp token (`1.3.6.1.4.1.311.3.3.1`).
+    Rfc3161(SignedData<'a>),
+}
+
+impl<'a> Countersignature<'a> {
+    pub fn time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            Self::Pkcs9(ref signer_info) => signer_info.signing_time(),
+            Self::Rfc3161(ref token) => token.timestamp(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SignerInfo<'a> {
+    pub version: u32,
+    pub sid: SignerIdentifier<'a>,
+    pub digest_algorithm: Oid<'a>,
+    pub authenticated_attributes: Vec<Attribute<'a>>,
+    pub digest_encryption_algorithm: Oid<'a>,
+    pub encrypted_digest: &'a [u8],
+    pub unauthenticated_attributes: Vec<Attribute<'a>>,
+    pub countersignatures: Vec<Countersignature<'a>>,
+
+    /// Signatures appended with `1.3.6.1.4.1.311.2.4.1`, typically a SHA-256 signature nested in
+    /// a SHA-1 one.
+    pub nested_signatures: Vec<SignedData<'a>>,
+}
+
+impl<'a> SignerInfo<'a> {
+    fn authenticated_attribute(&self, oid: &str) -> Option<&'a [u8]> {
+        self.authenticated_attributes
+            .iter()
+            .find(|a| a.oid == oid)
+            .and_then(|a| a.values.first().copied())
+    }
+
+    /// Value of the `messageDigest` authenticated attribute.
+    pub fn message_digest(&self) -> Option<&'a [u8]> {
+        let value = self.authenticated_attribute(OID_MESSAGE_DIGEST)?;
+        der::tagged::<nom::error::Error<&[u8]>>(der::OCTET_STRING)(value)
+            .ok()
+            .map(|(_, digest)| digest)
+    }
+
+    /// Value of the `signingTime` authenticated attribute.
+    pub fn signing_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let value = self.authenticated_attribute(OID_SIGNING_TIME)?;
+        der::time::<nom::error::Error<&[u8]>>(value)
+            .ok()
+            .map(|(_, time)| time)
+    }
+}
+
+impl<'a> fmt::Display for SignerInfo<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        match self.sid {
+            SignerIdentifier::IssuerAndSerialNumber {
+                ref issuer,
+                serial_number,
+            } => {
+                write!(f, "{offset}issuer: {}\n", issuer)?;
+                write!(f, "{offset}serial_number: ")?;
+                hex(f, serial_number)?;
+                write!(f, "\n")?;
+            }
+            SignerIdentifier::SubjectKeyIdentifier(ski) => {
+                write!(f, "{offset}subject_key_identifier: ")?;
+                hex(f, ski)?;
+                write!(f, "\n")?;
+            }
+        }
+        write!(f, "{offset}digest_algorithm: {}\n", self.digest_algorithm)?;
+        if let Some(time) = self.signing_time() {
+            write!(f, "{offset}signing_time: {}\n", time)?;
+        }
+        for countersignature in &self.countersignatures {
+            match countersignature.time() {
+                Some(time) => write!(f, "{offset}countersigned at: {}\n", time)?,
+                None => write!(f, "{offset}countersigned\n")?,
+            }
+        }
+        for nested in &self.nested_signatures {
+            write!(f, "{offset}nested_signature:\n{:width$}", nested)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for SignerInfo<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        Self::parse_nested(input, 0)
+    }
+}
+
+impl<'a> SignerInfo<'a> {
+    /// Parses a signer whose signature is nested `depth` levels deep.
+    fn parse_nested<E>(input: &'a [u8], depth: usize) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, signer_info) = context("Signer info", der::tagged(der::SEQUENCE))(input)?;
+        let (
+            _,
+            (
+                version,
+                sid,
+                digest_algorithm,
+                authenticated_attributes,
+                digest_encryption_algorithm,
+                encrypted_digest,
+                unauthenticated_attributes,
+            ),
+        ) = context(
+            "Signer info",
+            tuple((
+                der::small_integer,
+                der::tlv,
+                der::algorithm,
+                opt(der::tagged(der::context_specific(0))),
+                der::algorithm,
+                der::tagged(der::OCTET_STRING),
+                opt(der::tagged(der::context_specific(1))),
+            )),
+        )(signer_info)?;
+
+        let sid = if sid.tag == der::SEQUENCE {
+            let (_, (issuer, serial_number)) =
+                tuple((DistinguishedName::parse, der::tagged(der::INTEGER)))(sid.value)?;
+            SignerIdentifier::IssuerAndSerialNumber {
+                issuer,
+                serial_number,
+            }
+        } else {
+            SignerIdentifier::SubjectKeyIdentifier(sid.value)
+        };
+        let authenticated_attributes = der::all(
+            Attribute::parse,
+            authenticated_attributes.unwrap_or_default(),
+        )?;
+        let unauthenticated_attributes = der::all(
+            Attribute::parse,
+            unauthenticated_attributes.unwrap_or_default(),
+        )?;
+
+        let mut countersignatures = Vec::new();
+        let mut nested_signatures = Vec::new();
+        for attribute in &unauthenticated_attributes {
+            let is_signature = attribute.oid == OID_COUNTERSIGNATURE
+                || attribute.oid == OID_RFC3161_TIMESTAMP
+                || attribute.oid == OID_SPC_NESTED_SIGNATURE;
+            if is_signature && depth >= MAX_NESTING {
+                return Err(nom::Err::Error(E::add_context(
+                    signer_info,
+                    "Signatures are nested too deeply",
+                    E::from_error_kind(signer_info, nom::error::ErrorKind::TooLarge),
+                )));
+            }
+            for value in &attribute.values {
+                if attribute.oid == OID_COUNTERSIGNATURE {
+                    let (_, countersigner) = SignerInfo::parse_nested(value, depth + 1)?;
+                    countersignatures.push(Countersignature::Pkcs9(countersigner));
+                } else if attribute.oid == OID_RFC3161_TIMESTAMP {
+                    let (_, token) = SignedData::parse_nested(value, depth + 1)?;
+                    countersignatures.push(Countersignature::Rfc3161(token));
+                } else if attribute.oid == OID_SPC_NESTED_SIGNATURE {
+                    let (_, nested) = SignedData::parse_nested(value, depth + 1)?;
+                    nested_signatures.push(nested);
+                }
+            }
+        }
+
+        Ok((
+            rest,
+            Self {
+                version,
+                sid,
+                digest_algorithm,
+                authenticated_attributes,
+                digest_encryption_algorithm,
+                encrypted_digest,
+                unauthenticated_attributes,
+                countersignatures,
+                nested_signatures,
+            },
+        ))
+    }
+}
+
+/// `SpcIndirectDataContent`, holding the Authenticode digest of the image.
+#[derive(Debug)]
+pub struct SpcIndirectDataContent<'a> {
+    pub digest_algorithm: Oid<'a>,
+    pub digest: &'a [u8],
+}
+
+impl<'a> Parse<'a> for SpcIndirectDataContent<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, indirect_data) =
+            context("SPC indirect data", der::tagged(der::SEQUENCE))(input)?;
+        let (_, (_data, digest_info)) =
+            tuple((der::tlv, der::tagged(der::SEQUENCE)))(indirect_data)?;
+        let (_, (digest_algorithm, digest)) = context(
+            "Digest info",
+            tuple((der::algorithm, der::tagged(der::OCTET_STRING))),
+        )(digest_info)?;
+
+        Ok((
+            rest,
+            Self {
+                digest_algorithm,
+                digest,
+            },
+        ))
+    }
+}
+
+/// PKCS#7 `SignedData`, as found in the certificate table or in RFC 3161 timestamp tokens.
+#[derive(Debug)]
+pub struct SignedData<'a> {
+    pub version: u32,
+    pub digest_algorithms: Vec<Oid<'a>>,
+    pub content_type: Oid<'a>,
+
+    /// Value of the signed content, which is what the signers' `messageDigest` is computed on.
+    pub content: &'a [u8],
+    pub indirect_data: Option<SpcIndirectDataContent<'a>>,
+    pub certificates: Vec<Certificate<'a>>,
+    pub signer_infos: Vec<SignerInfo<'a>>,
+}
+
+impl<'a> SignedData<'a> {
+    /// Generation time of a RFC 3161 timestamp token.
+    pub fn timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        if self.content_type != OID_TST_INFO {
+            return None;
+        }
+        let (_, tst_info) =
+            der::tagged::<nom::error::Error<&[u8]>>(der::SEQUENCE)(self.content).ok()?;
+        let (_, (_version, _policy, _message_imprint, _serial_number, gen_time)) =
+            tuple::<_, _, nom::error::Error<&[u8]>, _>((
+                der::small_integer,
+                der::oid,
+                der::tlv,
+                der::tlv,
+                der::time,
+            ))(tst_info)
+            .ok()?;
+        Some(gen_time)
+    }
+
+    /// Certificate of the given signer, looked up by issuer and serial number.
+    pub fn signer_certificate(&self, signer_info: &SignerInfo<'a>) -> Option<&Certificate<'a>> {
+        match signer_info.sid {
+            SignerIdentifier::IssuerAndSerialNumber {
+                ref issuer,
+                serial_number,
+            } => self
+                .certificates
+                .iter()
+                .find(|c| c.issuer == *issuer && c.serial_number == serial_number),
+            SignerIdentifier::SubjectKeyIdentifier(_) => None,
+        }
+    }
+
+    /// Certificates from the signer up to the last issuer found in the signature.
+    pub fn certificate_chain(&self, signer_info: &SignerInfo<'a>) -> Vec<&Certificate<'a>> {
+        let mut chain = Vec::new();
+        let mut current = self.signer_certificate(signer_info);
+        while let Some(certificate) = current {
+            chain.push(certificate);
+            if certificate.is_self_signed() || chain.len() > self.certificates.len() {
+                break;
+            }
+            current = self
+                .certificates
+                .iter()
+                .find(|c| c.subject == certificate.issuer);
+        }
+        chain
+    }
+
+    /// Checks that the image digest matches the signed one, and that every signer signed this
+    /// content.
+    pub fn verify_digest(&self, pe: &Pe<'_>) -> bool {
+        let indirect_data = match self.indirect_data {
+            Some(ref indirect_data) => indirect_data,
+            None => return false,
+        };
+        let algorithm = match HashAlgorithm::from_oid(&indirect_data.digest_algorithm) {
+            Some(algorithm) => algorithm,
+            None => return false,
+        };
+        if pe.authentihash(algorithm) != indirect_data.digest {
+            return false;
+        }
+
+        !self.signer_infos.is_empty()
+            && self.signer_infos.iter().all(|signer_info| {
+                match (
+                    HashAlgorithm::from_oid(&signer_info.digest_algorithm),
+                    signer_info.message_digest(),
+                ) {
+                    (Some(algorithm), Some(digest)) => algorithm.digest(self.content) == digest,
+                    _ => false,
+                }
+            })
+    }
+
+    /// Checks that the certificate chain of every signer leads to a certificate of
+    /// `trust_store`, by comparing issuer and subject names only.
+    ///
+    /// No signature is verified, neither of the certificates nor of the signers'
+    /// `encrypted_digest`: a certificate naming a trusted issuer passes even when forged. This
+    /// is a hint for reporting, not a trust decision.
+    pub fn chains_to_by_name(&self, trust_store: &[Certificate<'_>]) -> bool {
+        !self.signer_infos.is_empty()
+            && self.signer_infos.iter().all(|signer_info| {
+                self.certificate_chain(signer_info).iter().any(|c| {
+                    trust_store
+                        .iter()
+                        .any(|anchor| anchor.raw == c.raw || anchor.subject.raw == c.issuer.raw)
+                })
+            })
+    }
+}
+
+impl<'a> fmt::Display for SignedData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}content_type: {}\n", self.content_type)?;
+        if let Some(ref indirect_data) = self.indirect_data {
+            write!(
+                f,
+                "{offset}digest_algorithm: {}\n",
+                indirect_data.digest_algorithm
+            )?;
+            write!(f, "{offset}digest: ")?;
+            hex(f, indirect_data.digest)?;
+            write!(f, "\n")?;
+        }
+        write!(f, "{offset}certificates:\n")?;
+        for certificate in &self.certificates {
+            write!(f, "{:width$}\n", certificate)?;
+        }
+        write!(f, "{offset}signer_infos:\n")?;
+        for signer_info in &self.signer_infos {
+            write!(f, "{:width$}\n", signer_info)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for SignedData<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        Self::parse_nested(input, 0)
+    }
+}
+
+impl<'a> SignedData<'a> {
+    /// Parses a signature nested `depth` levels deep.
+    fn parse_nested<E>(input: &'a [u8], depth: usize) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        // ContentInfo wrapping the SignedData
+        let (rest, content_info) = context("Content info", der::tagged(der::SEQUENCE))(input)?;
+        let (_, (content_type, content)) =
+            tuple((der::oid, der::tagged(der::context_specific(0))))(content_info)?;
+        if content_type != OID_SIGNED_DATA {
+            return Err(nom::Err::Error(E::add_context(
+                content_info,
+                "Content is not signed data",
+                E::from_error_kind(content_info, nom::error::ErrorKind::Verify),
+            )));
+        }
+
+        let (_, signed_data) = context("Signed data", der::tagged(der::SEQUENCE))(content)?;
+        let (
+            _,
+            (version, digest_algorithms, encapsulated_content, certificates, _crls, signer_infos),
+        ) = context(
+            "Signed data",
+            tuple((
+                der::small_integer,
+                der::tagged(der::SET),
+                der::tagged(der::SEQUENCE),
+                opt(der::tagged(der::context_specific(0))),
+                opt(der::tagged(der::context_specific(1))),
+                der::tagged(der::SET),
+            )),
+        )(signed_data)?;
+
+        let digest_algorithms = der::all(der::algorithm, digest_algorithms)?;
+        let (_, (content_type, content)) =
+            tuple((der::oid, opt(der::tagged(der::context_specific(0)))))(encapsulated_content)?;
+        let content = match content {
+            Some(content) => der::tlv(content)?.1,
+            None => Tlv {
+                tag: der::SEQUENCE,
+                value: &[],
+                raw: &[],
+            },
+        };
+        let indirect_data = if content_type == OID_SPC_INDIRECT_DATA {
+            Some(SpcIndirectDataContent::parse(content.raw)?.1)
+        } else {
+            None
+        };
+        let certificates = der::all(Certificate::parse, certificates.unwrap_or_default())?;
+        let signer_infos = der::all(|input| SignerInfo::parse_nested(input, depth), signer_infos)?;
+
+        Ok((
+            rest,
+            Self {
+                version,
+                digest_algorithms,
+                content_type,
+                content: content.value,
+                indirect_data,
+                certificates,
+                signer_infos,
+            },
+        ))
+    }
+}
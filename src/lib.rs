@@ -12,6 +12,10 @@ pub use enums::*;
 pub mod structures;
 pub use structures::*;
 
+pub mod authenticode;
+
+pub mod uefi;
+
 mod parsers;
 
 pub trait NomError<'a>:
@@ -32,16 +36,21 @@ impl<'a> exe::Section for SectionHeader<'a> {
     fn get_flags(&self) -> u32 {
         let mut flags = 0u32;
 
-        if self.characteristics.memory_read {
+        if self
+            .characteristics
+            .contains(SectionCharacteristics::MEMORY_READ)
+        {
             flags |= 4;
         }
-        if self.characteristics.memory_write {
+        if self
+            .characteristics
+            .contains(SectionCharacteristics::MEMORY_WRITE)
+        {
             flags |= 2;
         }
-        if self.characteristics.contains_code {
-            flags |= 1;
-        }
-        if self.characteristics.memory_execute {
+        if self.characteristics.intersects(
+            SectionCharacteristics::CONTAINS_CODE | SectionCharacteristics::MEMORY_EXECUTE,
+        ) {
             flags |= 1;
         }
 
@@ -57,6 +66,15 @@ impl<'a> exe::Section for SectionHeader<'a> {
     }
 }
 
+impl<'a> Pe<'a> {
+    fn optional_header_bits(&self) -> usize {
+        match self.pe_header.optional_header {
+            OptionalHeader::I386(_) => 32,
+            OptionalHeader::AMD64(_) => 64,
+        }
+    }
+}
+
 impl<'a> exe::Exe<'a> for Pe<'a> {
     type Item = SectionHeader<'a>;
 
@@ -69,15 +87,8 @@ impl<'a> exe::Exe<'a> for Pe<'a> {
     }
 
     fn get_section_name_at(&self, idx: usize) -> Option<&str> {
-        match self.pe_header.sections.iter().nth(idx) {
-            Some(s) => match s.name {
-                Name::String(name) => Some(name),
-                Name::Rva(_rva) => {
-                    todo!("Get long section name");
-                }
-            },
-            None => None,
-        }
+        let section = self.pe_header.sections.iter().nth(idx)?;
+        self.section_name(section)
     }
 
     fn get_data(&self, start: usize, len: usize) -> &[u8] {
@@ -85,16 +96,46 @@ impl<'a> exe::Exe<'a> for Pe<'a> {
     }
 
     fn get_info(&self) -> exe::Info {
+        let (arch, bits) = match &self.pe_header.file_header.machine {
+            FileMachine::MachineI386 | FileMachine::MachineCHPEX86 => ("x86", 32),
+            FileMachine::MachineAMD64 => ("x86", 64),
+            FileMachine::MachineIA64 => ("ia", 64),
+            FileMachine::MachineARM | FileMachine::MachineARMNT | FileMachine::MachineThumb => {
+                ("arm", 32)
+            }
+            FileMachine::MachineARM64
+            | FileMachine::MachineARM64EC
+            | FileMachine::MachineARM64X => ("arm", 64),
+            FileMachine::MachineRISCV32 => ("riscv", 32),
+            FileMachine::MachineRISCV64 => ("riscv", 64),
+            FileMachine::MachineRISCV128 => ("riscv", 128),
+            FileMachine::MachineLoongArch32 => ("loongarch", 32),
+            FileMachine::MachineLoongArch64 => ("loongarch", 64),
+            FileMachine::MachineMIPS16
+            | FileMachine::MachineMIPSFPU
+            | FileMachine::MachineMIPSFPU16
+            | FileMachine::MachineR3000BE
+            | FileMachine::MachineR3000
+            | FileMachine::MachineWCEMIPSV2 => ("mips", 32),
+            FileMachine::MachineR4000 | FileMachine::MachineR10000 => ("mips", 64),
+            FileMachine::MachinePowerPC | FileMachine::MachinePowerPCFP => ("ppc", 32),
+            FileMachine::MachineSH3
+            | FileMachine::MachineSH3DSP
+            | FileMachine::MachineSH4
+            | FileMachine::MachineSH5 => ("sh", 32),
+            FileMachine::MachineAlpha => ("alpha", 32),
+            FileMachine::MachineAlpha64 => ("alpha", 64),
+            FileMachine::MachineAM33 => ("am33", 32),
+            FileMachine::MachineM32R => ("m32r", 32),
+            // EBC is natively sized, so go with the width of the optional header
+            FileMachine::MachineEBC => ("ebc", self.optional_header_bits()),
+            FileMachine::Unknown(_) => ("unknown", self.optional_header_bits()),
+        };
+
         exe::Info {
             os: String::from("windows"),
-            arch: String::from(match &self.pe_header.file_header.machine {
-                FileMachine::MachineIA64 => "ia",
-                FileMachine::MachineI386 | FileMachine::MachineAMD64 => "x86",
-            }),
-            bits: match &self.pe_header.file_header.machine {
-                FileMachine::MachineI386 => 32,
-                FileMachine::MachineIA64 | FileMachine::MachineAMD64 => 64,
-            },
+            arch: String::from(arch),
+            bits,
         }
     }
 
@@ -145,3 +186,77 @@ pub extern "C" fn rs_pe_parse<'a>(i: *const u8, len: size_t) -> *const c_void {
 //     rs_pe_free_section,
 //     rs_pe_free_exe
 // );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exe::Exe;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    #[test]
+    fn info() {
+        let cases = [
+            (0x014c, "x86", 32),
+            (0x3a64, "x86", 32),
+            (0x8664, "x86", 64),
+            (0x0200, "ia", 64),
+            (0x01c0, "arm", 32),
+            (0x01c2, "arm", 32),
+            (0x01c4, "arm", 32),
+            (0xaa64, "arm", 64),
+            (0xa641, "arm", 64),
+            (0xa64e, "arm", 64),
+            (0x5032, "riscv", 32),
+            (0x5064, "riscv", 64),
+            (0x5128, "riscv", 128),
+            (0x6232, "loongarch", 32),
+            (0x6264, "loongarch", 64),
+            (0x0266, "mips", 32),
+            (0x0366, "mips", 32),
+            (0x0466, "mips", 32),
+            (0x0160, "mips", 32),
+            (0x0162, "mips", 32),
+            (0x0169, "mips", 32),
+            (0x0166, "mips", 64),
+            (0x0168, "mips", 64),
+            (0x01f0, "ppc", 32),
+            (0x01f1, "ppc", 32),
+            (0x01a2, "sh", 32),
+            (0x01a3, "sh", 32),
+            (0x01a6, "sh", 32),
+            (0x01a8, "sh", 32),
+            (0x0184, "alpha", 32),
+            (0x0284, "alpha", 64),
+            (0x01d3, "am33", 32),
+            (0x9041, "m32r", 32),
+            // Natively sized or unknown machines follow the optional header
+            (0x0ebc, "ebc", 64),
+            (0x1234, "unknown", 64),
+        ];
+        let mut file = structures::tests::image(0x40);
+        for (machine, arch, bits) in cases {
+            file[0x44..0x46].copy_from_slice(&u16::to_le_bytes(machine));
+            let (_, pe) = <Pe as Parse>::parse::<Error>(&file).unwrap();
+            let info = pe.get_info();
+            assert_eq!(info.os, "windows");
+            assert_eq!(
+                (info.arch.as_str(), info.bits),
+                (arch, bits),
+                "0x{:x}",
+                machine
+            );
+        }
+
+        // PE32 optional header, its data directories starting 16 bytes earlier
+        file[0x58..0x5a].copy_from_slice(&0x10bu16.to_le_bytes());
+        file[0x58 + 92..0x58 + 96].copy_from_slice(&16u32.to_le_bytes());
+        file[0x58 + 108..0x58 + 112].fill(0);
+        for (machine, arch) in [(0x0ebc, "ebc"), (0x1234, "unknown")] {
+            file[0x44..0x46].copy_from_slice(&u16::to_le_bytes(machine));
+            let (_, pe) = <Pe as Parse>::parse::<Error>(&file).unwrap();
+            let info = pe.get_info();
+            assert_eq!((info.arch.as_str(), info.bits), (arch, 32));
+        }
+    }
+}
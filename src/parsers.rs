@@ -1,6 +1,11 @@
+use nom::bytes::complete::{tag, take, take_until};
+use nom::combinator::map_opt;
 use nom::error::ParseError;
+use nom::sequence::terminated;
 use nom::Parser;
 
+use crate::NomError;
+
 pub(crate) fn count_fixed<I, O, E, F, const N: usize>(
     mut f: F,
 ) -> impl FnMut(I) -> nom::IResult<I, [O; N], E>
@@ -24,3 +29,21 @@ where
         Ok((input, unsafe { MaybeUninit::array_assume_init(array) }))
     }
 }
+
+/// Returns `input` from `offset`, failing if it is out of bounds.
+pub(crate) fn at<'a, E>(input: &'a [u8], offset: usize) -> Result<&'a [u8], nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    take(offset)(input).map(|(rest, _)| rest)
+}
+
+/// Parses a nul-terminated UTF8 string, the nul being consumed.
+pub(crate) fn nul_terminated_str<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], &'a str, E>
+where
+    E: NomError<'a>,
+{
+    map_opt(terminated(take_until(&b"\0"[..]), tag(b"\0")), |s| {
+        std::str::from_utf8(s).ok()
+    })(input)
+}
@@ -1,22 +1,74 @@
 use std::fmt;
 
-use crate::enums::ImageDataDirectoryIndex;
+use crate::enums::{FileMachine, ImageDataDirectoryIndex};
+use crate::parsers::at;
 use crate::{NomError, Parse};
 
 use nom::bytes::complete::take;
 use nom::combinator::verify;
 use nom::error::context;
-use nom::multi::many1;
-use nom::number::complete::{le_u32, le_u64};
+use nom::multi::{count, many1};
+use nom::number::complete::{le_u16, le_u32, le_u64};
+
+mod archive;
+pub use archive::{
+    Archive, ArchiveMember, ArchiveMemberContent, ArchiveMemberHeader, FirstLinkerMember,
+    ImportNameType, ImportObject, ImportType, SecondLinkerMember, IMAGE_ARCHIVE_START,
+};
+
+mod coff;
+pub use coff::{
+    Amd64RelocationType, Arm64RelocationType, BigObjHeader, CoffAuxSymbol, CoffHeader,
+    CoffLineNumber, CoffObject, CoffRelocation, CoffSymbol, ComdatSelection, FunctionLines,
+    I386RelocationType, RelocationType, StorageClass, StringTable, SymbolTable, BIGOBJ_CLASS_ID,
+    BIGOBJ_SYMBOL_SIZE, IMAGE_SYM_ABSOLUTE, IMAGE_SYM_DEBUG, IMAGE_SYM_UNDEFINED,
+    IMAGE_WEAK_EXTERN_SEARCH_ALIAS, IMAGE_WEAK_EXTERN_SEARCH_LIBRARY,
+    IMAGE_WEAK_EXTERN_SEARCH_NOLIBRARY, SYMBOL_SIZE,
+};
 
 mod dos;
 pub use dos::DosHeader;
 
+mod guid;
+pub use guid::Guid;
+
+mod le;
+pub use le::{
+    LeEntryKind, LeEntryPoint, LeExecutable, LeFixup, LeFixupTarget, LeHeader, LeObject, LePage,
+};
+
+mod mz;
+pub use mz::{MzExecutable, MzRelocation};
+
+mod ne;
+pub use ne::{
+    NeEntryPoint, NeExecutable, NeHeader, NeName, NeResource, NeResourceId, NeResourceTable,
+    NeResourceType, NeSegment,
+};
+
+mod rich;
+pub use rich::{RichEntry, RichHeader};
+
 mod file_header;
 pub use file_header::FileHeader;
 
 mod data_directory;
-pub use data_directory::{DataDirectory, ImportByName, ImportDescriptor};
+pub use data_directory::{
+    Arm64ECCodeRangeEntryPoint, Arm64ECMetadata, Arm64ECRedirectionEntry, Arm64EpilogScope,
+    Arm64Function, Arm64KernelImportCallTransfer, Arm64PackedUnwindData, Arm64RuntimeFunction,
+    Arm64UnwindInfo, Arm64XData, Arm64XFixup, Arm64XFixupKind, BddInfo, BddNode, CertificateTable,
+    ChpeMetadata, ChpeRangeEntry, ChpeRangeType, DataDirectory, DynamicRelocation,
+    DynamicRelocationFixups, DynamicRelocationTable, EpilogueHeader, Export, ExportDirectory,
+    ExportTable, FunctionOverride, FunctionOverrideRelocation, FunctionOverrides, ImportByName,
+    ImportControlTransfer, ImportDescriptor, IndirControlTransfer, LoadConfigDirectory,
+    SwitchableBranch, WinCertificate, IMAGE_DYNAMIC_RELOCATION_ARM64X,
+    IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER,
+    IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE, IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH,
+};
 
 mod optional_header;
 pub use optional_header::{OptionalHeader, OptionalHeader32, OptionalHeader64};
@@ -27,9 +79,13 @@ pub use section::SectionHeader;
 mod pe;
 pub use pe::PeHeader;
 
+mod te;
+pub use te::{TeHeader, TeImage};
+
 #[derive(Debug)]
 pub enum Name<'a> {
     String(&'a str),
+    /// RVA of the name, or its string table offset for long section names.
     Rva(u64),
 }
 
@@ -49,10 +105,148 @@ enum ImportSymbol<'a> {
 }
 
 pub struct Pe<'a> {
+    pub(super) file: &'a [u8],
     pub(super) data: &'a [u8],
     pub(super) dos_header: DosHeader,
+    pub(super) dos_stub: &'a [u8],
+    pub(super) rich_header: Option<RichHeader<'a>>,
     pub(super) pe_header: PeHeader<'a>,
+    export_table: Option<ExportTable<'a>>,
     import_table: Vec<(&'a str, Vec<ImportSymbol<'a>>)>,
+    exception_table: Vec<Arm64Function<'a>>,
+    certificate_table: Option<CertificateTable<'a>>,
+    load_config: Option<LoadConfigDirectory>,
+    chpe_metadata: Option<ChpeMetadata>,
+    dynamic_relocation_table: Option<DynamicRelocationTable<'a>>,
+    symbol_table: Option<SymbolTable<'a>>,
+}
+
+impl<'a> Pe<'a> {
+    pub fn dos_header(&self) -> &DosHeader {
+        &self.dos_header
+    }
+
+    /// Bytes between the DOS header and the PE signature: the MS-DOS program, followed by the
+    /// Rich header if any.
+    pub fn dos_stub(&self) -> &'a [u8] {
+        self.dos_stub
+    }
+
+    pub fn rich_header(&self) -> Option<&RichHeader<'a>> {
+        self.rich_header.as_ref()
+    }
+
+    pub fn export_table(&self) -> Option<&ExportTable<'a>> {
+        self.export_table.as_ref()
+    }
+
+    /// Functions described by the exception directory (only decoded for ARM64 images).
+    pub fn exception_table(&self) -> &[Arm64Function<'a>] {
+        &self.exception_table[..]
+    }
+
+    pub fn certificate_table(&self) -> Option<&CertificateTable<'a>> {
+        self.certificate_table.as_ref()
+    }
+
+    pub fn load_config(&self) -> Option<&LoadConfigDirectory> {
+        self.load_config.as_ref()
+    }
+
+    /// Hybrid metadata of ARM64EC and ARM64X images.
+    pub fn chpe_metadata(&self) -> Option<&ChpeMetadata> {
+        self.chpe_metadata.as_ref()
+    }
+
+    pub fn dynamic_relocation_table(&self) -> Option<&DynamicRelocationTable<'a>> {
+        self.dynamic_relocation_table.as_ref()
+    }
+
+    /// Whether this is an ARM64X image, whose native ARM64 view is the file itself while x64
+    /// code gets the view of `x64_view`.
+    pub fn is_arm64x(&self) -> bool {
+        self.dynamic_relocation_table
+            .as_ref()
+            .map_or(false, |table| table.arm64x_fixups().next().is_some())
+    }
+
+    /// Copy of the file with the ARM64X fixups applied, which parses as the x64 view of the
+    /// image: x64 headers, exports and load config. Fixups outside of the file contents are
+    /// skipped.
+    pub fn x64_view(&self) -> Option<Vec<u8>> {
+        if !self.is_arm64x() {
+            return None;
+        }
+        let mut file = self.file.to_vec();
+        for fixup in self.dynamic_relocation_table.as_ref()?.arm64x_fixups() {
+            if let Some(offset) = rva_to_offset(&self.pe_header, fixup.rva as u64) {
+                if let Some(data) = file.get_mut(offset..) {
+                    fixup.apply(data);
+                }
+            }
+        }
+        Some(file)
+    }
+
+    /// COFF symbol table, deprecated for images but still emitted by MinGW.
+    pub fn symbol_table(&self) -> Option<&SymbolTable<'a>> {
+        self.symbol_table.as_ref()
+    }
+
+    /// Name of `section`, long names being looked up in the string table.
+    pub fn section_name(&self, section: &SectionHeader<'a>) -> Option<&'a str> {
+        let string_table = self
+            .symbol_table
+            .as_ref()
+            .and_then(|symbol_table| symbol_table.string_table.as_ref());
+        section.resolve_name(string_table)
+    }
+
+    /// COFF relocations of `section`, which images only have when linked incrementally or
+    /// with relocations kept.
+    pub fn section_relocations(&self, section: &SectionHeader<'a>) -> Option<Vec<CoffRelocation>> {
+        coff::section_relocations::<nom::error::Error<&'a [u8]>>(
+            self.file,
+            section,
+            &self.pe_header.file_header.machine,
+        )
+        .ok()
+    }
+
+    /// COFF line numbers of `section`, deprecated but still emitted by old toolchains.
+    pub fn section_line_numbers(&self, section: &SectionHeader<'a>) -> Option<Vec<CoffLineNumber>> {
+        coff::section_line_numbers::<nom::error::Error<&'a [u8]>>(self.file, section).ok()
+    }
+
+    /// Computes the image checksum the way `CheckSumMappedFile` does.
+    pub fn compute_checksum(&self) -> u32 {
+        let mut words = self.file.chunks_exact(2);
+        let mut sum = 0u32;
+        for word in &mut words {
+            sum += u16::from_le_bytes([word[0], word[1]]) as u32;
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        if let [last] = words.remainder() {
+            sum += *last as u32;
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+
+        // The stored checksum was summed too, take its halves back out with their carries. This
+        // holds wherever the field lies, even when an odd `e_lfanew` misaligns it.
+        let mut sum = sum as u16;
+        let check_sum = self.pe_header.optional_header.check_sum();
+        for half in [check_sum as u16, (check_sum >> 16) as u16] {
+            sum = sum.wrapping_sub((sum < half) as u16).wrapping_sub(half);
+        }
+
+        (sum as u32).wrapping_add(self.file.len() as u32)
+    }
+
+    /// Whether `check_sum` matches the computed checksum. Loaders only enforce it for drivers
+    /// and boot components, so user mode images often just leave it to 0.
+    pub fn checksum_valid(&self) -> bool {
+        self.pe_header.optional_header.check_sum() == self.compute_checksum()
+    }
 }
 
 fn get_section_containing_rva<'a, 'b, E>(
@@ -73,6 +267,23 @@ where
     )))
 }
 
+/// File offset of `rva`, if it is backed by the file.
+fn rva_to_offset(pe_header: &PeHeader, rva: u64) -> Option<usize> {
+    if rva < pe_header.optional_header.size_of_headers() as u64 {
+        return Some(rva as usize);
+    }
+    let section = pe_header
+        .sections
+        .iter()
+        .find(|section| section.contains(rva))?;
+    let offset = section.offset(rva);
+    if offset < section.size_of_raw_data as usize {
+        Some(section.pointer_to_raw_data as usize + offset)
+    } else {
+        None
+    }
+}
+
 fn get_data<'a, 'b, E>(
     pe_header: &'b PeHeader<'a>,
     data: &'a [u8],
@@ -85,13 +296,51 @@ where
     let section = get_section_containing_rva(pe_header, rva)?;
     let offset = section.offset(rva);
 
+    // Raw data may be truncated by the end of the file, or not cover `size`
+    let start = (section.pointer_to_raw_data as usize).min(data.len());
     let section_data =
-        &data[section.pointer_to_raw_data as usize..][..section.size_of_raw_data as usize];
+        &data[start..][..(section.size_of_raw_data as usize).min(data.len() - start)];
+    let section_data = at(section_data, offset)?;
 
     if let Some(size) = size {
-        Ok(&section_data[offset..][..size as usize])
+        take(size as usize)(section_data).map(|(_, data)| data)
     } else {
-        Ok(&section_data[offset..])
+        Ok(section_data)
+    }
+}
+
+/// Decodes the unwind data of `runtime_function`, which is kept raw when it cannot be decoded.
+fn get_arm64_function<'a, E>(
+    pe_header: &PeHeader<'a>,
+    data: &'a [u8],
+    runtime_function: Arm64RuntimeFunction,
+) -> Arm64Function<'a>
+where
+    E: NomError<'a>,
+{
+    let unwind_info = match runtime_function.packed() {
+        Some(packed) => Arm64UnwindInfo::Packed(packed),
+        None => runtime_function
+            .xdata_rva()
+            .and_then(|rva| {
+                let data = get_data::<E>(pe_header, data, rva as u64, None).ok()?;
+                Arm64XData::parse::<E>(data).ok()
+            })
+            .map_or(
+                Arm64UnwindInfo::Raw(runtime_function.unwind_data),
+                |(_, xdata)| Arm64UnwindInfo::Unpacked(xdata),
+            ),
+    };
+    let function_length = match unwind_info {
+        Arm64UnwindInfo::Packed(ref packed) => packed.function_length,
+        Arm64UnwindInfo::Unpacked(ref xdata) => xdata.function_length,
+        Arm64UnwindInfo::Raw(_) => 0,
+    };
+
+    Arm64Function {
+        begin_address: runtime_function.begin_address,
+        function_length,
+        unwind_info,
     }
 }
 
@@ -124,15 +373,174 @@ where
     Ok(string)
 }
 
+/// ARM64EC metadata at `rva` along with the tables it points to.
+fn parse_chpe_metadata<'a, 'b, E>(
+    pe_header: &'b PeHeader<'a>,
+    input: &'a [u8],
+    rva: u64,
+) -> Result<ChpeMetadata, nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    let data = get_data(pe_header, input, rva, None)?;
+    let (_, metadata) = Arm64ECMetadata::parse(data)?;
+
+    let table = |rva: u32| get_data(pe_header, input, rva as u64, None);
+    let (_, code_map) = context(
+        "CHPE code map",
+        count(ChpeRangeEntry::parse, metadata.code_map_count as usize),
+    )(table(metadata.code_map)?)?;
+    let code_ranges_to_entry_points = if metadata.code_ranges_to_entry_points_count != 0 {
+        context(
+            "ARM64EC code ranges to entry points",
+            count(
+                Arm64ECCodeRangeEntryPoint::parse,
+                metadata.code_ranges_to_entry_points_count as usize,
+            ),
+        )(table(metadata.code_ranges_to_entry_points)?)?
+        .1
+    } else {
+        Vec::new()
+    };
+    let redirection_metadata = if metadata.redirection_metadata_count != 0 {
+        context(
+            "ARM64EC redirection metadata",
+            count(
+                Arm64ECRedirectionEntry::parse,
+                metadata.redirection_metadata_count as usize,
+            ),
+        )(table(metadata.redirection_metadata)?)?
+        .1
+    } else {
+        Vec::new()
+    };
+    // The auxiliary IAT mirrors the regular one, entry for entry
+    let auxiliary_iat = match pe_header
+        .optional_header
+        .get_data_directory(ImageDataDirectoryIndex::EntryIat)
+    {
+        Some(iat) if metadata.auxiliary_iat != 0 => {
+            context(
+                "ARM64EC auxiliary IAT",
+                count(le_u64, iat.size as usize / 8),
+            )(table(metadata.auxiliary_iat)?)?
+            .1
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(ChpeMetadata {
+        metadata,
+        code_map,
+        code_ranges_to_entry_points,
+        redirection_metadata,
+        auxiliary_iat,
+    })
+}
+
+/// Export table described by `data_dir`. Name pointers and forwarders that do not resolve to a
+/// string are skipped, the rest of the table being usable without them.
+fn parse_export_table<'a, 'b, E>(
+    pe_header: &'b PeHeader<'a>,
+    input: &'a [u8],
+    data_dir: &'b DataDirectory,
+) -> Result<ExportTable<'a>, nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    let data = get_data(
+        pe_header,
+        input,
+        data_dir.virtual_address as u64,
+        Some(data_dir.size as u64),
+    )?;
+    let (_, directory) = ExportDirectory::parse(data)?;
+    let name = get_string(pe_header, input, directory.name as u64)?;
+
+    let number_of_functions = directory.number_of_functions as usize;
+    let functions_data = get_data(
+        pe_header,
+        input,
+        directory.address_of_functions as u64,
+        None,
+    )?;
+    let (_, functions) =
+        context("Export address table", count(le_u32, number_of_functions))(functions_data)?;
+
+    let mut names = vec![None; number_of_functions];
+    if directory.number_of_names != 0 {
+        let number_of_names = directory.number_of_names as usize;
+        let names_data = get_data(pe_header, input, directory.address_of_names as u64, None)?;
+        let (_, name_rvas) =
+            context("Export name pointers", count(le_u32, number_of_names))(names_data)?;
+        let ordinals_data = get_data(
+            pe_header,
+            input,
+            directory.address_of_name_ordinals as u64,
+            None,
+        )?;
+        let (_, name_ordinals) =
+            context("Export ordinals", count(le_u16, number_of_names))(ordinals_data)?;
+        for (rva, ordinal) in name_rvas.iter().zip(name_ordinals) {
+            if let Some(slot) = names.get_mut(ordinal as usize) {
+                *slot = get_string::<E>(pe_header, input, *rva as u64).ok();
+            }
+        }
+    }
+
+    let directory_end = data_dir.virtual_address as u64 + data_dir.size as u64;
+    let mut exports = Vec::with_capacity(number_of_functions);
+    for (idx, (address, name)) in functions.into_iter().zip(names).enumerate() {
+        if address == 0 {
+            continue;
+        }
+        // Forwarders point inside the export directory instead of at code or data
+        let forwarder = if address >= data_dir.virtual_address && (address as u64) < directory_end {
+            match get_string::<E>(pe_header, input, address as u64) {
+                Ok(forwarder) => Some(forwarder),
+                Err(_) => continue,
+            }
+        } else {
+            None
+        };
+        exports.push(Export {
+            ordinal: directory.base.wrapping_add(idx as u32),
+            name,
+            address,
+            forwarder,
+        });
+    }
+
+    Ok(ExportTable {
+        directory,
+        name,
+        exports,
+    })
+}
+
 impl<'a> Parse<'a> for Pe<'a> {
     fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
     where
         E: NomError<'a>,
     {
         let (_, dos_header) = DosHeader::parse(input)?;
-        let (_, pe_header) = PeHeader::parse(&input[dos_header.e_lfanew as usize..])?;
+        let (pe_data, dos_data) = context("DOS stub", take(dos_header.e_lfanew as usize))(input)?;
+        let (_, pe_header) = PeHeader::parse(pe_data)?;
+        // Tiny images overlap the PE header with the DOS header, leaving no room for a stub
+        let dos_stub = dos_data.get(DosHeader::size()..).unwrap_or_default();
+        let rich_header = if dos_stub.is_empty() {
+            None
+        } else {
+            RichHeader::parse::<E>(dos_data)
+                .ok()
+                .map(|(_, rich_header)| rich_header)
+        };
 
         // ImageDataDirectoryIndex::EntryExport
+        let export_table = pe_header
+            .optional_header
+            .get_data_directory(ImageDataDirectoryIndex::EntryExport)
+            .and_then(|data_dir| parse_export_table::<E>(&pe_header, input, data_dir).ok());
 
         // ImageDataDirectoryIndex::EntryImport
         let import_table = if let Some(data_dir) = pe_header
@@ -215,8 +623,50 @@ impl<'a> Parse<'a> for Pe<'a> {
         // ImageDataDirectoryIndex::EntryResource
 
         // ImageDataDirectoryIndex::EntryException
+        let exception_table = match (
+            &pe_header.file_header.machine,
+            pe_header
+                .optional_header
+                .get_data_directory(ImageDataDirectoryIndex::EntryException),
+        ) {
+            (FileMachine::MachineARM64, Some(data_dir)) => get_data::<E>(
+                &pe_header,
+                input,
+                data_dir.virtual_address as u64,
+                Some(data_dir.size as u64),
+            )
+            .and_then(|data| {
+                context(
+                    "ARM64 exception table",
+                    count(Arm64RuntimeFunction::parse, data.len() / 8),
+                )(data)
+            })
+            .map(|(_, runtime_functions)| {
+                runtime_functions
+                    .into_iter()
+                    .map(|runtime_function| {
+                        get_arm64_function::<E>(&pe_header, input, runtime_function)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+            _ => Vec::new(),
+        };
 
         // ImageDataDirectoryIndex::EntrySecurity
+        // Unlike every other directory, `virtual_address` is a file offset here. The table is
+        // not part of the image, so a truncated or malformed one is just ignored.
+        let certificate_table = pe_header
+            .optional_header
+            .get_data_directory(ImageDataDirectoryIndex::EntrySecurity)
+            .and_then(|data_dir| {
+                CertificateTable::parse_at::<E>(
+                    input,
+                    data_dir.virtual_address as usize,
+                    data_dir.size as usize,
+                )
+                .ok()
+            });
 
         // ImageDataDirectoryIndex::EntryBasereloc
 
@@ -229,6 +679,52 @@ impl<'a> Parse<'a> for Pe<'a> {
         // ImageDataDirectoryIndex::EntryTls
 
         // ImageDataDirectoryIndex::EntryLoadConfig
+        let is_64 = matches!(pe_header.optional_header, OptionalHeader::AMD64(_));
+        // Load config, CHPE metadata and dynamic relocations only describe the image, a broken
+        // one leaves the rest of it usable
+        let load_config = pe_header
+            .optional_header
+            .get_data_directory(ImageDataDirectoryIndex::EntryLoadConfig)
+            .and_then(|data_dir| {
+                // The directory size is not reliable, the structure has its own
+                let data =
+                    get_data::<E>(&pe_header, input, data_dir.virtual_address as u64, None).ok()?;
+                LoadConfigDirectory::parse::<E>(data, is_64)
+                    .ok()
+                    .map(|(_, load_config)| load_config)
+            });
+
+        // x86 CHPE images have metadata of their own, which we do not decode
+        let chpe_metadata = match &load_config {
+            Some(load_config) if is_64 && load_config.chpe_metadata_pointer != 0 => load_config
+                .chpe_metadata_pointer
+                .checked_sub(pe_header.optional_header.image_base())
+                .and_then(|rva| parse_chpe_metadata::<E>(&pe_header, input, rva).ok()),
+            _ => None,
+        };
+
+        let dynamic_relocation_table = match &load_config {
+            Some(load_config) if load_config.dynamic_value_reloc_table_section != 0 => {
+                let section = (load_config.dynamic_value_reloc_table_section as usize)
+                    .checked_sub(1)
+                    .and_then(|idx| pe_header.sections.get(idx));
+                section
+                    .and_then(|section| {
+                        (section.pointer_to_raw_data as usize)
+                            .checked_add(load_config.dynamic_value_reloc_table_offset as usize)
+                    })
+                    .and_then(|offset| at::<E>(input, offset).ok())
+                    .and_then(|data| DynamicRelocationTable::parse::<E>(data, is_64).ok())
+                    .map(|(_, table)| table)
+            }
+            Some(load_config) if load_config.dynamic_value_reloc_table != 0 => load_config
+                .dynamic_value_reloc_table
+                .checked_sub(pe_header.optional_header.image_base())
+                .and_then(|rva| get_data::<E>(&pe_header, input, rva, None).ok())
+                .and_then(|data| DynamicRelocationTable::parse::<E>(data, is_64).ok())
+                .map(|(_, table)| table),
+            _ => None,
+        };
 
         // ImageDataDirectoryIndex::EntryBoundImport
 
@@ -238,15 +734,38 @@ impl<'a> Parse<'a> for Pe<'a> {
 
         // ImageDataDirectoryIndex::EntryComDescriptor
 
+        // Stripped and packed images often leave a stale pointer or a truncated table behind
+        let symbol_table = if pe_header.file_header.pointer_to_symbol_table == 0 {
+            None
+        } else {
+            SymbolTable::parse_at::<E>(
+                input,
+                pe_header.file_header.pointer_to_symbol_table as usize,
+                pe_header.file_header.number_of_symbols as usize,
+                SYMBOL_SIZE,
+            )
+            .ok()
+        };
+
         let (rest, data) = take(pe_header.optional_header.size_of_image().min(input.len()))(input)?;
 
         Ok((
             rest,
             Self {
+                file: input,
                 data,
                 dos_header,
+                dos_stub,
+                rich_header,
                 pe_header,
+                export_table,
                 import_table,
+                exception_table,
+                certificate_table,
+                load_config,
+                chpe_metadata,
+                dynamic_relocation_table,
+                symbol_table,
             },
         ))
     }
@@ -258,7 +777,14 @@ impl<'a> fmt::Display for Pe<'a> {
         let offset = "  ".repeat(width);
 
         write!(f, "{offset}dos_header:\n{:width$}", self.dos_header)?;
+        write!(f, "{offset}dos_stub_size: 0x{:x}\n", self.dos_stub.len())?;
+        if let Some(ref rich_header) = self.rich_header {
+            write!(f, "{offset}rich_header:\n{:width$}", rich_header)?;
+        }
         write!(f, "{offset}pe_header:\n{:width$}", self.pe_header)?;
+        if let Some(ref export_table) = self.export_table {
+            write!(f, "{offset}export_table:\n{:width$}", export_table)?;
+        }
         write!(f, "{offset}import_table:\n")?;
         for (module, symbols) in &self.import_table {
             for symbol in symbols {
@@ -269,6 +795,493 @@ impl<'a> fmt::Display for Pe<'a> {
             }
             write!(f, "\n")?;
         }
+        if !self.exception_table.is_empty() {
+            write!(f, "{offset}exception_table:\n")?;
+            for function in &self.exception_table {
+                write!(f, "{:width$}", function)?;
+            }
+        }
+        if let Some(ref certificate_table) = self.certificate_table {
+            write!(
+                f,
+                "{offset}certificate_table:\n{:width$}",
+                certificate_table
+            )?;
+        }
+        if let Some(ref load_config) = self.load_config {
+            write!(f, "{offset}load_config:\n{:width$}", load_config)?;
+        }
+        if let Some(ref chpe_metadata) = self.chpe_metadata {
+            write!(f, "{offset}chpe_metadata:\n{:width$}", chpe_metadata)?;
+        }
+        if let Some(ref dynamic_relocation_table) = self.dynamic_relocation_table {
+            write!(
+                f,
+                "{offset}dynamic_relocation_table:\n{:width$}",
+                dynamic_relocation_table
+            )?;
+        }
+        if let Some(ref symbol_table) = self.symbol_table {
+            write!(f, "{offset}symbol_table:\n{:width$}", symbol_table)?;
+        }
         Ok(())
     }
 }
+
+/// Any executable starting with a DOS header, identified by the signature found at `e_lfanew`.
+pub enum Executable<'a> {
+    Mz(MzExecutable<'a>),
+    Ne(NeExecutable<'a>),
+    Le(LeExecutable<'a>),
+    Pe(Pe<'a>),
+}
+
+impl<'a> Executable<'a> {
+    pub fn dos_header(&self) -> &DosHeader {
+        match self {
+            Self::Mz(mz) => &mz.dos_header,
+            Self::Ne(ne) => &ne.dos_header,
+            Self::Le(le) => &le.dos_header,
+            Self::Pe(pe) => &pe.dos_header,
+        }
+    }
+}
+
+impl<'a> fmt::Display for Executable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default();
+        match self {
+            Self::Mz(mz) => write!(f, "{:width$}", mz),
+            Self::Ne(ne) => write!(f, "{:width$}", ne),
+            Self::Le(le) => write!(f, "{:width$}", le),
+            Self::Pe(pe) => write!(f, "{:width$}", pe),
+        }
+    }
+}
+
+impl<'a> Parse<'a> for Executable<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (_, dos_header) = DosHeader::parse(input)?;
+
+        // Old MS-DOS programs leave garbage in `e_lfanew`, only trust a known signature.
+        let lfanew = dos_header.e_lfanew as usize;
+        match input.get(lfanew..).and_then(|data| data.get(..4)) {
+            Some(b"PE\0\0") => Pe::parse(input).map(|(rest, pe)| (rest, Self::Pe(pe))),
+            Some([b'N', b'E', ..]) => {
+                NeExecutable::parse(input).map(|(rest, ne)| (rest, Self::Ne(ne)))
+            }
+            Some([b'L', b'E' | b'X', ..]) => {
+                LeExecutable::parse(input).map(|(rest, le)| (rest, Self::Le(le)))
+            }
+            _ => MzExecutable::parse(input).map(|(rest, mz)| (rest, Self::Mz(mz))),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    /// PE32+ image with its PE header at `e_lfanew`, 16 empty data directories and a single
+    /// `.text` section of 0x200 bytes, at file offset 0x200 and RVA 0x1000.
+    pub(crate) fn image(e_lfanew: usize) -> Vec<u8> {
+        let mut file = vec![0u8; 0x400];
+        file[..2].copy_from_slice(b"MZ");
+        file[0x3c..0x40].copy_from_slice(&(e_lfanew as u32).to_le_bytes());
+        file[e_lfanew..e_lfanew + 4].copy_from_slice(b"PE\0\0");
+
+        // File header
+        let fh = e_lfanew + 4;
+        file[fh..fh + 2].copy_from_slice(&0x8664u16.to_le_bytes());
+        file[fh + 2..fh + 4].copy_from_slice(&1u16.to_le_bytes());
+        file[fh + 16..fh + 18].copy_from_slice(&240u16.to_le_bytes());
+        file[fh + 18..fh + 20].copy_from_slice(&0x22u16.to_le_bytes());
+
+        // Optional header
+        let oh = fh + 20;
+        file[oh..oh + 2].copy_from_slice(&0x20bu16.to_le_bytes());
+        file[oh + 24..oh + 32].copy_from_slice(&0x1_4000_0000u64.to_le_bytes());
+        file[oh + 32..oh + 36].copy_from_slice(&0x1000u32.to_le_bytes());
+        file[oh + 36..oh + 40].copy_from_slice(&0x200u32.to_le_bytes());
+        file[oh + 56..oh + 60].copy_from_slice(&0x2000u32.to_le_bytes());
+        file[oh + 60..oh + 64].copy_from_slice(&0x200u32.to_le_bytes());
+        file[oh + 68..oh + 70].copy_from_slice(&3u16.to_le_bytes());
+        file[oh + 108..oh + 112].copy_from_slice(&16u32.to_le_bytes());
+
+        // Section header
+        let sh = oh + 240;
+        file[sh..sh + 8].copy_from_slice(b".text\0\0\0");
+        file[sh + 8..sh + 12].copy_from_slice(&0x200u32.to_le_bytes());
+        file[sh + 12..sh + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        file[sh + 16..sh + 20].copy_from_slice(&0x200u32.to_le_bytes());
+        file[sh + 20..sh + 24].copy_from_slice(&0x200u32.to_le_bytes());
+        file[sh + 36..sh + 40].copy_from_slice(&0x6000_0020u32.to_le_bytes());
+
+        file
+    }
+
+    fn put_u32(file: &mut [u8], offset: usize, value: u32) {
+        file[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn checksum() {
+        let mut file = image(0x40);
+        for (idx, b) in file[0x200..].iter_mut().enumerate() {
+            *b = (idx * 7) as u8;
+        }
+        file.push(0x5a);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert_eq!(pe.compute_checksum(), 0x8478);
+        assert!(!pe.checksum_valid());
+
+        put_u32(&mut file, 0x40 + 24 + 64, 0x8478);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert_eq!(pe.compute_checksum(), 0x8478);
+        assert!(pe.checksum_valid());
+    }
+
+    #[test]
+    fn checksum_unaligned() {
+        // The stored checksum straddles words, its halves are still subtracted as is
+        let mut file = image(0x41);
+        for (idx, b) in file[0x200..].iter_mut().enumerate() {
+            *b = (idx * 7) as u8;
+        }
+        for (check_sum, expected) in [(0, 0xedb4), (0x0001_0000, 0xeeb3), (0x1234_5678, 0x3171)] {
+            put_u32(&mut file, 0x41 + 24 + 64, check_sum);
+            let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+            assert_eq!(pe.compute_checksum(), expected);
+        }
+    }
+
+    #[test]
+    fn overlapping_dos_header() {
+        let file = image(0x10);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.dos_stub().is_empty());
+        assert!(pe.rich_header().is_none());
+        assert_eq!(pe.pe_header.sections.len(), 1);
+
+        let (_, executable) = Executable::parse::<Error>(&file).unwrap();
+        assert!(matches!(executable, Executable::Pe(_)));
+    }
+
+    #[test]
+    fn dos_stub() {
+        let mut file = image(0x80);
+        file[0x40..0x44].copy_from_slice(&[0x0e, 0x1f, 0xba, 0x0e]);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert_eq!(pe.dos_stub().len(), 0x40);
+        assert_eq!(&pe.dos_stub()[..4], &[0x0e, 0x1f, 0xba, 0x0e]);
+        assert!(pe.rich_header().is_none());
+    }
+
+    #[test]
+    fn broken_symbol_table() {
+        let mut file = image(0x40);
+        let fh = 0x40 + 4;
+
+        // Past the end of the file
+        put_u32(&mut file, fh + 8, 0x1000);
+        put_u32(&mut file, fh + 12, 2);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.symbol_table().is_none());
+
+        // String table size larger than the file
+        put_u32(&mut file, fh + 8, 0x300);
+        put_u32(&mut file, 0x300 + 2 * 18, 0x1000);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.symbol_table().is_none());
+
+        put_u32(&mut file, 0x300 + 2 * 18, 4);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert_eq!(pe.symbol_table().unwrap().symbols.len(), 2);
+    }
+
+    #[test]
+    fn export_table() {
+        let mut file = image(0x40);
+        let export_directory = 0x40 + 24 + 112;
+        put_u32(&mut file, export_directory, 0x1000);
+        put_u32(&mut file, export_directory + 4, 0x100);
+
+        // Export directory at RVA 0x1000, file offset 0x200, with a base making ordinals wrap
+        put_u32(&mut file, 0x200 + 12, 0x1060);
+        put_u32(&mut file, 0x200 + 16, u32::MAX);
+        put_u32(&mut file, 0x200 + 20, 4);
+        put_u32(&mut file, 0x200 + 24, 2);
+        put_u32(&mut file, 0x200 + 28, 0x1028);
+        put_u32(&mut file, 0x200 + 32, 0x1040);
+        put_u32(&mut file, 0x200 + 36, 0x1050);
+        for (idx, address) in [0x1800, 0x1080, 0, 0x10f0].into_iter().enumerate() {
+            put_u32(&mut file, 0x228 + idx * 4, address);
+        }
+        // The second name points outside of any section
+        put_u32(&mut file, 0x240, 0x1070);
+        put_u32(&mut file, 0x244, 0x5000);
+        file[0x250..0x254].copy_from_slice(&[0, 0, 1, 0]);
+        file[0x260..0x269].copy_from_slice(b"test.dll\0");
+        file[0x270..0x276].copy_from_slice(b"first\0");
+        file[0x280..0x28d].copy_from_slice(b"other.Symbol\0");
+        // Forwarder that is not a valid string
+        file[0x2f0] = 0xff;
+
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        let export_table = pe.export_table().unwrap();
+        assert_eq!(export_table.name, "test.dll");
+        let exports: Vec<_> = export_table
+            .exports
+            .iter()
+            .map(|export| {
+                (
+                    export.ordinal,
+                    export.name,
+                    export.address,
+                    export.forwarder,
+                )
+            })
+            .collect();
+        assert_eq!(
+            exports,
+            [
+                (u32::MAX, Some("first"), 0x1800, None),
+                (0, None, 0x1080, Some("other.Symbol")),
+            ]
+        );
+        assert_eq!(export_table.by_ordinal(u32::MAX).unwrap().address, 0x1800);
+        assert_eq!(export_table.by_ordinal(0).unwrap().address, 0x1080);
+        assert!(export_table.by_ordinal(1).is_none());
+        assert_eq!(export_table.by_name("first").unwrap().ordinal, u32::MAX);
+
+        // A directory running past its section is ignored
+        put_u32(&mut file, export_directory + 4, u32::MAX);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.export_table().is_none());
+    }
+
+    /// Adds a load config directory at RVA 0x1100, file offset 0x300.
+    fn load_config(file: &mut [u8]) {
+        let load_config_directory = 0x40 + 24 + 112 + 10 * 8;
+        put_u32(file, load_config_directory, 0x1100);
+        put_u32(file, load_config_directory + 4, 0x100);
+        put_u32(file, 0x300, 232);
+    }
+
+    #[test]
+    fn broken_load_config() {
+        let mut file = image(0x40);
+        load_config(&mut file);
+        // CHPE metadata and dynamic relocations below ImageBase
+        file[0x300 + 192..0x300 + 200].copy_from_slice(&0x1000u64.to_le_bytes());
+        file[0x300 + 200..0x300 + 208].copy_from_slice(&0x1000u64.to_le_bytes());
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.load_config().is_some());
+        assert!(pe.chpe_metadata().is_none());
+        assert!(pe.dynamic_relocation_table().is_none());
+
+        // Dynamic relocations in a section that does not exist
+        file[0x300 + 228..0x300 + 230].copy_from_slice(&2u16.to_le_bytes());
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.dynamic_relocation_table().is_none());
+
+        // Load config outside of any section
+        put_u32(&mut file, 0x40 + 24 + 112 + 10 * 8, 0x5000);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.load_config().is_none());
+    }
+
+    #[test]
+    fn arm64x_fixups() {
+        let mut file = image(0x40);
+        // Section raw data running past the end of the file
+        let sh = 0x40 + 24 + 240;
+        put_u32(&mut file, sh + 8, 0x1000);
+        put_u32(&mut file, sh + 16, 0x1000);
+        load_config(&mut file);
+        // Dynamic relocations at offset 0x180 of the first section
+        put_u32(&mut file, 0x300 + 224, 0x180);
+        file[0x300 + 228..0x300 + 230].copy_from_slice(&1u16.to_le_bytes());
+
+        let mut table = Vec::new();
+        table.extend_from_slice(&1u32.to_le_bytes());
+        table.extend_from_slice(&32u32.to_le_bytes());
+        table.extend_from_slice(&6u64.to_le_bytes());
+        table.extend_from_slice(&20u32.to_le_bytes());
+        table.extend_from_slice(&0x1000u32.to_le_bytes());
+        table.extend_from_slice(&20u32.to_le_bytes());
+        // 4 bytes value at 0x1010, +3 * 4 at 0x1020, zero byte out of the file at 0x1ff0
+        table.extend_from_slice(&0x9010u16.to_le_bytes());
+        table.extend_from_slice(&0x1122_3344u32.to_le_bytes());
+        table.extend_from_slice(&0x2020u16.to_le_bytes());
+        table.extend_from_slice(&3u16.to_le_bytes());
+        table.extend_from_slice(&0x0ff0u16.to_le_bytes());
+        file[0x380..0x380 + table.len()].copy_from_slice(&table);
+        put_u32(&mut file, 0x220, 0x100);
+
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.is_arm64x());
+        assert_eq!(
+            pe.dynamic_relocation_table()
+                .unwrap()
+                .arm64x_fixups()
+                .count(),
+            3
+        );
+
+        let x64 = pe.x64_view().unwrap();
+        assert_eq!(x64.len(), file.len());
+        assert_eq!(&x64[0x210..0x214], &0x1122_3344u32.to_le_bytes());
+        assert_eq!(&x64[0x220..0x224], &0x10cu32.to_le_bytes());
+        assert_eq!(&x64[..0x210], &file[..0x210]);
+    }
+
+    /// Image with ARM64EC metadata of `version` at RVA 0x1000, file offset 0x200, and the
+    /// tables it points to.
+    fn chpe_image(version: u32) -> (Vec<u8>, [u32; 23]) {
+        let mut file = image(0x40);
+        load_config(&mut file);
+        file[0x300 + 200..0x300 + 208].copy_from_slice(&0x1_4000_1000u64.to_le_bytes());
+        // IAT of two entries
+        let iat_directory = 0x40 + 24 + 112 + 12 * 8;
+        put_u32(&mut file, iat_directory, 0x1300);
+        put_u32(&mut file, iat_directory + 4, 16);
+
+        let mut fields: [u32; 23] = std::array::from_fn(|idx| 0xa000 + idx as u32 * 0x10);
+        fields[0] = version;
+        fields[1] = 0x1060;
+        fields[2] = 3;
+        fields[3] = 0x1078;
+        fields[4] = 0x1090;
+        fields[11] = 0x10a0;
+        fields[12] = 2;
+        fields[13] = 2;
+        for (idx, field) in fields.iter().enumerate() {
+            put_u32(&mut file, 0x200 + idx * 4, *field);
+        }
+
+        // ARM64, ARM64EC and x64 ranges
+        for (idx, (start, length)) in [(0x1000, 0x100), (0x1101, 0x80), (0x1202, 0x10)]
+            .into_iter()
+            .enumerate()
+        {
+            put_u32(&mut file, 0x260 + idx * 8, start);
+            put_u32(&mut file, 0x264 + idx * 8, length);
+        }
+        for (idx, entry) in [[0x1100, 0x1140, 0x1104], [0x1140, 0x1180, 0x1144]]
+            .into_iter()
+            .enumerate()
+        {
+            for (field, value) in entry.into_iter().enumerate() {
+                put_u32(&mut file, 0x278 + idx * 12 + field * 4, value);
+            }
+        }
+        for (idx, (source, destination)) in
+            [(0x1200, 0x1110), (0x1208, 0x1150)].into_iter().enumerate()
+        {
+            put_u32(&mut file, 0x290 + idx * 8, source);
+            put_u32(&mut file, 0x294 + idx * 8, destination);
+        }
+        file[0x2a0..0x2a8].copy_from_slice(&0x1_4000_1120u64.to_le_bytes());
+        file[0x2a8..0x2b0].copy_from_slice(&0x1_4000_1160u64.to_le_bytes());
+
+        (file, fields)
+    }
+
+    #[test]
+    fn chpe_metadata() {
+        for version in [1, 2] {
+            let (file, fields) = chpe_image(version);
+            let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+            let chpe = pe.chpe_metadata().unwrap();
+
+            let metadata = &chpe.metadata;
+            assert_eq!(metadata.version, version);
+            assert_eq!(metadata.code_map, fields[1]);
+            assert_eq!(metadata.code_map_count, fields[2]);
+            assert_eq!(metadata.code_ranges_to_entry_points, fields[3]);
+            assert_eq!(metadata.redirection_metadata, fields[4]);
+            assert_eq!(metadata.os_arm64x_dispatch_call_no_redirect, fields[5]);
+            assert_eq!(metadata.os_arm64x_dispatch_ret, fields[6]);
+            assert_eq!(metadata.os_arm64x_dispatch_call, fields[7]);
+            assert_eq!(metadata.os_arm64x_dispatch_icall, fields[8]);
+            assert_eq!(metadata.os_arm64x_dispatch_icall_cfg, fields[9]);
+            assert_eq!(metadata.alternate_entry_point, fields[10]);
+            assert_eq!(metadata.auxiliary_iat, fields[11]);
+            assert_eq!(metadata.code_ranges_to_entry_points_count, fields[12]);
+            assert_eq!(metadata.redirection_metadata_count, fields[13]);
+            assert_eq!(metadata.get_x64_information_function_pointer, fields[14]);
+            assert_eq!(metadata.set_x64_information_function_pointer, fields[15]);
+            assert_eq!(metadata.extra_rfe_table, fields[16]);
+            assert_eq!(metadata.extra_rfe_table_size, fields[17]);
+            assert_eq!(metadata.os_arm64x_dispatch_fptr, fields[18]);
+            assert_eq!(metadata.auxiliary_iat_copy, fields[19]);
+            // Version 1 stops before the delay load IATs
+            let version_2 = |field: u32| if version >= 2 { field } else { 0 };
+            assert_eq!(metadata.auxiliary_delay_load_iat, version_2(fields[20]));
+            assert_eq!(
+                metadata.auxiliary_delay_load_iat_copy,
+                version_2(fields[21])
+            );
+            assert_eq!(metadata.hybrid_image_info_bitfield, version_2(fields[22]));
+
+            let code_map: Vec<_> = chpe
+                .code_map
+                .iter()
+                .map(|range| (range.start, range.length, range.range_type))
+                .collect();
+            assert_eq!(
+                code_map,
+                [
+                    (0x1000, 0x100, ChpeRangeType::Arm64),
+                    (0x1100, 0x80, ChpeRangeType::Arm64EC),
+                    (0x1200, 0x10, ChpeRangeType::Amd64),
+                ]
+            );
+            assert_eq!(chpe.range_type(0x0fff), None);
+            assert_eq!(chpe.range_type(0x1000), Some(ChpeRangeType::Arm64));
+            assert_eq!(chpe.range_type(0x10ff), Some(ChpeRangeType::Arm64));
+            assert_eq!(chpe.range_type(0x1100), Some(ChpeRangeType::Arm64EC));
+            assert_eq!(chpe.range_type(0x1180), None);
+            assert_eq!(chpe.range_type(0x120f), Some(ChpeRangeType::Amd64));
+            assert_eq!(chpe.range_type(0x1210), None);
+
+            let entry_points: Vec<_> = chpe
+                .code_ranges_to_entry_points
+                .iter()
+                .map(|entry| (entry.start_rva, entry.end_rva, entry.entry_point))
+                .collect();
+            assert_eq!(
+                entry_points,
+                [(0x1100, 0x1140, 0x1104), (0x1140, 0x1180, 0x1144)]
+            );
+            let redirections: Vec<_> = chpe
+                .redirection_metadata
+                .iter()
+                .map(|entry| (entry.source, entry.destination))
+                .collect();
+            assert_eq!(redirections, [(0x1200, 0x1110), (0x1208, 0x1150)]);
+            assert_eq!(chpe.auxiliary_iat, [0x1_4000_1120, 0x1_4000_1160]);
+        }
+    }
+
+    #[test]
+    fn broken_chpe_metadata() {
+        // Code map outside of any section
+        let (mut file, _) = chpe_image(2);
+        put_u32(&mut file, 0x204, 0x5000);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.chpe_metadata().is_none());
+
+        // No auxiliary IAT
+        let (mut file, _) = chpe_image(2);
+        put_u32(&mut file, 0x22c, 0);
+        let (_, pe) = Pe::parse::<Error>(&file).unwrap();
+        assert!(pe.chpe_metadata().unwrap().auxiliary_iat.is_empty());
+    }
+}
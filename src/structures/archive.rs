@@ -0,0 +1,359 @@
+use nom::bytes::complete::{tag, take};
+use nom::error::context;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+use super::CoffObject;
+
+mod header;
+pub use header::ArchiveMemberHeader;
+
+mod import_object;
+pub use import_object::{ImportNameType, ImportObject, ImportType};
+
+mod linker_member;
+pub use linker_member::{FirstLinkerMember, SecondLinkerMember};
+
+/// `IMAGE_ARCHIVE_START`
+pub const IMAGE_ARCHIVE_START: &[u8] = b"!<arch>\n";
+
+#[derive(Debug)]
+pub enum ArchiveMemberContent<'a> {
+    Coff(CoffObject<'a>),
+    /// Short import description.
+    Import(ImportObject<'a>),
+    /// Member in a format we do not parse.
+    Raw(&'a [u8]),
+}
+
+#[derive(Debug)]
+pub struct ArchiveMember<'a> {
+    /// Offset of the member header in the archive, as found in the linker members.
+    pub offset: usize,
+    pub header: ArchiveMemberHeader<'a>,
+    /// Member name, looked up in the longnames member if needed.
+    pub name: &'a str,
+    pub data: &'a [u8],
+    pub content: ArchiveMemberContent<'a>,
+}
+
+impl<'a> fmt::Display for ArchiveMember<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}name: {}\n", self.name)?;
+        write!(f, "{offset}offset: 0x{:x}\n", self.offset)?;
+        write!(f, "{offset}header:\n{:width$}", self.header)?;
+        match &self.content {
+            ArchiveMemberContent::Coff(object) => write!(f, "{offset}coff:\n{:width$}", object),
+            ArchiveMemberContent::Import(import) => write!(f, "{offset}import:\n{:width$}", import),
+            ArchiveMemberContent::Raw(data) => write!(f, "{offset}raw: 0x{:x} bytes\n", data.len()),
+        }
+    }
+}
+
+/// Resolves a member name from its header, `/<offset>` designating a string of the longnames
+/// member terminated either by a nul (Microsoft) or by `/\n` (GNU).
+fn member_name<'a>(raw_name: &'a str, longnames: Option<&'a [u8]>) -> Option<&'a str> {
+    let name = match raw_name.strip_prefix('/').map(str::parse::<usize>) {
+        Some(Ok(offset)) => {
+            let data = longnames?.get(offset..)?;
+            let end = data
+                .iter()
+                .position(|b| *b == 0 || *b == b'\n')
+                .unwrap_or(data.len());
+            std::str::from_utf8(&data[..end]).ok()?
+        }
+        _ => raw_name,
+    };
+    Some(name.strip_suffix('/').unwrap_or(name))
+}
+
+/// Library archive, as produced by `lib.exe` or `ar`.
+#[derive(Debug)]
+pub struct Archive<'a> {
+    pub first_linker_member: Option<FirstLinkerMember<'a>>,
+    pub second_linker_member: Option<SecondLinkerMember<'a>>,
+    pub longnames: Option<&'a [u8]>,
+    pub members: Vec<ArchiveMember<'a>>,
+}
+
+impl<'a> Archive<'a> {
+    /// Member at `offset`, the offset of its header.
+    pub fn member_at(&self, offset: usize) -> Option<&ArchiveMember<'a>> {
+        self.members
+            .binary_search_by_key(&offset, |member| member.offset)
+            .ok()
+            .map(|idx| &self.members[idx])
+    }
+
+    /// Short import descriptions, as found in import libraries.
+    pub fn imports(&self) -> impl Iterator<Item = &ImportObject<'a>> {
+        self.members
+            .iter()
+            .filter_map(|member| match &member.content {
+                ArchiveMemberContent::Import(import) => Some(import),
+                _ => None,
+            })
+    }
+
+    /// Member defining the symbol `name`, according to the linker members.
+    pub fn find_symbol(&self, name: &str) -> Option<&ArchiveMember<'a>> {
+        let offset = match &self.second_linker_member {
+            Some(second_linker_member) => second_linker_member.find(name)?,
+            None => self.first_linker_member.as_ref()?.find(name)?,
+        };
+        self.member_at(offset as usize)
+    }
+}
+
+impl<'a> fmt::Display for Archive<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        if let Some(first_linker_member) = &self.first_linker_member {
+            write!(
+                f,
+                "{offset}first_linker_member:\n{:width$}",
+                first_linker_member
+            )?;
+        }
+        if let Some(second_linker_member) = &self.second_linker_member {
+            write!(
+                f,
+                "{offset}second_linker_member:\n{:width$}",
+                second_linker_member
+            )?;
+        }
+        write!(f, "{offset}members:\n")?;
+        for member in &self.members {
+            write!(f, "{:width$}\n", member)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for Archive<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (mut rest, _) = context("Archive signature", tag(IMAGE_ARCHIVE_START))(input)?;
+
+        let mut first_linker_member = None;
+        let mut second_linker_member = None;
+        let mut longnames = None;
+        let mut members = Vec::new();
+        while !rest.is_empty() {
+            let offset = input.len() - rest.len();
+            let (r, header) = ArchiveMemberHeader::parse(rest)?;
+            let (r, data) = context("Archive member data", take(header.size as usize))(r)?;
+            // Members start on an even offset
+            rest = if (input.len() - r.len()) % 2 == 1 && !r.is_empty() {
+                &r[1..]
+            } else {
+                r
+            };
+
+            match header.name {
+                "/" if first_linker_member.is_none() => {
+                    let (_, linker_member) = FirstLinkerMember::parse(data)?;
+                    first_linker_member = Some(linker_member);
+                }
+                "/" if second_linker_member.is_none() => {
+                    let (_, linker_member) = SecondLinkerMember::parse(data)?;
+                    second_linker_member = Some(linker_member);
+                }
+                "//" => longnames = Some(data),
+                _ => {
+                    let name = member_name(header.name, longnames).ok_or_else(|| {
+                        nom::Err::Error(E::add_context(
+                            data,
+                            "Invalid archive member name",
+                            E::from_error_kind(data, nom::error::ErrorKind::Verify),
+                        ))
+                    })?;
+                    // Anything else, such as LLVM bitcode, is kept as is.
+                    let content = if let Ok((_, import)) = ImportObject::parse::<E>(data) {
+                        ArchiveMemberContent::Import(import)
+                    } else if let Ok((_, object)) = CoffObject::parse::<E>(data) {
+                        ArchiveMemberContent::Coff(object)
+                    } else {
+                        ArchiveMemberContent::Raw(data)
+                    };
+                    members.push(ArchiveMember {
+                        offset,
+                        header,
+                        name,
+                        data,
+                        content,
+                    });
+                }
+            }
+        }
+
+        Ok((
+            rest,
+            Self {
+                first_linker_member,
+                second_linker_member,
+                longnames,
+                members,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn member(archive: &mut Vec<u8>, name: &str, date: &str, data: &[u8]) {
+        archive.extend_from_slice(
+            format!(
+                "{name:<16}{date:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+                "",
+                "",
+                "644",
+                data.len()
+            )
+            .as_bytes(),
+        );
+        archive.extend_from_slice(data);
+        if archive.len() % 2 == 1 {
+            archive.push(b'\n');
+        }
+    }
+
+    #[test]
+    fn longnames() {
+        let mut archive = IMAGE_ARCHIVE_START.to_vec();
+        member(
+            &mut archive,
+            "//",
+            "",
+            b"a_rather_long_member_name.obj\0gnu_style_name.o/\n",
+        );
+        member(&mut archive, "/0", "-1", b"first");
+        member(&mut archive, "/30", "1700000000", b"second");
+        member(&mut archive, "short.o/", "", b"third");
+
+        let (_, archive) = Archive::parse::<Error>(&archive).unwrap();
+        let names: Vec<_> = archive.members.iter().map(|member| member.name).collect();
+        assert_eq!(
+            names,
+            [
+                "a_rather_long_member_name.obj",
+                "gnu_style_name.o",
+                "short.o"
+            ]
+        );
+        assert_eq!(archive.members[1].data, b"second");
+        assert!(matches!(
+            archive.members[2].content,
+            ArchiveMemberContent::Raw(b"third")
+        ));
+
+        // Informational fields are kept only when they read as numbers
+        let header = &archive.members[0].header;
+        assert_eq!(header.date, None);
+        assert_eq!(header.user_id, Some(0));
+        assert_eq!(header.mode, Some(0o644));
+        assert_eq!(archive.members[1].header.date, Some(1_700_000_000));
+    }
+
+    #[test]
+    fn member_names() {
+        assert_eq!(member_name("/10", None), None);
+        assert_eq!(member_name("/10", Some(b"short")), None);
+        assert_eq!(member_name("/", None), Some(""));
+        assert_eq!(member_name("name/", None), Some("name"));
+    }
+
+    #[test]
+    fn invalid_size() {
+        let mut archive = IMAGE_ARCHIVE_START.to_vec();
+        member(&mut archive, "short.o/", "", b"data");
+        archive[8 + 48] = b'x';
+        assert!(Archive::parse::<Error>(&archive).is_err());
+    }
+
+    /// Archive of the members `a.o` and `b.o`, with symbols `_alpha` and `_gamma` in the
+    /// first one and `_beta` in the second, optionally indexed by a second linker member.
+    fn linked_archive(second: bool) -> Vec<u8> {
+        let build = |offsets: [u32; 2]| {
+            let mut first_member = 3u32.to_be_bytes().to_vec();
+            for idx in [0, 1, 0] {
+                first_member.extend_from_slice(&offsets[idx].to_be_bytes());
+            }
+            first_member.extend_from_slice(b"_alpha\0_beta\0_gamma\0");
+
+            let mut second_member = 2u32.to_le_bytes().to_vec();
+            for offset in offsets {
+                second_member.extend_from_slice(&offset.to_le_bytes());
+            }
+            second_member.extend_from_slice(&4u32.to_le_bytes());
+            for index in [1u16, 2, 1, 0] {
+                second_member.extend_from_slice(&index.to_le_bytes());
+            }
+            second_member.extend_from_slice(b"_alpha\0_beta\0_gamma\0_unlinked\0");
+
+            let mut archive = IMAGE_ARCHIVE_START.to_vec();
+            member(&mut archive, "/", "", &first_member);
+            if second {
+                member(&mut archive, "/", "", &second_member);
+            }
+            let a = archive.len() as u32;
+            member(&mut archive, "a.o/", "", b"first");
+            let b = archive.len() as u32;
+            member(&mut archive, "b.o/", "", b"second");
+            (archive, [a, b])
+        };
+        // Linker members keep their size whatever the offsets they hold
+        let (_, offsets) = build([0, 0]);
+        build(offsets).0
+    }
+
+    #[test]
+    fn linker_members() {
+        let data = linked_archive(true);
+        let (_, archive) = Archive::parse::<Error>(&data).unwrap();
+        assert_eq!(archive.members.len(), 2);
+
+        let first = archive.first_linker_member.as_ref().unwrap();
+        assert_eq!(first.names, ["_alpha", "_beta", "_gamma"]);
+        assert_eq!(first.find("_beta"), Some(archive.members[1].offset as u32));
+        let second = archive.second_linker_member.as_ref().unwrap();
+        assert_eq!(second.indices, [1, 2, 1, 0]);
+        assert_eq!(
+            second.find("_gamma"),
+            Some(archive.members[0].offset as u32)
+        );
+        // Index 0 does not designate any member
+        assert_eq!(second.find("_unlinked"), None);
+
+        let member = |name| archive.find_symbol(name).map(|member| member.name);
+        assert_eq!(member("_alpha"), Some("a.o"));
+        assert_eq!(member("_beta"), Some("b.o"));
+        assert_eq!(member("_gamma"), Some("a.o"));
+        assert_eq!(member("_delta"), None);
+        assert_eq!(member("_unlinked"), None);
+    }
+
+    #[test]
+    fn first_linker_member_only() {
+        let data = linked_archive(false);
+        let (_, archive) = Archive::parse::<Error>(&data).unwrap();
+        assert!(archive.second_linker_member.is_none());
+
+        let member = |name| archive.find_symbol(name).map(|member| member.data);
+        assert_eq!(member("_alpha"), Some(&b"first"[..]));
+        assert_eq!(member("_beta"), Some(&b"second"[..]));
+        assert_eq!(member("_gamma"), Some(&b"first"[..]));
+        assert_eq!(member("_delta"), None);
+    }
+}
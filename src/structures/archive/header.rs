@@ -0,0 +1,106 @@
+use nom::bytes::complete::{tag, take};
+use nom::combinator::{map, map_opt};
+use nom::error::context;
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// Reads a space-padded ASCII number, blank fields reading as 0.
+fn ascii_number(field: &[u8], radix: u32) -> Option<u64> {
+    let field = std::str::from_utf8(field).ok()?.trim_end_matches(' ');
+    if field.is_empty() {
+        Some(0)
+    } else {
+        u64::from_str_radix(field, radix).ok()
+    }
+}
+
+/// Parses an informational number field, which tools fill inconsistently (e.g. a date of
+/// `-1`): `None` when it does not read as a number.
+fn lenient_ascii_number<'a, E>(
+    len: usize,
+    radix: u32,
+) -> impl FnMut(&'a [u8]) -> nom::IResult<&'a [u8], Option<u64>, E>
+where
+    E: NomError<'a>,
+{
+    map(take(len), move |field: &'a [u8]| ascii_number(field, radix))
+}
+
+/// `IMAGE_ARCHIVE_MEMBER_HEADER`
+#[derive(Debug)]
+pub struct ArchiveMemberHeader<'a> {
+    /// Raw name field: `/` and `//` for the special members, `/<offset>` for names in the
+    /// longnames member, and the name followed by `/` otherwise.
+    pub name: &'a str,
+    pub date: Option<u64>,
+    pub user_id: Option<u64>,
+    pub group_id: Option<u64>,
+    pub mode: Option<u64>,
+    pub size: u64,
+}
+
+impl<'a> ArchiveMemberHeader<'a> {
+    pub const fn size() -> usize {
+        60
+    }
+}
+
+impl<'a> fmt::Display for ArchiveMemberHeader<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}name: {}\n", self.name)?;
+        if let Some(date) = self.date {
+            write!(f, "{offset}date: {}\n", date)?;
+        }
+        if let Some(user_id) = self.user_id {
+            write!(f, "{offset}user_id: {}\n", user_id)?;
+        }
+        if let Some(group_id) = self.group_id {
+            write!(f, "{offset}group_id: {}\n", group_id)?;
+        }
+        if let Some(mode) = self.mode {
+            write!(f, "{offset}mode: {:o}\n", mode)?;
+        }
+        write!(f, "{offset}size: 0x{:x}\n", self.size)
+    }
+}
+
+impl<'a> Parse<'a> for ArchiveMemberHeader<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (name, date, user_id, group_id, mode, size, _)) = context(
+            "Archive member header",
+            tuple((
+                map_opt(take(16usize), |name: &'a [u8]| {
+                    Some(std::str::from_utf8(name).ok()?.trim_end_matches(' '))
+                }),
+                lenient_ascii_number(12, 10),
+                lenient_ascii_number(6, 10),
+                lenient_ascii_number(6, 10),
+                lenient_ascii_number(8, 8),
+                context(
+                    "Archive member size",
+                    map_opt(take(10usize), |field| ascii_number(field, 10)),
+                ),
+                tag(b"`\n"),
+            )),
+        )(input)?;
+
+        Ok((
+            rest,
+            Self {
+                name,
+                date,
+                user_id,
+                group_id,
+                mode,
+                size,
+            },
+        ))
+    }
+}
@@ -0,0 +1,301 @@
+use nom::bytes::complete::tag;
+use nom::combinator::{cond, verify};
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32};
+use nom::sequence::tuple;
+
+use crate::enums::FileMachine;
+use crate::parsers::nul_terminated_str;
+use crate::structures::{Export, ExportTable};
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// `IMPORT_OBJECT_CODE`, `IMPORT_OBJECT_DATA` and `IMPORT_OBJECT_CONST`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportType {
+    Code,
+    Data,
+    Const,
+    Other(u8),
+}
+
+impl From<u8> for ImportType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Code,
+            1 => Self::Data,
+            2 => Self::Const,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for ImportType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Code => f.write_str("code"),
+            Self::Data => f.write_str("data"),
+            Self::Const => f.write_str("const"),
+            Self::Other(value) => write!(f, "0x{:x}", value),
+        }
+    }
+}
+
+/// `IMPORT_OBJECT_NAME_TYPE`, how the imported name derives from the symbol name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportNameType {
+    /// Imported by ordinal, `ordinal_or_hint` being the ordinal.
+    Ordinal,
+    /// The symbol name.
+    Name,
+    /// The symbol name without its leading `?`, `@` or `_`.
+    NameNoPrefix,
+    /// The symbol name without its leading `?`, `@` or `_`, and truncated at the first `@`.
+    NameUndecorate,
+    /// The name following the DLL name.
+    NameExportAs,
+    Other(u8),
+}
+
+impl From<u8> for ImportNameType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Ordinal,
+            1 => Self::Name,
+            2 => Self::NameNoPrefix,
+            3 => Self::NameUndecorate,
+            4 => Self::NameExportAs,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for ImportNameType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ordinal => f.write_str("ordinal"),
+            Self::Name => f.write_str("name"),
+            Self::NameNoPrefix => f.write_str("name without prefix"),
+            Self::NameUndecorate => f.write_str("undecorated name"),
+            Self::NameExportAs => f.write_str("export as"),
+            Self::Other(value) => write!(f, "0x{:x}", value),
+        }
+    }
+}
+
+/// Short import library member: `IMPORT_OBJECT_HEADER` and the names following it, standing
+/// for the stubs the linker generates.
+#[derive(Debug)]
+pub struct ImportObject<'a> {
+    pub version: u16,
+    pub machine: FileMachine,
+    pub time_date_stamp: u32,
+    pub size_of_data: u32,
+    pub ordinal_or_hint: u16,
+    pub import_type: ImportType,
+    pub name_type: ImportNameType,
+    /// Public symbol name, `__imp_` prefix excluded.
+    pub symbol_name: &'a str,
+    pub dll_name: &'a str,
+    /// Explicit import name, for `NameExportAs`.
+    pub export_name: Option<&'a str>,
+}
+
+impl<'a> ImportObject<'a> {
+    /// Name the DLL must export, or `None` when imported by ordinal.
+    pub fn import_name(&self) -> Option<&'a str> {
+        let strip_prefix = |name: &'a str| {
+            name.strip_prefix(|c| c == '?' || c == '@' || c == '_')
+                .unwrap_or(name)
+        };
+        match self.name_type {
+            ImportNameType::Ordinal => None,
+            ImportNameType::NameNoPrefix => Some(strip_prefix(self.symbol_name)),
+            ImportNameType::NameUndecorate => {
+                let name = strip_prefix(self.symbol_name);
+                Some(name.split('@').next().unwrap_or(name))
+            }
+            ImportNameType::NameExportAs => self.export_name,
+            ImportNameType::Name | ImportNameType::Other(_) => Some(self.symbol_name),
+        }
+    }
+
+    /// Ordinal the DLL must export, when imported by ordinal.
+    pub fn ordinal(&self) -> Option<u16> {
+        match self.name_type {
+            ImportNameType::Ordinal => Some(self.ordinal_or_hint),
+            _ => None,
+        }
+    }
+
+    /// Export of `exports` this import binds to, if the DLL provides it.
+    pub fn resolve<'b>(&self, exports: &'b ExportTable<'a>) -> Option<&'b Export<'a>> {
+        match self.ordinal() {
+            Some(ordinal) => exports.by_ordinal(ordinal as u32),
+            None => exports.by_name(self.import_name()?),
+        }
+    }
+}
+
+impl<'a> fmt::Display for ImportObject<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}version: {}\n", self.version)?;
+        write!(f, "{offset}machine: {}\n", self.machine)?;
+        write!(f, "{offset}time_date_stamp: 0x{:x}\n", self.time_date_stamp)?;
+        write!(f, "{offset}size_of_data: 0x{:x}\n", self.size_of_data)?;
+        write!(f, "{offset}ordinal_or_hint: {}\n", self.ordinal_or_hint)?;
+        write!(f, "{offset}import_type: {}\n", self.import_type)?;
+        write!(f, "{offset}name_type: {}\n", self.name_type)?;
+        write!(f, "{offset}symbol_name: {}\n", self.symbol_name)?;
+        write!(f, "{offset}dll_name: {}\n", self.dll_name)?;
+        if let Some(export_name) = self.export_name {
+            write!(f, "{offset}export_name: {}\n", export_name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for ImportObject<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (_, version, machine, time_date_stamp, size_of_data, ordinal_or_hint, types)) =
+            context(
+                "Import object header",
+                tuple((
+                    // `IMAGE_FILE_MACHINE_UNKNOWN` and 0xffff
+                    tag(b"\x00\x00\xff\xff"),
+                    verify(le_u16, |version| *version == 0),
+                    FileMachine::parse,
+                    le_u32,
+                    le_u32,
+                    le_u16,
+                    le_u16,
+                )),
+            )(input)?;
+        let import_type = ImportType::from((types & 0x3) as u8);
+        let name_type = ImportNameType::from(((types >> 2) & 0x7) as u8);
+
+        let (rest, (symbol_name, dll_name, export_name)) = context(
+            "Import object names",
+            tuple((
+                nul_terminated_str,
+                nul_terminated_str,
+                cond(
+                    name_type == ImportNameType::NameExportAs,
+                    nul_terminated_str,
+                ),
+            )),
+        )(rest)?;
+
+        Ok((
+            rest,
+            Self {
+                version,
+                machine,
+                time_date_stamp,
+                size_of_data,
+                ordinal_or_hint,
+                import_type,
+                name_type,
+                symbol_name,
+                dll_name,
+                export_name,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::ExportDirectory;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn object(ordinal_or_hint: u16, name_type: u8, names: &[&str]) -> Vec<u8> {
+        let mut object = vec![0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0x4c, 0x01];
+        object.extend_from_slice(&[0; 8]);
+        object.extend_from_slice(&ordinal_or_hint.to_le_bytes());
+        object.extend_from_slice(&((name_type as u16) << 2).to_le_bytes());
+        for name in names {
+            object.extend_from_slice(name.as_bytes());
+            object.push(0);
+        }
+        object
+    }
+
+    #[test]
+    fn import_names() {
+        let cases = [
+            (0, &["_foo@8", "test.dll"][..], None, Some(7)),
+            (1, &["_foo@8", "test.dll"], Some("_foo@8"), None),
+            (2, &["_foo@8", "test.dll"], Some("foo@8"), None),
+            (2, &["?foo@@YAXXZ", "test.dll"], Some("foo@@YAXXZ"), None),
+            (3, &["_foo@8", "test.dll"], Some("foo"), None),
+            (3, &["@foo@8", "test.dll"], Some("foo"), None),
+            (3, &["foo", "test.dll"], Some("foo"), None),
+            (4, &["_foo@8", "test.dll", "bar"], Some("bar"), None),
+            (5, &["_foo@8", "test.dll"], Some("_foo@8"), None),
+        ];
+        for (name_type, names, import_name, ordinal) in cases {
+            let data = object(7, name_type, names);
+            let (rest, import) = ImportObject::parse::<Error>(&data).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(import.machine, FileMachine::MachineI386);
+            assert_eq!(import.symbol_name, names[0]);
+            assert_eq!(import.dll_name, "test.dll");
+            assert_eq!(import.import_name(), import_name, "{:?}", names);
+            assert_eq!(import.ordinal(), ordinal);
+        }
+        // The export name is required with `NameExportAs`
+        assert!(ImportObject::parse::<Error>(&object(7, 4, &["_foo@8", "test.dll"])).is_err());
+    }
+
+    #[test]
+    fn resolve() {
+        let export = |ordinal, name, address| Export {
+            ordinal,
+            name,
+            address,
+            forwarder: None,
+        };
+        let exports = ExportTable {
+            directory: ExportDirectory {
+                characteristics: 0,
+                time_date_stamp: 0,
+                major_version: 0,
+                minor_version: 0,
+                name: 0,
+                base: 7,
+                number_of_functions: 3,
+                number_of_names: 2,
+                address_of_functions: 0,
+                address_of_names: 0,
+                address_of_name_ordinals: 0,
+            },
+            name: "test.dll",
+            exports: vec![
+                export(7, Some("foo"), 0x1000),
+                export(8, None, 0x1010),
+                export(9, Some("_foo@8"), 0x1020),
+            ],
+        };
+
+        let resolve = |ordinal_or_hint, name_type, names: &[&str]| {
+            let data = object(ordinal_or_hint, name_type, names);
+            let (_, import) = ImportObject::parse::<Error>(&data).unwrap();
+            import.resolve(&exports).map(|export| export.address)
+        };
+        assert_eq!(resolve(8, 0, &["_bar", "test.dll"]), Some(0x1010));
+        assert_eq!(resolve(10, 0, &["_bar", "test.dll"]), None);
+        // The hint does not matter when importing by name
+        assert_eq!(resolve(8, 3, &["_foo@8", "test.dll"]), Some(0x1000));
+        assert_eq!(resolve(0, 1, &["_foo@8", "test.dll"]), Some(0x1020));
+        assert_eq!(resolve(0, 4, &["_bar", "test.dll", "foo"]), Some(0x1000));
+        assert_eq!(resolve(0, 2, &["_bar", "test.dll"]), None);
+    }
+}
@@ -0,0 +1,112 @@
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{be_u32, le_u16, le_u32};
+
+use crate::parsers::nul_terminated_str;
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// Parses `n` nul-terminated symbol names.
+fn symbol_names<'a, E>(input: &'a [u8], n: usize) -> nom::IResult<&'a [u8], Vec<&'a str>, E>
+where
+    E: NomError<'a>,
+{
+    context("Symbol names", count(nul_terminated_str, n))(input)
+}
+
+/// First `/` member, in big endian for compatibility with Unix archives: the offset of the
+/// member header defining each symbol, in member order.
+#[derive(Debug)]
+pub struct FirstLinkerMember<'a> {
+    pub offsets: Vec<u32>,
+    pub names: Vec<&'a str>,
+}
+
+impl<'a> FirstLinkerMember<'a> {
+    /// Offset of the header of the member defining `name`.
+    pub fn find(&self, name: &str) -> Option<u32> {
+        self.names
+            .iter()
+            .position(|n| *n == name)
+            .map(|idx| self.offsets[idx])
+    }
+}
+
+impl<'a> fmt::Display for FirstLinkerMember<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        for (name, member_offset) in self.names.iter().zip(&self.offsets) {
+            write!(f, "{offset}0x{:08x}: {}\n", member_offset, name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for FirstLinkerMember<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, number_of_symbols) = context("Number of symbols", be_u32)(input)?;
+        let (rest, offsets) =
+            context("Member offsets", count(be_u32, number_of_symbols as usize))(rest)?;
+        let (rest, names) = symbol_names(rest, number_of_symbols as usize)?;
+
+        Ok((rest, Self { offsets, names }))
+    }
+}
+
+/// Second `/` member, written by Microsoft tools: member offsets, and symbols sorted by name
+/// with the 1-based index of their member offset.
+#[derive(Debug)]
+pub struct SecondLinkerMember<'a> {
+    pub offsets: Vec<u32>,
+    pub indices: Vec<u16>,
+    pub names: Vec<&'a str>,
+}
+
+impl<'a> SecondLinkerMember<'a> {
+    /// Offset of the header of the member defining `name`.
+    pub fn find(&self, name: &str) -> Option<u32> {
+        let idx = self.names.binary_search(&name).ok()?;
+        let member = (self.indices[idx] as usize).checked_sub(1)?;
+        self.offsets.get(member).copied()
+    }
+}
+
+impl<'a> fmt::Display for SecondLinkerMember<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        for (name, index) in self.names.iter().zip(&self.indices) {
+            write!(f, "{offset}{}: {}\n", index, name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for SecondLinkerMember<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, number_of_members) = context("Number of members", le_u32)(input)?;
+        let (rest, offsets) =
+            context("Member offsets", count(le_u32, number_of_members as usize))(rest)?;
+        let (rest, number_of_symbols) = context("Number of symbols", le_u32)(rest)?;
+        let (rest, indices) = context(
+            "Symbol member indices",
+            count(le_u16, number_of_symbols as usize),
+        )(rest)?;
+        let (rest, names) = symbol_names(rest, number_of_symbols as usize)?;
+
+        Ok((
+            rest,
+            Self {
+                offsets,
+                indices,
+                names,
+            },
+        ))
+    }
+}
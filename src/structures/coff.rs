@@ -0,0 +1,241 @@
+use nom::error::context;
+use nom::multi::count;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+use super::SectionHeader;
+
+mod aux_symbol;
+pub use aux_symbol::{
+    CoffAuxSymbol, ComdatSelection, IMAGE_WEAK_EXTERN_SEARCH_ALIAS,
+    IMAGE_WEAK_EXTERN_SEARCH_LIBRARY, IMAGE_WEAK_EXTERN_SEARCH_NOLIBRARY,
+};
+
+mod header;
+pub use header::{BigObjHeader, CoffHeader, BIGOBJ_CLASS_ID};
+
+mod line_number;
+pub(crate) use line_number::section_line_numbers;
+pub use line_number::{CoffLineNumber, FunctionLines};
+
+mod relocation;
+pub(crate) use relocation::section_relocations;
+pub use relocation::{
+    Amd64RelocationType, Arm64RelocationType, CoffRelocation, I386RelocationType, RelocationType,
+};
+
+mod string_table;
+pub use string_table::StringTable;
+
+mod symbol;
+pub use symbol::{
+    CoffSymbol, StorageClass, SymbolTable, BIGOBJ_SYMBOL_SIZE, IMAGE_SYM_ABSOLUTE, IMAGE_SYM_DEBUG,
+    IMAGE_SYM_UNDEFINED, SYMBOL_SIZE,
+};
+
+/// Bare COFF file, as produced by compilers and assemblers.
+#[derive(Debug)]
+pub struct CoffObject<'a> {
+    pub(super) data: &'a [u8],
+    pub header: CoffHeader,
+    pub sections: Vec<SectionHeader<'a>>,
+    /// Relocations of each section, in section order.
+    pub relocations: Vec<Vec<CoffRelocation>>,
+    /// Line numbers of each section, in section order.
+    pub line_numbers: Vec<Vec<CoffLineNumber>>,
+    pub symbol_table: Option<SymbolTable<'a>>,
+}
+
+impl<'a> CoffObject<'a> {
+    pub fn section_data(&self, section: &SectionHeader<'a>) -> Option<&'a [u8]> {
+        // Uninitialized data has no raw data, whatever its size.
+        if section.pointer_to_raw_data == 0 {
+            return None;
+        }
+        self.data
+            .get(section.pointer_to_raw_data as usize..)?
+            .get(..section.size_of_raw_data as usize)
+    }
+
+    /// Name of `section`, long names being looked up in the string table.
+    pub fn section_name(&self, section: &SectionHeader<'a>) -> Option<&'a str> {
+        let string_table = self
+            .symbol_table
+            .as_ref()
+            .and_then(|symbol_table| symbol_table.string_table.as_ref());
+        section.resolve_name(string_table)
+    }
+
+    /// Source lines of every function with line numbers.
+    pub fn function_lines(&self) -> Vec<FunctionLines<'a>> {
+        let symbol_table = match &self.symbol_table {
+            Some(symbol_table) => symbol_table,
+            None => return Vec::new(),
+        };
+        self.line_numbers
+            .iter()
+            .flat_map(|line_numbers| symbol_table.function_lines(line_numbers))
+            .collect()
+    }
+
+    /// Symbol at `index` in the symbol table.
+    pub fn symbol(&self, index: u32) -> Option<&CoffSymbol<'a>> {
+        self.symbol_table.as_ref()?.get(index)
+    }
+}
+
+impl<'a> fmt::Display for CoffObject<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}header:\n{:width$}", self.header)?;
+        write!(f, "{offset}sections:\n")?;
+        for ((section, relocations), line_numbers) in self
+            .sections
+            .iter()
+            .zip(&self.relocations)
+            .zip(&self.line_numbers)
+        {
+            write!(f, "{:width$}", section)?;
+            if !relocations.is_empty() {
+                write!(f, "{offset}  relocations:\n")?;
+                for relocation in relocations {
+                    write!(f, "{:width$}", relocation, width = width + 1)?;
+                }
+            }
+            if !line_numbers.is_empty() {
+                write!(f, "{offset}  line_numbers:\n")?;
+                for line_number in line_numbers {
+                    write!(f, "{:width$}", line_number, width = width + 1)?;
+                }
+            }
+            write!(f, "\n")?;
+        }
+        if let Some(symbol_table) = &self.symbol_table {
+            write!(f, "{offset}symbols:\n{:width$}", symbol_table)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for CoffObject<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, header) = CoffHeader::parse(input)?;
+        let (_, sections) = context(
+            "Section headers",
+            count(SectionHeader::parse, header.number_of_sections() as usize),
+        )(rest)?;
+
+        let mut relocations = Vec::with_capacity(sections.len());
+        let mut line_numbers = Vec::with_capacity(sections.len());
+        for section in &sections {
+            relocations.push(section_relocations(input, section, header.machine())?);
+            line_numbers.push(section_line_numbers(input, section)?);
+        }
+
+        let symbol_table = if header.pointer_to_symbol_table() == 0 {
+            None
+        } else {
+            let symbol_size = if header.is_bigobj() {
+                BIGOBJ_SYMBOL_SIZE
+            } else {
+                SYMBOL_SIZE
+            };
+            Some(SymbolTable::parse_at(
+                input,
+                header.pointer_to_symbol_table() as usize,
+                header.number_of_symbols() as usize,
+                symbol_size,
+            )?)
+        };
+
+        Ok((
+            &[],
+            Self {
+                data: input,
+                header,
+                sections,
+                relocations,
+                line_numbers,
+                symbol_table,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn section(name: &[u8; 8], size: u32, pointer: u32, relocations: (u32, u16)) -> Vec<u8> {
+        let mut section = name.to_vec();
+        section.extend_from_slice(&[0; 8]);
+        section.extend_from_slice(&size.to_le_bytes());
+        section.extend_from_slice(&pointer.to_le_bytes());
+        section.extend_from_slice(&relocations.0.to_le_bytes());
+        section.extend_from_slice(&[0; 4]);
+        section.extend_from_slice(&relocations.1.to_le_bytes());
+        section.extend_from_slice(&[0; 2]);
+        section.extend_from_slice(&0x6030_0020u32.to_le_bytes());
+        section
+    }
+
+    #[test]
+    fn object() {
+        let mut data = 0x14cu16.to_le_bytes().to_vec();
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&114u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend(section(b".text\0\0\0", 4, 100, (104, 1)));
+        // Uninitialized data with a long name
+        data.extend(section(b"/4\0\0\0\0\0\0", 0x10, 0, (0, 0)));
+        data.extend_from_slice(b"\xe8\0\0\0");
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0x14u16.to_le_bytes());
+        data.extend_from_slice(b"main\0\0\0\0\0\0\0\0\x01\0\x20\0\x02\0");
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(b".debug_info\0");
+
+        let (_, object) = CoffObject::parse::<Error>(&data).unwrap();
+        assert_eq!(object.sections.len(), 2);
+        let (text, bss) = (&object.sections[0], &object.sections[1]);
+        assert_eq!(object.section_name(text), Some(".text"));
+        assert_eq!(object.section_data(text), Some(&b"\xe8\0\0\0"[..]));
+        assert_eq!(text.characteristics.alignment(), Some(4));
+        assert_eq!(object.section_name(bss), Some(".debug_info"));
+        assert_eq!(object.section_data(bss), None);
+
+        assert_eq!(object.relocations[0].len(), 1);
+        assert_eq!(object.relocations[0][0].virtual_address, 1);
+        assert_eq!(
+            object.relocations[0][0].relocation_type,
+            RelocationType::I386(I386RelocationType::Rel32)
+        );
+        assert!(object.relocations[1].is_empty());
+
+        let symbol_table = object.symbol_table.as_ref().unwrap();
+        assert_eq!(symbol_table.symbols.len(), 1);
+        assert_eq!(symbol_table.symbols[0].name, "main");
+        assert!(object.function_lines().is_empty());
+    }
+
+    #[test]
+    fn truncated_relocations() {
+        let mut data = 0x14cu16.to_le_bytes().to_vec();
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        data.extend(section(b".text\0\0\0", 0, 0, (60, 2)));
+        data.extend_from_slice(&[0; 10]);
+        assert!(CoffObject::parse::<Error>(&data).is_err());
+    }
+}
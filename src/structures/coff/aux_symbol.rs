@@ -0,0 +1,275 @@
+use nom::bytes::complete::take;
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::NomError;
+
+use std::fmt;
+
+use super::symbol::{StorageClass, BIGOBJ_SYMBOL_SIZE};
+
+/// `IMAGE_COMDAT_SELECT_*`, how the linker picks among duplicate COMDAT sections.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComdatSelection {
+    NoDuplicates,
+    Any,
+    SameSize,
+    ExactMatch,
+    /// Linked if and only if the section given by `number` is.
+    Associative,
+    Largest,
+    Newest,
+    Other(u8),
+}
+
+impl From<u8> for ComdatSelection {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::NoDuplicates,
+            2 => Self::Any,
+            3 => Self::SameSize,
+            4 => Self::ExactMatch,
+            5 => Self::Associative,
+            6 => Self::Largest,
+            7 => Self::Newest,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for ComdatSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoDuplicates => f.write_str("no duplicates"),
+            Self::Any => f.write_str("any"),
+            Self::SameSize => f.write_str("same size"),
+            Self::ExactMatch => f.write_str("exact match"),
+            Self::Associative => f.write_str("associative"),
+            Self::Largest => f.write_str("largest"),
+            Self::Newest => f.write_str("newest"),
+            Self::Other(value) => write!(f, "0x{:x}", value),
+        }
+    }
+}
+
+/// Search for the default symbol of a weak external.
+pub const IMAGE_WEAK_EXTERN_SEARCH_NOLIBRARY: u32 = 1;
+pub const IMAGE_WEAK_EXTERN_SEARCH_LIBRARY: u32 = 2;
+pub const IMAGE_WEAK_EXTERN_SEARCH_ALIAS: u32 = 3;
+
+/// Auxiliary symbol records, whose format depends on the symbol they follow.
+#[derive(Debug)]
+pub enum CoffAuxSymbol<'a> {
+    /// Follows the definition of a function symbol.
+    FunctionDefinition {
+        /// Index of the matching `.bf` symbol.
+        tag_index: u32,
+        total_size: u32,
+        pointer_to_linenumber: u32,
+        /// Index of the next function symbol, 0 for the last one.
+        pointer_to_next_function: u32,
+    },
+    /// Follows the `.bf` and `.ef` symbols.
+    BeginEndFunction {
+        /// Line number in the source file, relative to the start of the function for `.ef`.
+        line_number: u16,
+        /// Index of the next `.bf` symbol, only set on `.bf` symbols.
+        pointer_to_next_function: u32,
+    },
+    WeakExternal {
+        /// Index of the symbol used when the weak external is not defined.
+        tag_index: u32,
+        /// One of the `IMAGE_WEAK_EXTERN_SEARCH_*` values.
+        characteristics: u32,
+    },
+    /// Source file name, spanning every auxiliary record of the `.file` symbol.
+    File(&'a str),
+    /// Follows the symbol of a section.
+    SectionDefinition {
+        length: u32,
+        number_of_relocations: u16,
+        number_of_linenumbers: u16,
+        checksum: u32,
+        /// Associated section for associative COMDAT sections, 1-based.
+        number: u32,
+        selection: Option<ComdatSelection>,
+    },
+    ClrToken {
+        symbol_table_index: u32,
+    },
+    /// Record in an unknown format.
+    Raw(&'a [u8]),
+}
+
+impl<'a> fmt::Display for CoffAuxSymbol<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        match self {
+            Self::FunctionDefinition {
+                tag_index,
+                total_size,
+                pointer_to_linenumber,
+                pointer_to_next_function,
+            } => write!(
+                f,
+                "{offset}function: tag {}, size 0x{:x}, line numbers 0x{:x}, next {}\n",
+                tag_index, total_size, pointer_to_linenumber, pointer_to_next_function
+            ),
+            Self::BeginEndFunction {
+                line_number,
+                pointer_to_next_function,
+            } => write!(
+                f,
+                "{offset}line {}, next {}\n",
+                line_number, pointer_to_next_function
+            ),
+            Self::WeakExternal {
+                tag_index,
+                characteristics,
+            } => write!(
+                f,
+                "{offset}weak external: default {}, search {}\n",
+                tag_index, characteristics
+            ),
+            Self::File(name) => write!(f, "{offset}file: {}\n", name),
+            Self::SectionDefinition {
+                length,
+                number_of_relocations,
+                number_of_linenumbers,
+                checksum,
+                number,
+                selection,
+            } => {
+                write!(
+                    f,
+                    "{offset}section: length 0x{:x}, relocations {}, line numbers {}, checksum 0x{:08x}",
+                    length, number_of_relocations, number_of_linenumbers, checksum
+                )?;
+                if let Some(selection) = selection {
+                    write!(f, ", selection {}, number {}", selection, number)?;
+                }
+                write!(f, "\n")
+            }
+            Self::ClrToken { symbol_table_index } => {
+                write!(f, "{offset}CLR token: symbol {}\n", symbol_table_index)
+            }
+            Self::Raw(data) => write!(f, "{offset}{:02x?}\n", data),
+        }
+    }
+}
+
+impl<'a> CoffAuxSymbol<'a> {
+    /// Parses the `number_of_aux_symbols` records following a symbol, given the symbol's
+    /// fields selecting their format.
+    pub(super) fn parse<E>(
+        input: &'a [u8],
+        number_of_aux_symbols: usize,
+        name: &str,
+        storage_class: StorageClass,
+        section_number: i32,
+        is_function: bool,
+        symbol_size: usize,
+    ) -> nom::IResult<&'a [u8], Vec<Self>, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, data) = context(
+            "Auxiliary symbols",
+            take(number_of_aux_symbols * symbol_size),
+        )(input)?;
+        if number_of_aux_symbols == 0 {
+            return Ok((rest, Vec::new()));
+        }
+
+        if storage_class == StorageClass::File {
+            // Like symbol names, a file name that is not valid UTF8 is left empty
+            let name = data.split(|b| *b == 0).next().unwrap_or_default();
+            let name = std::str::from_utf8(name).unwrap_or_default();
+            return Ok((rest, vec![Self::File(name)]));
+        }
+
+        let mut aux = Vec::with_capacity(number_of_aux_symbols);
+        for record in data.chunks(symbol_size) {
+            let aux_symbol = match storage_class {
+                StorageClass::External if is_function && section_number > 0 => {
+                    let (
+                        _,
+                        (tag_index, total_size, pointer_to_linenumber, pointer_to_next_function),
+                    ) = context(
+                        "Function definition",
+                        tuple((le_u32, le_u32, le_u32, le_u32)),
+                    )(record)?;
+                    Self::FunctionDefinition {
+                        tag_index,
+                        total_size,
+                        pointer_to_linenumber,
+                        pointer_to_next_function,
+                    }
+                }
+                StorageClass::Function if name == ".bf" || name == ".ef" => {
+                    let (_, (_, line_number, _, pointer_to_next_function)) =
+                        context(
+                            "Begin/end function",
+                            tuple((le_u32, le_u16, take(6usize), le_u32)),
+                        )(record)?;
+                    Self::BeginEndFunction {
+                        line_number,
+                        pointer_to_next_function,
+                    }
+                }
+                // Weak externals are described as undefined externals, but some toolchains use a
+                // dedicated storage class.
+                StorageClass::WeakExternal | StorageClass::External if section_number == 0 => {
+                    let (_, (tag_index, characteristics)) =
+                        context("Weak external", tuple((le_u32, le_u32)))(record)?;
+                    Self::WeakExternal {
+                        tag_index,
+                        characteristics,
+                    }
+                }
+                StorageClass::Static => {
+                    let (
+                        _,
+                        (
+                            length,
+                            number_of_relocations,
+                            number_of_linenumbers,
+                            checksum,
+                            number,
+                            selection,
+                            _,
+                            high_number,
+                        ),
+                    ) = context(
+                        "Section definition",
+                        tuple((le_u32, le_u16, le_u16, le_u32, le_u16, le_u8, le_u8, le_u16)),
+                    )(record)?;
+                    // Only bigobj files use the high bits, others may leave garbage there
+                    let number = if symbol_size == BIGOBJ_SYMBOL_SIZE {
+                        (high_number as u32) << 16 | number as u32
+                    } else {
+                        number as u32
+                    };
+                    Self::SectionDefinition {
+                        length,
+                        number_of_relocations,
+                        number_of_linenumbers,
+                        checksum,
+                        number,
+                        selection: (selection != 0).then(|| selection.into()),
+                    }
+                }
+                StorageClass::ClrToken => {
+                    let (_, (_, _, symbol_table_index)) =
+                        context("CLR token", tuple((le_u8, le_u8, le_u32)))(record)?;
+                    Self::ClrToken { symbol_table_index }
+                }
+                _ => Self::Raw(record),
+            };
+            aux.push(aux_symbol);
+        }
+
+        Ok((rest, aux))
+    }
+}
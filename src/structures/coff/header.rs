@@ -0,0 +1,271 @@
+use nom::bytes::complete::{tag, take};
+use nom::combinator::verify;
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32};
+use nom::sequence::tuple;
+
+use crate::enums::FileMachine;
+use crate::structures::{FileHeader, Guid};
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// Class ID identifying `ANON_OBJECT_HEADER_BIGOBJ`.
+pub const BIGOBJ_CLASS_ID: Guid = Guid::new(
+    0xd1baa1c7,
+    0xbaee,
+    0x4ba9,
+    [0xaf, 0x20, 0xfa, 0xf6, 0x6a, 0xa4, 0xdc, 0xb8],
+);
+
+/// `ANON_OBJECT_HEADER_BIGOBJ`, emitted by `cl /bigobj` when sections overflow 16 bits.
+#[derive(Debug)]
+pub struct BigObjHeader {
+    pub version: u16,
+    pub machine: FileMachine,
+    pub time_date_stamp: u32,
+    pub size_of_data: u32,
+    pub flags: u32,
+    pub meta_data_size: u32,
+    pub meta_data_offset: u32,
+    pub number_of_sections: u32,
+    pub pointer_to_symbol_table: u32,
+    pub number_of_symbols: u32,
+}
+
+impl fmt::Display for BigObjHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}version: 0x{:x}\n", self.version)?;
+        write!(f, "{offset}machine: {}\n", self.machine)?;
+        write!(f, "{offset}time_date_stamp: 0x{:x}\n", self.time_date_stamp)?;
+        write!(f, "{offset}size_of_data: 0x{:x}\n", self.size_of_data)?;
+        write!(f, "{offset}flags: 0x{:x}\n", self.flags)?;
+        write!(f, "{offset}meta_data_size: 0x{:x}\n", self.meta_data_size)?;
+        write!(
+            f,
+            "{offset}meta_data_offset: 0x{:x}\n",
+            self.meta_data_offset
+        )?;
+        write!(
+            f,
+            "{offset}number_of_sections: 0x{:x}\n",
+            self.number_of_sections
+        )?;
+        write!(
+            f,
+            "{offset}pointer_to_symbol_table: 0x{:x}\n",
+            self.pointer_to_symbol_table
+        )?;
+        write!(
+            f,
+            "{offset}number_of_symbols: 0x{:x}\n",
+            self.number_of_symbols
+        )
+    }
+}
+
+impl<'a> Parse<'a> for BigObjHeader {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            rest,
+            (
+                _,
+                version,
+                machine,
+                time_date_stamp,
+                _,
+                size_of_data,
+                flags,
+                meta_data_size,
+                meta_data_offset,
+                number_of_sections,
+                pointer_to_symbol_table,
+                number_of_symbols,
+            ),
+        ) = context(
+            "Bigobj header",
+            tuple((
+                // `IMAGE_FILE_MACHINE_UNKNOWN` and 0xffff, telling it apart from a COFF header
+                tag(b"\x00\x00\xff\xff"),
+                verify(le_u16, |version| *version >= 2),
+                FileMachine::parse,
+                le_u32,
+                verify(Guid::parse, |class_id| *class_id == BIGOBJ_CLASS_ID),
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+            )),
+        )(input)?;
+
+        Ok((
+            rest,
+            Self {
+                version,
+                machine,
+                time_date_stamp,
+                size_of_data,
+                flags,
+                meta_data_size,
+                meta_data_offset,
+                number_of_sections,
+                pointer_to_symbol_table,
+                number_of_symbols,
+            },
+        ))
+    }
+}
+
+/// Header of a COFF object, in either the regular or the bigobj format.
+#[derive(Debug)]
+pub enum CoffHeader {
+    Coff(FileHeader),
+    BigObj(BigObjHeader),
+}
+
+impl CoffHeader {
+    pub fn machine(&self) -> &FileMachine {
+        match self {
+            Self::Coff(header) => &header.machine,
+            Self::BigObj(header) => &header.machine,
+        }
+    }
+
+    pub fn time_date_stamp(&self) -> u32 {
+        match self {
+            Self::Coff(header) => header.time_date_stamp,
+            Self::BigObj(header) => header.time_date_stamp,
+        }
+    }
+
+    pub fn number_of_sections(&self) -> u32 {
+        match self {
+            Self::Coff(header) => header.number_of_sections as u32,
+            Self::BigObj(header) => header.number_of_sections,
+        }
+    }
+
+    pub fn pointer_to_symbol_table(&self) -> u32 {
+        match self {
+            Self::Coff(header) => header.pointer_to_symbol_table,
+            Self::BigObj(header) => header.pointer_to_symbol_table,
+        }
+    }
+
+    pub fn number_of_symbols(&self) -> u32 {
+        match self {
+            Self::Coff(header) => header.number_of_symbols,
+            Self::BigObj(header) => header.number_of_symbols,
+        }
+    }
+
+    pub fn is_bigobj(&self) -> bool {
+        matches!(self, Self::BigObj(_))
+    }
+}
+
+impl fmt::Display for CoffHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default();
+        match self {
+            Self::Coff(header) => write!(f, "{:width$}", header),
+            Self::BigObj(header) => write!(f, "{:width$}", header),
+        }
+    }
+}
+
+impl<'a> Parse<'a> for CoffHeader {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        if let Ok((rest, header)) = BigObjHeader::parse::<E>(input) {
+            return Ok((rest, Self::BigObj(header)));
+        }
+        let (rest, file_header) = FileHeader::parse(input)?;
+        // Only images need an optional header, but nothing prevents an object from having one
+        let (rest, _) = context(
+            "Optional header",
+            take(file_header.size_of_optional_header as usize),
+        )(rest)?;
+
+        Ok((rest, Self::Coff(file_header)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::ImportObject;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn bigobj_header(version: u16, class_id: &[u8; 16]) -> Vec<u8> {
+        let mut header = vec![0x00, 0x00, 0xff, 0xff];
+        header.extend_from_slice(&version.to_le_bytes());
+        header.extend_from_slice(&0x8664u16.to_le_bytes());
+        header.extend_from_slice(&0x1234_5678u32.to_le_bytes());
+        header.extend_from_slice(class_id);
+        for value in [0u32, 0, 0, 0, 0x1_0001, 0x100, 3] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        header
+    }
+
+    const CLASS_ID: [u8; 16] = [
+        0xc7, 0xa1, 0xba, 0xd1, 0xee, 0xba, 0xa9, 0x4b, 0xaf, 0x20, 0xfa, 0xf6, 0x6a, 0xa4, 0xdc,
+        0xb8,
+    ];
+
+    #[test]
+    fn bigobj() {
+        let data = bigobj_header(2, &CLASS_ID);
+        let (rest, header) = CoffHeader::parse::<Error>(&data).unwrap();
+        assert!(rest.is_empty());
+        assert!(header.is_bigobj());
+        assert_eq!(*header.machine(), FileMachine::MachineAMD64);
+        assert_eq!(header.time_date_stamp(), 0x1234_5678);
+        assert_eq!(header.number_of_sections(), 0x1_0001);
+        assert_eq!(header.pointer_to_symbol_table(), 0x100);
+        assert_eq!(header.number_of_symbols(), 3);
+        assert!(ImportObject::parse::<Error>(&data).is_err());
+    }
+
+    #[test]
+    fn import_object_is_not_bigobj() {
+        // Same signature, but version 0 and names instead of a class ID
+        let mut data = vec![0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0x64, 0x86];
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&12u32.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&0x0004u16.to_le_bytes());
+        data.extend_from_slice(b"foo\0bar.dll\0");
+        assert!(BigObjHeader::parse::<Error>(&data).is_err());
+        let (_, import) = ImportObject::parse::<Error>(&data).unwrap();
+        assert_eq!(import.symbol_name, "foo");
+        assert_eq!(import.dll_name, "bar.dll");
+
+        // Version 1 import objects or unknown class IDs are not bigobj either
+        assert!(BigObjHeader::parse::<Error>(&bigobj_header(1, &CLASS_ID)).is_err());
+        assert!(BigObjHeader::parse::<Error>(&bigobj_header(2, &[0; 16])).is_err());
+    }
+
+    #[test]
+    fn coff() {
+        let mut data = 0x14cu16.to_le_bytes().to_vec();
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        let (rest, header) = CoffHeader::parse::<Error>(&data).unwrap();
+        assert!(rest.is_empty());
+        assert!(!header.is_bigobj());
+        assert_eq!(*header.machine(), FileMachine::MachineI386);
+        assert_eq!(header.number_of_sections(), 2);
+    }
+}
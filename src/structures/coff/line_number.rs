@@ -0,0 +1,257 @@
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_u16, le_u32};
+use nom::sequence::tuple;
+
+use crate::parsers::at;
+use crate::structures::SectionHeader;
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+use super::{CoffAuxSymbol, SymbolTable};
+
+/// `IMAGE_LINENUMBER`
+#[derive(Debug, Clone, Copy)]
+pub enum CoffLineNumber {
+    /// Starts the line numbers of the function defined by the given symbol.
+    Function { symbol_table_index: u32 },
+    /// Line relative to the start of the current function, 1 being its `.bf` line.
+    Line {
+        virtual_address: u32,
+        line_number: u16,
+    },
+}
+
+impl fmt::Display for CoffLineNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        match self {
+            Self::Function { symbol_table_index } => {
+                write!(f, "{offset}function: symbol {}\n", symbol_table_index)
+            }
+            Self::Line {
+                virtual_address,
+                line_number,
+            } => write!(f, "{offset}0x{:08x}: {}\n", virtual_address, line_number),
+        }
+    }
+}
+
+impl<'a> Parse<'a> for CoffLineNumber {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (value, line_number)) =
+            context("COFF line number", tuple((le_u32, le_u16)))(input)?;
+        let line_number = match line_number {
+            0 => Self::Function {
+                symbol_table_index: value,
+            },
+            line_number => Self::Line {
+                virtual_address: value,
+                line_number,
+            },
+        };
+
+        Ok((rest, line_number))
+    }
+}
+
+/// Parses the line numbers of `section` in the whole file `input`.
+pub(crate) fn section_line_numbers<'a, E>(
+    input: &'a [u8],
+    section: &SectionHeader<'a>,
+) -> Result<Vec<CoffLineNumber>, nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    if section.number_of_linenumbers == 0 {
+        return Ok(Vec::new());
+    }
+
+    let (_, line_numbers) = context(
+        "Section line numbers",
+        count(
+            CoffLineNumber::parse,
+            section.number_of_linenumbers as usize,
+        ),
+    )(at(input, section.pointer_to_linenumbers as usize)?)?;
+
+    Ok(line_numbers)
+}
+
+/// Source lines of a function, from its line numbers and `.bf` symbol.
+#[derive(Debug)]
+pub struct FunctionLines<'a> {
+    pub symbol_table_index: u32,
+    pub name: &'a str,
+    /// Source line of the start of the function, as given by its `.bf` symbol.
+    pub base_line: Option<u32>,
+    /// Address of each line with its absolute source line, or its relative one without
+    /// `base_line`.
+    pub lines: Vec<(u32, u32)>,
+}
+
+impl<'a> fmt::Display for FunctionLines<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}{}", self.name)?;
+        if let Some(base_line) = self.base_line {
+            write!(f, " (line {})", base_line)?;
+        }
+        write!(f, "\n")?;
+        for (address, line) in &self.lines {
+            write!(f, "{offset}  0x{:08x}: {}\n", address, line)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> SymbolTable<'a> {
+    /// Groups `line_numbers` by function, turning them into source lines with the line of the
+    /// `.bf` symbol of each function.
+    pub fn function_lines(&self, line_numbers: &[CoffLineNumber]) -> Vec<FunctionLines<'a>> {
+        let mut functions: Vec<FunctionLines<'a>> = Vec::new();
+        for line_number in line_numbers {
+            match *line_number {
+                CoffLineNumber::Function { symbol_table_index } => {
+                    let name = self
+                        .get(symbol_table_index)
+                        .map(|symbol| symbol.name)
+                        .unwrap_or_default();
+                    functions.push(FunctionLines {
+                        symbol_table_index,
+                        name,
+                        base_line: self.function_base_line(symbol_table_index),
+                        lines: Vec::new(),
+                    });
+                }
+                CoffLineNumber::Line {
+                    virtual_address,
+                    line_number,
+                } => {
+                    // Lines before any function record are malformed
+                    if let Some(function) = functions.last_mut() {
+                        let line = match function.base_line {
+                            Some(base_line) => base_line + line_number as u32 - 1,
+                            None => line_number as u32,
+                        };
+                        function.lines.push((virtual_address, line));
+                    }
+                }
+            }
+        }
+        functions
+    }
+
+    /// Line of the `.bf` symbol of the function defined by the symbol at `index`.
+    fn function_base_line(&self, index: u32) -> Option<u32> {
+        let function = self.get(index)?;
+        // The function definition points at the `.bf` symbol, which otherwise follows
+        let bf_index = match function.aux.first() {
+            Some(CoffAuxSymbol::FunctionDefinition { tag_index, .. }) if *tag_index != 0 => {
+                *tag_index
+            }
+            _ => index + 1 + function.number_of_aux_symbols as u32,
+        };
+        let bf = self.get(bf_index).filter(|symbol| symbol.name == ".bf")?;
+        match bf.aux.first() {
+            Some(CoffAuxSymbol::BeginEndFunction { line_number, .. }) => Some(*line_number as u32),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::coff::SYMBOL_SIZE;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn symbol(name: &[u8; 8], symbol_type: u16, storage_class: u8, aux: &[u8]) -> Vec<u8> {
+        let mut symbol = name.to_vec();
+        symbol.extend_from_slice(&0u32.to_le_bytes());
+        symbol.extend_from_slice(&1i16.to_le_bytes());
+        symbol.extend_from_slice(&symbol_type.to_le_bytes());
+        symbol.push(storage_class);
+        symbol.push(!aux.is_empty() as u8);
+        if !aux.is_empty() {
+            let mut aux = aux.to_vec();
+            aux.resize(SYMBOL_SIZE, 0);
+            symbol.extend(aux);
+        }
+        symbol
+    }
+
+    fn bf(line_number: u16) -> Vec<u8> {
+        let mut aux = vec![0; 4];
+        aux.extend_from_slice(&line_number.to_le_bytes());
+        symbol(b".bf\0\0\0\0\0", 0, 101, &aux)
+    }
+
+    fn line_number(value: u32, line_number: u16) -> Vec<u8> {
+        let mut data = value.to_le_bytes().to_vec();
+        data.extend_from_slice(&line_number.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn function_lines() {
+        // `main` points at its `.bf` symbol, `helper` is followed by it and `nobf` has none
+        let mut data = symbol(b"main\0\0\0\0", 0x20, 2, &2u32.to_le_bytes());
+        data.extend(bf(10));
+        data.extend(symbol(b"helper\0\0", 0x20, 2, &[]));
+        data.extend(bf(20));
+        data.extend(symbol(b"nobf\0\0\0\0", 0x20, 2, &[]));
+        data.extend_from_slice(&4u32.to_le_bytes());
+        let symbol_table = SymbolTable::parse_at::<Error>(&data, 0, 8, SYMBOL_SIZE).unwrap();
+
+        let mut section = vec![0; 40];
+        section[..5].copy_from_slice(b".text");
+        section[28..32].copy_from_slice(&40u32.to_le_bytes());
+        section[34..36].copy_from_slice(&8u16.to_le_bytes());
+        for (value, line) in [
+            (0x08, 1),
+            (0, 0),
+            (0x10, 1),
+            (0x14, 3),
+            (4, 0),
+            (0x30, 2),
+            (7, 0),
+            (0x40, 5),
+        ] {
+            section.extend(line_number(value, line));
+        }
+        let (_, header) = SectionHeader::parse::<Error>(&section).unwrap();
+        let line_numbers = section_line_numbers::<Error>(&section, &header).unwrap();
+        assert_eq!(line_numbers.len(), 8);
+
+        // The line before the first function is dropped
+        let functions = symbol_table.function_lines(&line_numbers);
+        let functions: Vec<_> = functions
+            .iter()
+            .map(|function| (function.name, function.base_line, function.lines.clone()))
+            .collect();
+        assert_eq!(
+            functions,
+            [
+                ("main", Some(10), vec![(0x10, 10), (0x14, 12)]),
+                ("helper", Some(20), vec![(0x30, 21)]),
+                ("nobf", None, vec![(0x40, 5)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_line_numbers() {
+        let mut section = vec![0; 40];
+        section[28..32].copy_from_slice(&40u32.to_le_bytes());
+        section[34..36].copy_from_slice(&2u16.to_le_bytes());
+        section.extend(line_number(0, 0));
+        let (_, header) = SectionHeader::parse::<Error>(&section).unwrap();
+        assert!(section_line_numbers::<Error>(&section, &header).is_err());
+    }
+}
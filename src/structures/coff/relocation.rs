@@ -0,0 +1,352 @@
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_u16, le_u32};
+use nom::sequence::tuple;
+
+use crate::enums::{FileMachine, SectionCharacteristics};
+use crate::parsers::at;
+use crate::structures::SectionHeader;
+use crate::NomError;
+
+use std::fmt;
+
+/// `IMAGE_REL_AMD64_*`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Amd64RelocationType {
+    Absolute,
+    Addr64,
+    Addr32,
+    Addr32Nb,
+    Rel32,
+    Rel32_1,
+    Rel32_2,
+    Rel32_3,
+    Rel32_4,
+    Rel32_5,
+    Section,
+    SecRel,
+    SecRel7,
+    Token,
+    SRel32,
+    Pair,
+    SSpan32,
+    Other(u16),
+}
+
+impl From<u16> for Amd64RelocationType {
+    fn from(value: u16) -> Self {
+        match value {
+            0x00 => Self::Absolute,
+            0x01 => Self::Addr64,
+            0x02 => Self::Addr32,
+            0x03 => Self::Addr32Nb,
+            0x04 => Self::Rel32,
+            0x05 => Self::Rel32_1,
+            0x06 => Self::Rel32_2,
+            0x07 => Self::Rel32_3,
+            0x08 => Self::Rel32_4,
+            0x09 => Self::Rel32_5,
+            0x0a => Self::Section,
+            0x0b => Self::SecRel,
+            0x0c => Self::SecRel7,
+            0x0d => Self::Token,
+            0x0e => Self::SRel32,
+            0x0f => Self::Pair,
+            0x10 => Self::SSpan32,
+            value => Self::Other(value),
+        }
+    }
+}
+
+/// `IMAGE_REL_I386_*`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum I386RelocationType {
+    Absolute,
+    Dir16,
+    Rel16,
+    Dir32,
+    Dir32Nb,
+    Seg12,
+    Section,
+    SecRel,
+    Token,
+    SecRel7,
+    Rel32,
+    Other(u16),
+}
+
+impl From<u16> for I386RelocationType {
+    fn from(value: u16) -> Self {
+        match value {
+            0x00 => Self::Absolute,
+            0x01 => Self::Dir16,
+            0x02 => Self::Rel16,
+            0x06 => Self::Dir32,
+            0x07 => Self::Dir32Nb,
+            0x09 => Self::Seg12,
+            0x0a => Self::Section,
+            0x0b => Self::SecRel,
+            0x0c => Self::Token,
+            0x0d => Self::SecRel7,
+            0x14 => Self::Rel32,
+            value => Self::Other(value),
+        }
+    }
+}
+
+/// `IMAGE_REL_ARM64_*`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arm64RelocationType {
+    Absolute,
+    Addr32,
+    Addr32Nb,
+    Branch26,
+    PageBaseRel21,
+    Rel21,
+    PageOffset12A,
+    PageOffset12L,
+    SecRel,
+    SecRelLow12A,
+    SecRelHigh12A,
+    SecRelLow12L,
+    Token,
+    Section,
+    Addr64,
+    Branch19,
+    Branch14,
+    Rel32,
+    Other(u16),
+}
+
+impl From<u16> for Arm64RelocationType {
+    fn from(value: u16) -> Self {
+        match value {
+            0x00 => Self::Absolute,
+            0x01 => Self::Addr32,
+            0x02 => Self::Addr32Nb,
+            0x03 => Self::Branch26,
+            0x04 => Self::PageBaseRel21,
+            0x05 => Self::Rel21,
+            0x06 => Self::PageOffset12A,
+            0x07 => Self::PageOffset12L,
+            0x08 => Self::SecRel,
+            0x09 => Self::SecRelLow12A,
+            0x0a => Self::SecRelHigh12A,
+            0x0b => Self::SecRelLow12L,
+            0x0c => Self::Token,
+            0x0d => Self::Section,
+            0x0e => Self::Addr64,
+            0x0f => Self::Branch19,
+            0x10 => Self::Branch14,
+            0x11 => Self::Rel32,
+            value => Self::Other(value),
+        }
+    }
+}
+
+/// Relocation type, whose meaning depends on the machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelocationType {
+    Amd64(Amd64RelocationType),
+    I386(I386RelocationType),
+    Arm64(Arm64RelocationType),
+    Other(u16),
+}
+
+impl RelocationType {
+    pub fn new(machine: &FileMachine, value: u16) -> Self {
+        match machine {
+            FileMachine::MachineAMD64 => Self::Amd64(value.into()),
+            FileMachine::MachineI386 => Self::I386(value.into()),
+            FileMachine::MachineARM64
+            | FileMachine::MachineARM64EC
+            | FileMachine::MachineARM64X => Self::Arm64(value.into()),
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for RelocationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Amd64(Amd64RelocationType::Other(value))
+            | Self::I386(I386RelocationType::Other(value))
+            | Self::Arm64(Arm64RelocationType::Other(value))
+            | Self::Other(value) => write!(f, "0x{:04x}", value),
+            Self::Amd64(relocation_type) => write!(f, "{:?}", relocation_type),
+            Self::I386(relocation_type) => write!(f, "{:?}", relocation_type),
+            Self::Arm64(relocation_type) => write!(f, "{:?}", relocation_type),
+        }
+    }
+}
+
+/// `IMAGE_RELOCATION`
+#[derive(Debug)]
+pub struct CoffRelocation {
+    /// Offset of the patched location from the start of the section.
+    pub virtual_address: u32,
+    pub symbol_table_index: u32,
+    pub relocation_type: RelocationType,
+}
+
+impl fmt::Display for CoffRelocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x}: {}, symbol {}\n",
+            self.virtual_address, self.relocation_type, self.symbol_table_index
+        )
+    }
+}
+
+impl CoffRelocation {
+    pub fn parse<'a, E>(input: &'a [u8], machine: &FileMachine) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (virtual_address, symbol_table_index, relocation_type)) =
+            context("COFF relocation", tuple((le_u32, le_u32, le_u16)))(input)?;
+
+        Ok((
+            rest,
+            Self {
+                virtual_address,
+                symbol_table_index,
+                relocation_type: RelocationType::new(machine, relocation_type),
+            },
+        ))
+    }
+}
+
+/// Parses the relocations of `section` in the whole file `input`.
+///
+/// When a section has more than 0xffff relocations, it is flagged with `LINK_N_RELOC_OVERFLOW` and
+/// the `virtual_address` of the first relocation holds the actual count, itself included.
+pub(crate) fn section_relocations<'a, E>(
+    input: &'a [u8],
+    section: &SectionHeader<'a>,
+    machine: &FileMachine,
+) -> Result<Vec<CoffRelocation>, nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    if section.number_of_relocations == 0 {
+        return Ok(Vec::new());
+    }
+
+    let data = at(input, section.pointer_to_relocations as usize)?;
+    let (data, number_of_relocations) = if section
+        .characteristics
+        .contains(SectionCharacteristics::LINK_N_RELOC_OVERFLOW)
+        && section.number_of_relocations == 0xffff
+    {
+        let (rest, first) = CoffRelocation::parse(data, machine)?;
+        (rest, (first.virtual_address as usize).saturating_sub(1))
+    } else {
+        (data, section.number_of_relocations as usize)
+    };
+    let (_, relocations) = context(
+        "Section relocations",
+        count(
+            |input| CoffRelocation::parse(input, machine),
+            number_of_relocations,
+        ),
+    )(data)?;
+
+    Ok(relocations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::Name;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn section<'a>(number_of_relocations: u16, characteristics: u32) -> SectionHeader<'a> {
+        SectionHeader {
+            name: Name::String(".text"),
+            physical_address: 0,
+            virtual_address: 0,
+            size_of_raw_data: 0,
+            pointer_to_raw_data: 0,
+            pointer_to_relocations: 4,
+            pointer_to_linenumbers: 0,
+            number_of_relocations,
+            number_of_linenumbers: 0,
+            characteristics: SectionCharacteristics::from_bits_retain(characteristics),
+        }
+    }
+
+    fn relocation(virtual_address: u32, symbol_table_index: u32, relocation_type: u16) -> Vec<u8> {
+        let mut relocation = virtual_address.to_le_bytes().to_vec();
+        relocation.extend_from_slice(&symbol_table_index.to_le_bytes());
+        relocation.extend_from_slice(&relocation_type.to_le_bytes());
+        relocation
+    }
+
+    #[test]
+    fn types_depend_on_machine() {
+        let mut data = vec![0; 4];
+        data.extend(relocation(0x10, 1, 0x04));
+        data.extend(relocation(0x20, 2, 0x14));
+
+        let amd64 = section_relocations::<Error>(&data, &section(2, 0), &FileMachine::MachineAMD64)
+            .unwrap();
+        assert_eq!(amd64.len(), 2);
+        assert_eq!(amd64[0].virtual_address, 0x10);
+        assert_eq!(amd64[0].symbol_table_index, 1);
+        assert_eq!(
+            amd64[0].relocation_type,
+            RelocationType::Amd64(Amd64RelocationType::Rel32)
+        );
+        assert_eq!(
+            amd64[1].relocation_type,
+            RelocationType::Amd64(Amd64RelocationType::Other(0x14))
+        );
+
+        let i386 =
+            section_relocations::<Error>(&data, &section(2, 0), &FileMachine::MachineI386).unwrap();
+        assert_eq!(
+            i386[1].relocation_type,
+            RelocationType::I386(I386RelocationType::Rel32)
+        );
+        let arm64ec =
+            section_relocations::<Error>(&data, &section(1, 0), &FileMachine::MachineARM64EC)
+                .unwrap();
+        assert_eq!(arm64ec.len(), 1);
+        assert_eq!(
+            arm64ec[0].relocation_type,
+            RelocationType::Arm64(Arm64RelocationType::PageBaseRel21)
+        );
+    }
+
+    #[test]
+    fn overflow_count() {
+        // The first relocation holds the count, itself included
+        let mut data = vec![0; 4];
+        data.extend(relocation(3, 0, 0));
+        data.extend(relocation(0x10, 1, 0x01));
+        data.extend(relocation(0x18, 2, 0x01));
+        data.extend(relocation(0x20, 3, 0x01));
+
+        let section = section(0xffff, 0x0100_0020);
+        let relocations =
+            section_relocations::<Error>(&data, &section, &FileMachine::MachineAMD64).unwrap();
+        assert_eq!(relocations.len(), 2);
+        assert_eq!(relocations[0].virtual_address, 0x10);
+        assert_eq!(relocations[1].symbol_table_index, 2);
+
+        // Without the flag, 0xffff is a count like any other
+        let section = self::section(0xffff, 0x20);
+        assert!(section_relocations::<Error>(&data, &section, &FileMachine::MachineAMD64).is_err());
+
+        // A count of 0 does not underflow
+        data[4..8].copy_from_slice(&0u32.to_le_bytes());
+        let section = self::section(0xffff, 0x0100_0020);
+        let relocations =
+            section_relocations::<Error>(&data, &section, &FileMachine::MachineAMD64).unwrap();
+        assert!(relocations.is_empty());
+    }
+}
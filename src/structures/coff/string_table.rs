@@ -0,0 +1,34 @@
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::error::context;
+use nom::number::complete::le_u32;
+
+use crate::{NomError, Parse};
+
+/// COFF string table, found right after the symbol table.
+#[derive(Debug)]
+pub struct StringTable<'a> {
+    /// Whole table, the leading size included: offsets are relative to its start.
+    pub data: &'a [u8],
+}
+
+impl<'a> StringTable<'a> {
+    /// Nul-terminated string at `offset`.
+    pub fn get(&self, offset: usize) -> Option<&'a str> {
+        let data = self.data.get(offset..)?;
+        let string = data.split(|b| *b == 0).next()?;
+        std::str::from_utf8(string).ok()
+    }
+}
+
+impl<'a> Parse<'a> for StringTable<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (_, size) = context("String table size", verify(le_u32, |size| *size >= 4))(input)?;
+        let (rest, data) = context("String table", take(size as usize))(input)?;
+
+        Ok((rest, Self { data }))
+    }
+}
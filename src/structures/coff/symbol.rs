@@ -0,0 +1,389 @@
+use nom::bytes::complete::take;
+use nom::combinator::map;
+use nom::error::context;
+use nom::number::complete::{le_i16, le_i32, le_u16, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::parsers::at;
+use crate::{NomError, Parse};
+
+use super::{CoffAuxSymbol, StringTable};
+
+use std::fmt;
+
+/// Size of a symbol table record, auxiliary records included.
+pub const SYMBOL_SIZE: usize = 18;
+/// Size of a symbol table record in bigobj files, whose section numbers take 32 bits.
+pub const BIGOBJ_SYMBOL_SIZE: usize = 20;
+
+/// `IMAGE_SYM_CLASS_*`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageClass {
+    EndOfFunction,
+    Null,
+    Automatic,
+    External,
+    Static,
+    Register,
+    ExternalDef,
+    Label,
+    UndefinedLabel,
+    MemberOfStruct,
+    Argument,
+    StructTag,
+    MemberOfUnion,
+    UnionTag,
+    TypeDefinition,
+    UndefinedStatic,
+    EnumTag,
+    MemberOfEnum,
+    RegisterParam,
+    BitField,
+    Block,
+    Function,
+    EndOfStruct,
+    File,
+    Section,
+    WeakExternal,
+    ClrToken,
+    Other(u8),
+}
+
+impl From<u8> for StorageClass {
+    fn from(value: u8) -> Self {
+        match value {
+            0xff => Self::EndOfFunction,
+            0 => Self::Null,
+            1 => Self::Automatic,
+            2 => Self::External,
+            3 => Self::Static,
+            4 => Self::Register,
+            5 => Self::ExternalDef,
+            6 => Self::Label,
+            7 => Self::UndefinedLabel,
+            8 => Self::MemberOfStruct,
+            9 => Self::Argument,
+            10 => Self::StructTag,
+            11 => Self::MemberOfUnion,
+            12 => Self::UnionTag,
+            13 => Self::TypeDefinition,
+            14 => Self::UndefinedStatic,
+            15 => Self::EnumTag,
+            16 => Self::MemberOfEnum,
+            17 => Self::RegisterParam,
+            18 => Self::BitField,
+            100 => Self::Block,
+            101 => Self::Function,
+            102 => Self::EndOfStruct,
+            103 => Self::File,
+            104 => Self::Section,
+            105 => Self::WeakExternal,
+            107 => Self::ClrToken,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for StorageClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EndOfFunction => f.write_str("end of function"),
+            Self::Null => f.write_str("null"),
+            Self::Automatic => f.write_str("automatic"),
+            Self::External => f.write_str("external"),
+            Self::Static => f.write_str("static"),
+            Self::Register => f.write_str("register"),
+            Self::ExternalDef => f.write_str("external def"),
+            Self::Label => f.write_str("label"),
+            Self::UndefinedLabel => f.write_str("undefined label"),
+            Self::MemberOfStruct => f.write_str("member of struct"),
+            Self::Argument => f.write_str("argument"),
+            Self::StructTag => f.write_str("struct tag"),
+            Self::MemberOfUnion => f.write_str("member of union"),
+            Self::UnionTag => f.write_str("union tag"),
+            Self::TypeDefinition => f.write_str("type definition"),
+            Self::UndefinedStatic => f.write_str("undefined static"),
+            Self::EnumTag => f.write_str("enum tag"),
+            Self::MemberOfEnum => f.write_str("member of enum"),
+            Self::RegisterParam => f.write_str("register param"),
+            Self::BitField => f.write_str("bit field"),
+            Self::Block => f.write_str("block"),
+            Self::Function => f.write_str("function"),
+            Self::EndOfStruct => f.write_str("end of struct"),
+            Self::File => f.write_str("file"),
+            Self::Section => f.write_str("section"),
+            Self::WeakExternal => f.write_str("weak external"),
+            Self::ClrToken => f.write_str("CLR token"),
+            Self::Other(value) => write!(f, "0x{:02x}", value),
+        }
+    }
+}
+
+/// Special section numbers.
+pub const IMAGE_SYM_UNDEFINED: i32 = 0;
+pub const IMAGE_SYM_ABSOLUTE: i32 = -1;
+pub const IMAGE_SYM_DEBUG: i32 = -2;
+
+/// `IMAGE_SYM_DTYPE_FUNCTION`, in the complex type nibble of `symbol_type`.
+const IMAGE_SYM_DTYPE_FUNCTION: u16 = 2;
+
+/// `IMAGE_SYMBOL`
+#[derive(Debug)]
+pub struct CoffSymbol<'a> {
+    /// Index in the symbol table, auxiliary records taking an index each.
+    pub index: u32,
+    /// Empty when it is not valid UTF8 or lies outside of the string table.
+    pub name: &'a str,
+    pub value: u32,
+    /// 1-based section index, or one of the special `IMAGE_SYM_*` values.
+    pub section_number: i32,
+    pub symbol_type: u16,
+    pub storage_class: StorageClass,
+    pub number_of_aux_symbols: u8,
+    pub aux: Vec<CoffAuxSymbol<'a>>,
+}
+
+impl<'a> CoffSymbol<'a> {
+    pub fn is_function(&self) -> bool {
+        (self.symbol_type >> 4) & 0x3 == IMAGE_SYM_DTYPE_FUNCTION
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        self.section_number == IMAGE_SYM_UNDEFINED
+    }
+
+    /// Source file name of a `.file` symbol.
+    pub fn file_name(&self) -> Option<&'a str> {
+        match self.aux.first() {
+            Some(CoffAuxSymbol::File(name)) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> fmt::Display for CoffSymbol<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(
+            f,
+            "{offset}[{}] {}: value 0x{:x}, section {}, type 0x{:x}, class {}\n",
+            self.index,
+            self.name,
+            self.value,
+            self.section_number,
+            self.symbol_type,
+            self.storage_class
+        )?;
+        for aux in &self.aux {
+            write!(f, "{:width$}", aux)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a symbol name: inline when it fits in 8 bytes, else an offset in the string table.
+/// Names that cannot be decoded are left empty rather than failing the whole table.
+fn symbol_name<'a, E>(
+    input: &'a [u8],
+    string_table: Option<&StringTable<'a>>,
+) -> nom::IResult<&'a [u8], &'a str, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, raw_name) = context("Symbol name", take(8usize))(input)?;
+    let name = if raw_name[..4] == [0, 0, 0, 0] {
+        let (_, offset) = le_u32(&raw_name[4..])?;
+        string_table.and_then(|string_table| string_table.get(offset as usize))
+    } else {
+        let raw_name = raw_name.split(|b| *b == 0).next().unwrap_or_default();
+        std::str::from_utf8(raw_name).ok()
+    };
+
+    Ok((rest, name.unwrap_or_default()))
+}
+
+impl<'a> CoffSymbol<'a> {
+    fn parse<E>(
+        input: &'a [u8],
+        index: u32,
+        string_table: Option<&StringTable<'a>>,
+        symbol_size: usize,
+    ) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, name) = symbol_name(input, string_table)?;
+        let (rest, value) = context("Symbol value", le_u32)(rest)?;
+        let (rest, section_number) = if symbol_size == BIGOBJ_SYMBOL_SIZE {
+            context("Symbol section number", le_i32)(rest)?
+        } else {
+            context("Symbol section number", map(le_i16, i32::from))(rest)?
+        };
+        let (rest, (symbol_type, storage_class, number_of_aux_symbols)) = context(
+            "Symbol",
+            tuple((le_u16, map(le_u8, StorageClass::from), le_u8)),
+        )(rest)?;
+        let is_function = (symbol_type >> 4) & 0x3 == IMAGE_SYM_DTYPE_FUNCTION;
+        let (rest, aux) = CoffAuxSymbol::parse(
+            rest,
+            number_of_aux_symbols as usize,
+            name,
+            storage_class,
+            section_number,
+            is_function,
+            symbol_size,
+        )?;
+
+        Ok((
+            rest,
+            Self {
+                index,
+                name,
+                value,
+                section_number,
+                symbol_type,
+                storage_class,
+                number_of_aux_symbols,
+                aux,
+            },
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct SymbolTable<'a> {
+    pub symbols: Vec<CoffSymbol<'a>>,
+    pub string_table: Option<StringTable<'a>>,
+}
+
+impl<'a> SymbolTable<'a> {
+    /// Parses the `number_of_symbols` records of `symbol_size` bytes found at `offset` in the
+    /// whole file `input`, and the string table following them.
+    pub fn parse_at<E>(
+        input: &'a [u8],
+        offset: usize,
+        number_of_symbols: usize,
+        symbol_size: usize,
+    ) -> Result<Self, nom::Err<E>>
+    where
+        E: NomError<'a>,
+    {
+        let (string_table_data, symbol_data) =
+            context("Symbol table", take(number_of_symbols * symbol_size))(at(input, offset)?)?;
+        // Some linkers omit the string table when it is empty.
+        let string_table = if string_table_data.len() >= 4 {
+            let (_, string_table) = StringTable::parse(string_table_data)?;
+            Some(string_table)
+        } else {
+            None
+        };
+
+        let mut symbols = Vec::new();
+        let mut rest = symbol_data;
+        while !rest.is_empty() {
+            let index = ((symbol_data.len() - rest.len()) / symbol_size) as u32;
+            let (r, symbol) = CoffSymbol::parse(rest, index, string_table.as_ref(), symbol_size)?;
+            symbols.push(symbol);
+            rest = r;
+        }
+
+        Ok(Self {
+            symbols,
+            string_table,
+        })
+    }
+
+    /// Symbol at `index`, which must not designate an auxiliary record.
+    pub fn get(&self, index: u32) -> Option<&CoffSymbol<'a>> {
+        self.symbols
+            .binary_search_by_key(&index, |symbol| symbol.index)
+            .ok()
+            .map(|idx| &self.symbols[idx])
+    }
+}
+
+impl<'a> fmt::Display for SymbolTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default();
+        for symbol in &self.symbols {
+            write!(f, "{:width$}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn symbol(name: &[u8; 8], section_number: i16, storage_class: u8, aux: u8) -> Vec<u8> {
+        let mut symbol = name.to_vec();
+        symbol.extend_from_slice(&0x10u32.to_le_bytes());
+        symbol.extend_from_slice(&section_number.to_le_bytes());
+        symbol.extend_from_slice(&0x20u16.to_le_bytes());
+        symbol.push(storage_class);
+        symbol.push(aux);
+        symbol
+    }
+
+    fn long_name(offset: u32) -> [u8; 8] {
+        let mut name = [0; 8];
+        name[4..].copy_from_slice(&offset.to_le_bytes());
+        name
+    }
+
+    #[test]
+    fn names() {
+        let mut data = symbol(b".file\0\0\0", -2, 103, 1);
+        data.extend_from_slice(b"main.c\0\0\0\0\0\0\0\0\0\0\0\0");
+        data.extend(symbol(b"main\0\0\0\0", 1, 2, 0));
+        data.extend(symbol(&long_name(4), 1, 2, 0));
+        data.extend_from_slice(&18u32.to_le_bytes());
+        data.extend_from_slice(b"a_long_symbol\0");
+
+        let table = SymbolTable::parse_at::<Error>(&data, 0, 4, SYMBOL_SIZE).unwrap();
+        assert_eq!(table.symbols.len(), 3);
+        assert_eq!(table.symbols[0].file_name(), Some("main.c"));
+        assert_eq!(table.get(2).map(|symbol| symbol.name), Some("main"));
+        assert!(table.get(2).unwrap().is_function());
+        assert_eq!(
+            table.get(3).map(|symbol| symbol.name),
+            Some("a_long_symbol")
+        );
+        assert!(table.get(1).is_none());
+    }
+
+    #[test]
+    fn undecodable_names_are_kept() {
+        let mut data = symbol(b"\xffbad\0\0\0\0", 1, 3, 0);
+        data.extend(symbol(&long_name(0x1000), 1, 2, 0));
+        data.extend(symbol(b".file\0\0\0", -2, 103, 1));
+        data.extend_from_slice(b"\xfe\xff\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0");
+        data.extend(symbol(b"ok\0\0\0\0\0\0", 1, 2, 0));
+        data.extend_from_slice(&4u32.to_le_bytes());
+
+        let table = SymbolTable::parse_at::<Error>(&data, 0, 5, SYMBOL_SIZE).unwrap();
+        assert_eq!(table.symbols.len(), 4);
+        assert_eq!(table.symbols[0].name, "");
+        assert_eq!(table.symbols[0].storage_class, StorageClass::Static);
+        assert_eq!(table.symbols[1].name, "");
+        assert_eq!(table.symbols[2].file_name(), Some(""));
+        assert_eq!(table.symbols[3].name, "ok");
+    }
+
+    #[test]
+    fn bigobj_symbols() {
+        let mut data = b"big\0\0\0\0\0".to_vec();
+        data.extend_from_slice(&0x10u32.to_le_bytes());
+        data.extend_from_slice(&0x12345i32.to_le_bytes());
+        data.extend_from_slice(&[0, 0, 2, 0]);
+
+        let table = SymbolTable::parse_at::<Error>(&data, 0, 1, BIGOBJ_SYMBOL_SIZE).unwrap();
+        assert_eq!(table.symbols[0].name, "big");
+        assert_eq!(table.symbols[0].section_number, 0x12345);
+        assert!(table.string_table.is_none());
+    }
+}
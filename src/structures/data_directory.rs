@@ -6,9 +6,44 @@ use crate::{NomError, Parse};
 
 use std::fmt;
 
+mod certificate;
+pub use certificate::{CertificateTable, WinCertificate};
+
+mod chpe_metadata;
+pub use chpe_metadata::{
+    Arm64ECCodeRangeEntryPoint, Arm64ECMetadata, Arm64ECRedirectionEntry, ChpeMetadata,
+    ChpeRangeEntry, ChpeRangeType,
+};
+
+mod dynamic_relocation;
+pub use dynamic_relocation::{
+    Arm64KernelImportCallTransfer, Arm64XFixup, Arm64XFixupKind, BddInfo, BddNode,
+    DynamicRelocation, DynamicRelocationFixups, DynamicRelocationTable, EpilogueHeader,
+    FunctionOverride, FunctionOverrideRelocation, FunctionOverrides, ImportControlTransfer,
+    IndirControlTransfer, SwitchableBranch, IMAGE_DYNAMIC_RELOCATION_ARM64X,
+    IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER,
+    IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE, IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE,
+    IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH,
+};
+
+mod export_directory;
+pub use export_directory::{Export, ExportDirectory, ExportTable};
+
 mod import_descriptor;
 pub use import_descriptor::{ImportByName, ImportDescriptor};
 
+mod load_config;
+pub use load_config::LoadConfigDirectory;
+
+mod runtime_function;
+pub use runtime_function::{
+    Arm64EpilogScope, Arm64Function, Arm64PackedUnwindData, Arm64RuntimeFunction, Arm64UnwindInfo,
+    Arm64XData,
+};
+
 #[derive(Debug)]
 pub struct DataDirectory {
     pub virtual_address: u32,
@@ -0,0 +1,193 @@
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::error::context;
+use nom::number::complete::le_u32;
+use nom::sequence::tuple;
+
+use crate::enums::{WinCertificateRevision, WinCertificateType};
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+#[derive(Debug)]
+pub struct WinCertificate<'a> {
+    /// Length of the entry, header included, without the padding to 8 bytes.
+    pub length: u32,
+    pub revision: WinCertificateRevision,
+    pub certificate_type: WinCertificateType,
+    pub certificate: &'a [u8],
+}
+
+impl<'a> fmt::Display for WinCertificate<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}length: 0x{:x}\n", self.length)?;
+        write!(f, "{offset}revision: {}\n", self.revision)?;
+        write!(f, "{offset}certificate_type: {}\n", self.certificate_type)
+    }
+}
+
+impl<'a> Parse<'a> for WinCertificate<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (length, revision, certificate_type)) = context(
+            "Certificate header",
+            tuple((
+                verify(le_u32, |length| *length >= 8),
+                WinCertificateRevision::parse,
+                WinCertificateType::parse,
+            )),
+        )(input)?;
+        let (rest, certificate) = context("Certificate", take(length as usize - 8))(rest)?;
+
+        // Entries are padded to 8 bytes, the last one may not be.
+        let padding = (8 - length as usize % 8) % 8;
+        let rest = &rest[padding.min(rest.len())..];
+
+        Ok((
+            rest,
+            Self {
+                length,
+                revision,
+                certificate_type,
+                certificate,
+            },
+        ))
+    }
+}
+
+/// Content of the `EntrySecurity` directory, which is located by file offset rather than RVA.
+#[derive(Debug)]
+pub struct CertificateTable<'a> {
+    pub certificates: Vec<WinCertificate<'a>>,
+
+    /// Bytes found in the file after the end of the table. The table is expected to be at the
+    /// very end of the file, so anything here was appended after signing.
+    pub trailing_data: &'a [u8],
+}
+
+impl<'a> CertificateTable<'a> {
+    /// Parses the table located at `offset` in the whole file `input`.
+    pub fn parse_at<E>(input: &'a [u8], offset: usize, size: usize) -> Result<Self, nom::Err<E>>
+    where
+        E: NomError<'a>,
+    {
+        let data = input
+            .get(offset..)
+            .and_then(|data| data.get(..size))
+            .ok_or_else(|| {
+                nom::Err::Error(E::add_context(
+                    input,
+                    "Certificate table is out of file",
+                    E::from_error_kind(input, nom::error::ErrorKind::Eof),
+                ))
+            })?;
+
+        let mut certificates = Vec::new();
+        let mut rest = data;
+        while rest.len() >= 8 {
+            let (r, certificate) = WinCertificate::parse(rest)?;
+            certificates.push(certificate);
+            rest = r;
+        }
+
+        Ok(Self {
+            certificates,
+            trailing_data: &input[offset + size..],
+        })
+    }
+
+    /// Raw PKCS#7 `SignedData` blobs of the table.
+    pub fn signatures(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.certificates
+            .iter()
+            .filter(|c| c.certificate_type == WinCertificateType::PkcsSignedData)
+            .map(|c| c.certificate)
+    }
+
+    pub fn has_trailing_data(&self) -> bool {
+        !self.trailing_data.is_empty()
+    }
+}
+
+impl<'a> fmt::Display for CertificateTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}certificates:\n")?;
+        for certificate in &self.certificates {
+            write!(f, "{:width$}\n", certificate)?;
+        }
+        write!(
+            f,
+            "{offset}trailing_data: 0x{:x} bytes\n",
+            self.trailing_data.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn entry(revision: u16, certificate_type: u16, certificate: &[u8]) -> Vec<u8> {
+        let mut entry = (8 + certificate.len() as u32).to_le_bytes().to_vec();
+        entry.extend_from_slice(&revision.to_le_bytes());
+        entry.extend_from_slice(&certificate_type.to_le_bytes());
+        entry.extend_from_slice(certificate);
+        entry
+    }
+
+    #[test]
+    fn entries_are_padded_to_8_bytes() {
+        let mut file = vec![0xaa; 4];
+        file.extend(entry(0x0200, 0x0002, b"12345"));
+        file.extend_from_slice(&[0; 3]);
+        file.extend(entry(0x0100, 0x0001, b"ab"));
+        let size = file.len() - 4;
+        file.extend_from_slice(b"tail");
+
+        let table = CertificateTable::parse_at::<Error>(&file, 4, size).unwrap();
+        assert_eq!(table.certificates.len(), 2);
+        assert_eq!(table.certificates[0].length, 13);
+        assert_eq!(
+            table.certificates[0].revision,
+            WinCertificateRevision::Revision2
+        );
+        assert_eq!(table.certificates[0].certificate, b"12345");
+        assert_eq!(
+            table.certificates[1].certificate_type,
+            WinCertificateType::X509
+        );
+        assert_eq!(table.certificates[1].certificate, b"ab");
+        assert_eq!(table.signatures().collect::<Vec<_>>(), [&b"12345"[..]]);
+        assert_eq!(table.trailing_data, b"tail");
+    }
+
+    #[test]
+    fn unknown_revision_and_type() {
+        let data = entry(0x0300, 0x0ef1, b"");
+        let (rest, certificate) = WinCertificate::parse::<Error>(&data).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            certificate.revision,
+            WinCertificateRevision::Unknown(0x0300)
+        );
+        assert_eq!(
+            certificate.certificate_type,
+            WinCertificateType::Unknown(0x0ef1)
+        );
+        assert_eq!(u16::from(certificate.certificate_type), 0x0ef1);
+    }
+
+    #[test]
+    fn truncated_table() {
+        let data = entry(0x0200, 0x0002, b"12345678");
+        assert!(CertificateTable::parse_at::<Error>(&data, 0, data.len() + 8).is_err());
+        assert!(CertificateTable::parse_at::<Error>(&data, 0, data.len() - 1).is_err());
+    }
+}
@@ -0,0 +1,418 @@
+use nom::combinator::cond;
+use nom::error::context;
+use nom::number::complete::le_u32;
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// `IMAGE_ARM64EC_METADATA`, pointed to by the load config of ARM64EC and ARM64X images.
+#[derive(Debug)]
+pub struct Arm64ECMetadata {
+    pub version: u32,
+    /// RVA of the `IMAGE_CHPE_RANGE_ENTRY` array describing the architecture of the code.
+    pub code_map: u32,
+    pub code_map_count: u32,
+    /// RVA of the `IMAGE_ARM64EC_CODE_RANGE_ENTRY_POINT` array.
+    pub code_ranges_to_entry_points: u32,
+    /// RVA of the `IMAGE_ARM64EC_REDIRECTION_ENTRY` array.
+    pub redirection_metadata: u32,
+    pub os_arm64x_dispatch_call_no_redirect: u32,
+    pub os_arm64x_dispatch_ret: u32,
+    pub os_arm64x_dispatch_call: u32,
+    pub os_arm64x_dispatch_icall: u32,
+    pub os_arm64x_dispatch_icall_cfg: u32,
+    pub alternate_entry_point: u32,
+    /// RVA of the IAT used by ARM64EC code, parallel to the one of the x64 view.
+    pub auxiliary_iat: u32,
+    pub code_ranges_to_entry_points_count: u32,
+    pub redirection_metadata_count: u32,
+    pub get_x64_information_function_pointer: u32,
+    pub set_x64_information_function_pointer: u32,
+    pub extra_rfe_table: u32,
+    pub extra_rfe_table_size: u32,
+    pub os_arm64x_dispatch_fptr: u32,
+    pub auxiliary_iat_copy: u32,
+    /// Only set from version 2.
+    pub auxiliary_delay_load_iat: u32,
+    /// Only set from version 2.
+    pub auxiliary_delay_load_iat_copy: u32,
+    /// Only set from version 2.
+    pub hybrid_image_info_bitfield: u32,
+}
+
+impl fmt::Display for Arm64ECMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}version: {}\n", self.version)?;
+        write!(f, "{offset}code_map: 0x{:x}\n", self.code_map)?;
+        write!(f, "{offset}code_map_count: {}\n", self.code_map_count)?;
+        write!(
+            f,
+            "{offset}code_ranges_to_entry_points: 0x{:x}\n",
+            self.code_ranges_to_entry_points
+        )?;
+        write!(
+            f,
+            "{offset}redirection_metadata: 0x{:x}\n",
+            self.redirection_metadata
+        )?;
+        write!(
+            f,
+            "{offset}os_arm64x_dispatch_call_no_redirect: 0x{:x}\n",
+            self.os_arm64x_dispatch_call_no_redirect
+        )?;
+        write!(
+            f,
+            "{offset}os_arm64x_dispatch_ret: 0x{:x}\n",
+            self.os_arm64x_dispatch_ret
+        )?;
+        write!(
+            f,
+            "{offset}os_arm64x_dispatch_call: 0x{:x}\n",
+            self.os_arm64x_dispatch_call
+        )?;
+        write!(
+            f,
+            "{offset}os_arm64x_dispatch_icall: 0x{:x}\n",
+            self.os_arm64x_dispatch_icall
+        )?;
+        write!(
+            f,
+            "{offset}os_arm64x_dispatch_icall_cfg: 0x{:x}\n",
+            self.os_arm64x_dispatch_icall_cfg
+        )?;
+        write!(
+            f,
+            "{offset}alternate_entry_point: 0x{:x}\n",
+            self.alternate_entry_point
+        )?;
+        write!(f, "{offset}auxiliary_iat: 0x{:x}\n", self.auxiliary_iat)?;
+        write!(
+            f,
+            "{offset}code_ranges_to_entry_points_count: {}\n",
+            self.code_ranges_to_entry_points_count
+        )?;
+        write!(
+            f,
+            "{offset}redirection_metadata_count: {}\n",
+            self.redirection_metadata_count
+        )?;
+        write!(
+            f,
+            "{offset}get_x64_information_function_pointer: 0x{:x}\n",
+            self.get_x64_information_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}set_x64_information_function_pointer: 0x{:x}\n",
+            self.set_x64_information_function_pointer
+        )?;
+        write!(f, "{offset}extra_rfe_table: 0x{:x}\n", self.extra_rfe_table)?;
+        write!(
+            f,
+            "{offset}extra_rfe_table_size: 0x{:x}\n",
+            self.extra_rfe_table_size
+        )?;
+        write!(
+            f,
+            "{offset}os_arm64x_dispatch_fptr: 0x{:x}\n",
+            self.os_arm64x_dispatch_fptr
+        )?;
+        write!(
+            f,
+            "{offset}auxiliary_iat_copy: 0x{:x}\n",
+            self.auxiliary_iat_copy
+        )?;
+        write!(
+            f,
+            "{offset}auxiliary_delay_load_iat: 0x{:x}\n",
+            self.auxiliary_delay_load_iat
+        )?;
+        write!(
+            f,
+            "{offset}auxiliary_delay_load_iat_copy: 0x{:x}\n",
+            self.auxiliary_delay_load_iat_copy
+        )?;
+        write!(
+            f,
+            "{offset}hybrid_image_info_bitfield: 0x{:x}\n",
+            self.hybrid_image_info_bitfield
+        )
+    }
+}
+
+impl<'a> Parse<'a> for Arm64ECMetadata {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            rest,
+            (
+                version,
+                code_map,
+                code_map_count,
+                code_ranges_to_entry_points,
+                redirection_metadata,
+                os_arm64x_dispatch_call_no_redirect,
+                os_arm64x_dispatch_ret,
+                os_arm64x_dispatch_call,
+                os_arm64x_dispatch_icall,
+                os_arm64x_dispatch_icall_cfg,
+                alternate_entry_point,
+                auxiliary_iat,
+                code_ranges_to_entry_points_count,
+                redirection_metadata_count,
+                get_x64_information_function_pointer,
+                set_x64_information_function_pointer,
+                extra_rfe_table,
+                extra_rfe_table_size,
+                os_arm64x_dispatch_fptr,
+                auxiliary_iat_copy,
+            ),
+        ) = context(
+            "ARM64EC metadata",
+            tuple((
+                le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
+                le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
+            )),
+        )(input)?;
+        let (rest, version_2) = context(
+            "ARM64EC metadata v2",
+            cond(version >= 2, tuple((le_u32, le_u32, le_u32))),
+        )(rest)?;
+        let (auxiliary_delay_load_iat, auxiliary_delay_load_iat_copy, hybrid_image_info_bitfield) =
+            version_2.unwrap_or_default();
+
+        Ok((
+            rest,
+            Self {
+                version,
+                code_map,
+                code_map_count,
+                code_ranges_to_entry_points,
+                redirection_metadata,
+                os_arm64x_dispatch_call_no_redirect,
+                os_arm64x_dispatch_ret,
+                os_arm64x_dispatch_call,
+                os_arm64x_dispatch_icall,
+                os_arm64x_dispatch_icall_cfg,
+                alternate_entry_point,
+                auxiliary_iat,
+                code_ranges_to_entry_points_count,
+                redirection_metadata_count,
+                get_x64_information_function_pointer,
+                set_x64_information_function_pointer,
+                extra_rfe_table,
+                extra_rfe_table_size,
+                os_arm64x_dispatch_fptr,
+                auxiliary_iat_copy,
+                auxiliary_delay_load_iat,
+                auxiliary_delay_load_iat_copy,
+                hybrid_image_info_bitfield,
+            },
+        ))
+    }
+}
+
+/// Architecture of a code range, from the low bits of `IMAGE_CHPE_RANGE_ENTRY::StartOffset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChpeRangeType {
+    Arm64,
+    Arm64EC,
+    Amd64,
+    Other(u8),
+}
+
+impl From<u8> for ChpeRangeType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Arm64,
+            1 => Self::Arm64EC,
+            2 => Self::Amd64,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for ChpeRangeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Arm64 => f.write_str("ARM64"),
+            Self::Arm64EC => f.write_str("ARM64EC"),
+            Self::Amd64 => f.write_str("x64"),
+            Self::Other(value) => write!(f, "0x{:x}", value),
+        }
+    }
+}
+
+/// `IMAGE_CHPE_RANGE_ENTRY`
+#[derive(Debug)]
+pub struct ChpeRangeEntry {
+    pub start: u32,
+    pub length: u32,
+    pub range_type: ChpeRangeType,
+}
+
+impl ChpeRangeEntry {
+    pub fn contains(&self, rva: u32) -> bool {
+        self.start <= rva && rva - self.start < self.length
+    }
+}
+
+impl fmt::Display for ChpeRangeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x}-0x{:08x}: {}\n",
+            self.start,
+            self.start.wrapping_add(self.length),
+            self.range_type
+        )
+    }
+}
+
+impl<'a> Parse<'a> for ChpeRangeEntry {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (start_offset, length)) =
+            context("CHPE range entry", tuple((le_u32, le_u32)))(input)?;
+
+        Ok((
+            rest,
+            Self {
+                start: start_offset & !0x3,
+                length,
+                range_type: ((start_offset & 0x3) as u8).into(),
+            },
+        ))
+    }
+}
+
+/// `IMAGE_ARM64EC_CODE_RANGE_ENTRY_POINT`, the entry point x64 code has to call to reach an
+/// ARM64EC range.
+#[derive(Debug)]
+pub struct Arm64ECCodeRangeEntryPoint {
+    pub start_rva: u32,
+    pub end_rva: u32,
+    pub entry_point: u32,
+}
+
+impl fmt::Display for Arm64ECCodeRangeEntryPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x}-0x{:08x}: 0x{:08x}\n",
+            self.start_rva, self.end_rva, self.entry_point
+        )
+    }
+}
+
+impl<'a> Parse<'a> for Arm64ECCodeRangeEntryPoint {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (start_rva, end_rva, entry_point)) = context(
+            "ARM64EC code range entry point",
+            tuple((le_u32, le_u32, le_u32)),
+        )(input)?;
+
+        Ok((
+            rest,
+            Self {
+                start_rva,
+                end_rva,
+                entry_point,
+            },
+        ))
+    }
+}
+
+/// `IMAGE_ARM64EC_REDIRECTION_ENTRY`, redirecting an export to its ARM64EC implementation.
+#[derive(Debug)]
+pub struct Arm64ECRedirectionEntry {
+    pub source: u32,
+    pub destination: u32,
+}
+
+impl fmt::Display for Arm64ECRedirectionEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x} -> 0x{:08x}\n",
+            self.source, self.destination
+        )
+    }
+}
+
+impl<'a> Parse<'a> for Arm64ECRedirectionEntry {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (source, destination)) =
+            context("ARM64EC redirection entry", tuple((le_u32, le_u32)))(input)?;
+
+        Ok((
+            rest,
+            Self {
+                source,
+                destination,
+            },
+        ))
+    }
+}
+
+/// Hybrid metadata of an ARM64EC or ARM64X image, with the tables it points to.
+#[derive(Debug)]
+pub struct ChpeMetadata {
+    pub metadata: Arm64ECMetadata,
+    pub code_map: Vec<ChpeRangeEntry>,
+    pub code_ranges_to_entry_points: Vec<Arm64ECCodeRangeEntryPoint>,
+    pub redirection_metadata: Vec<Arm64ECRedirectionEntry>,
+    /// Entries of the auxiliary IAT, as many as in the regular IAT.
+    pub auxiliary_iat: Vec<u64>,
+}
+
+impl ChpeMetadata {
+    /// Architecture of the code at `rva`, if it belongs to a code range.
+    pub fn range_type(&self, rva: u32) -> Option<ChpeRangeType> {
+        self.code_map
+            .iter()
+            .find(|range| range.contains(rva))
+            .map(|range| range.range_type)
+    }
+}
+
+impl fmt::Display for ChpeMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}metadata:\n{:width$}", self.metadata)?;
+        write!(f, "{offset}code_map:\n")?;
+        for range in &self.code_map {
+            write!(f, "{:width$}", range)?;
+        }
+        write!(f, "{offset}code_ranges_to_entry_points:\n")?;
+        for entry_point in &self.code_ranges_to_entry_points {
+            write!(f, "{:width$}", entry_point)?;
+        }
+        write!(f, "{offset}redirection_metadata:\n")?;
+        for redirection in &self.redirection_metadata {
+            write!(f, "{:width$}", redirection)?;
+        }
+        write!(f, "{offset}auxiliary_iat:\n")?;
+        for entry in &self.auxiliary_iat {
+            write!(f, "{offset}  0x{:x}\n", entry)?;
+        }
+        Ok(())
+    }
+}
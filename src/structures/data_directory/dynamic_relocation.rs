@@ -0,0 +1,1280 @@
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_u16, le_u32, le_u64, le_u8};
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// `IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE`
+pub const IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE: u64 = 1;
+/// `IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE`
+pub const IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE: u64 = 2;
+/// `IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER`
+pub const IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER: u64 = 3;
+/// `IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER`
+pub const IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER: u64 = 4;
+/// `IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH`
+pub const IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH: u64 = 5;
+/// `IMAGE_DYNAMIC_RELOCATION_ARM64X`
+pub const IMAGE_DYNAMIC_RELOCATION_ARM64X: u64 = 6;
+/// `IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE`
+pub const IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE: u64 = 7;
+/// `IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER`
+pub const IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER: u64 = 8;
+
+/// Parses fixups grouped by page like base relocations, `record` parsing one of them from the
+/// page RVA and the rest of its block, `None` standing for padding.
+fn parse_blocks<'a, T, E, F>(mut input: &'a [u8], mut record: F) -> Result<Vec<T>, nom::Err<E>>
+where
+    E: NomError<'a>,
+    F: FnMut(u32, &'a [u8]) -> nom::IResult<&'a [u8], Option<T>, E>,
+{
+    let mut fixups = Vec::new();
+    while !input.is_empty() {
+        let (rest, (page, size_of_block)) =
+            context("Dynamic relocation block", tuple((le_u32, le_u32)))(input)?;
+        let (rest_of_blocks, mut block) = context(
+            "Dynamic relocation block data",
+            take((size_of_block as usize).saturating_sub(8)),
+        )(rest)?;
+        input = rest_of_blocks;
+
+        while !block.is_empty() {
+            let (rest, fixup) = record(page, block)?;
+            fixups.extend(fixup);
+            block = rest;
+        }
+    }
+    Ok(fixups)
+}
+
+/// `IMAGE_IMPORT_CONTROL_TRANSFER_DYNAMIC_RELOCATION`, a call or jump through the IAT.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportControlTransfer {
+    pub rva: u32,
+    /// Whether this is a `call` rather than a `jmp`.
+    pub indirect_call: bool,
+    pub iat_index: u32,
+}
+
+impl fmt::Display for ImportControlTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x}: {} IAT entry {}\n",
+            self.rva,
+            if self.indirect_call { "call" } else { "jmp" },
+            self.iat_index
+        )
+    }
+}
+
+fn parse_import_control_transfer<'a, E>(
+    page: u32,
+    input: &'a [u8],
+) -> nom::IResult<&'a [u8], Option<ImportControlTransfer>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, record) = context("Import control transfer", le_u32)(input)?;
+    if record == 0 && rest.is_empty() {
+        return Ok((rest, None));
+    }
+
+    Ok((
+        rest,
+        Some(ImportControlTransfer {
+            rva: page.wrapping_add(record & 0xfff),
+            indirect_call: record & 0x1000 != 0,
+            iat_index: record >> 13,
+        }),
+    ))
+}
+
+/// `IMAGE_INDIR_CONTROL_TRANSFER_DYNAMIC_RELOCATION`, an indirect call or jump through a
+/// register or memory.
+#[derive(Debug, Clone, Copy)]
+pub struct IndirControlTransfer {
+    pub rva: u32,
+    /// Whether this is a `call` rather than a `jmp`.
+    pub indirect_call: bool,
+    pub rex_w_prefix: bool,
+    /// Whether the target is checked by CFG.
+    pub cfg_check: bool,
+}
+
+impl fmt::Display for IndirControlTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x}: {}",
+            self.rva,
+            if self.indirect_call { "call" } else { "jmp" }
+        )?;
+        if self.rex_w_prefix {
+            write!(f, ", rex.w")?;
+        }
+        if self.cfg_check {
+            write!(f, ", cfg check")?;
+        }
+        write!(f, "\n")
+    }
+}
+
+fn parse_indir_control_transfer<'a, E>(
+    page: u32,
+    input: &'a [u8],
+) -> nom::IResult<&'a [u8], Option<IndirControlTransfer>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, record) = context("Indirect control transfer", le_u16)(input)?;
+    if record == 0 && rest.is_empty() {
+        return Ok((rest, None));
+    }
+
+    Ok((
+        rest,
+        Some(IndirControlTransfer {
+            rva: page.wrapping_add((record & 0xfff) as u32),
+            indirect_call: record & 0x1000 != 0,
+            rex_w_prefix: record & 0x2000 != 0,
+            cfg_check: record & 0x4000 != 0,
+        }),
+    ))
+}
+
+/// `IMAGE_SWITCHTABLE_BRANCH_DYNAMIC_RELOCATION`, a jump through a switch table.
+#[derive(Debug, Clone, Copy)]
+pub struct SwitchableBranch {
+    pub rva: u32,
+    /// Register holding the jump target.
+    pub register_number: u8,
+}
+
+impl fmt::Display for SwitchableBranch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x}: register {}\n",
+            self.rva, self.register_number
+        )
+    }
+}
+
+fn parse_switchable_branch<'a, E>(
+    page: u32,
+    input: &'a [u8],
+) -> nom::IResult<&'a [u8], Option<SwitchableBranch>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, record) = context("Switchable branch", le_u16)(input)?;
+    if record == 0 && rest.is_empty() {
+        return Ok((rest, None));
+    }
+
+    Ok((
+        rest,
+        Some(SwitchableBranch {
+            rva: page.wrapping_add((record & 0xfff) as u32),
+            register_number: (record >> 12) as u8,
+        }),
+    ))
+}
+
+/// `IMAGE_IMPORT_CONTROL_TRANSFER_ARM64_RELOCATION`, a call of an import by ARM64 kernel code.
+#[derive(Debug, Clone, Copy)]
+pub struct Arm64KernelImportCallTransfer {
+    pub rva: u32,
+    /// Whether this is a `blr` rather than a `br`.
+    pub indirect_call: bool,
+    /// Register holding the import address.
+    pub register_index: u8,
+    /// 0 for a static import, 1 for a delay loaded one.
+    pub import_type: u8,
+    pub iat_index: u16,
+}
+
+impl fmt::Display for Arm64KernelImportCallTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}0x{:08x}: {} x{}, {} IAT entry {}\n",
+            self.rva,
+            if self.indirect_call { "blr" } else { "br" },
+            self.register_index,
+            if self.import_type == 0 {
+                "static"
+            } else {
+                "delay load"
+            },
+            self.iat_index
+        )
+    }
+}
+
+fn parse_arm64_kernel_import_call_transfer<'a, E>(
+    page: u32,
+    input: &'a [u8],
+) -> nom::IResult<&'a [u8], Option<Arm64KernelImportCallTransfer>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, record) = context("ARM64 kernel import call transfer", le_u32)(input)?;
+    if record == 0 && rest.is_empty() {
+        return Ok((rest, None));
+    }
+
+    Ok((
+        rest,
+        Some(Arm64KernelImportCallTransfer {
+            // Instructions are 4 bytes aligned, so is the offset
+            rva: page.wrapping_add((record & 0x3ff) * 4),
+            indirect_call: record & 0x400 != 0,
+            register_index: ((record >> 11) & 0x1f) as u8,
+            import_type: ((record >> 16) & 0x1) as u8,
+            iat_index: (record >> 17) as u16,
+        }),
+    ))
+}
+
+/// Change made to an ARM64X image to present its x64 view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arm64XFixupKind {
+    /// Zeroes `size` bytes.
+    ZeroFill { size: u8 },
+    /// Overwrites `size` bytes with the low bytes of `value`.
+    Value { size: u8, value: u64 },
+    /// Adds `delta` to the 32 bits value.
+    Delta(i64),
+}
+
+/// Fixup of an `IMAGE_DYNAMIC_RELOCATION_ARM64X` relocation.
+#[derive(Debug, Clone, Copy)]
+pub struct Arm64XFixup {
+    pub rva: u32,
+    pub kind: Arm64XFixupKind,
+}
+
+impl Arm64XFixup {
+    /// Applies the fixup to `data`, the image contents at `rva`.
+    pub fn apply(&self, data: &mut [u8]) {
+        match self.kind {
+            Arm64XFixupKind::ZeroFill { size } => {
+                if let Some(data) = data.get_mut(..size as usize) {
+                    data.fill(0);
+                }
+            }
+            Arm64XFixupKind::Value { size, value } => {
+                if let Some(data) = data.get_mut(..size as usize) {
+                    data.copy_from_slice(&value.to_le_bytes()[..size as usize]);
+                }
+            }
+            Arm64XFixupKind::Delta(delta) => {
+                if let Some(data) = data.get_mut(..4) {
+                    let value = u32::from_le_bytes(data.try_into().unwrap());
+                    data.copy_from_slice(&value.wrapping_add(delta as u32).to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Arm64XFixup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        match self.kind {
+            Arm64XFixupKind::ZeroFill { size } => {
+                write!(f, "{offset}0x{:08x}: zero {} bytes\n", self.rva, size)
+            }
+            Arm64XFixupKind::Value { size, value } => write!(
+                f,
+                "{offset}0x{:08x}: set {} bytes to 0x{:x}\n",
+                self.rva, size, value
+            ),
+            Arm64XFixupKind::Delta(delta) => {
+                write!(f, "{offset}0x{:08x}: add {}\n", self.rva, delta)
+            }
+        }
+    }
+}
+
+fn parse_arm64x_fixup<'a, E>(
+    page: u32,
+    input: &'a [u8],
+) -> nom::IResult<&'a [u8], Option<Arm64XFixup>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, record) = context("ARM64X fixup", le_u16)(input)?;
+    // Blocks are padded to 32 bits with a null record
+    if record == 0 && rest.is_empty() {
+        return Ok((rest, None));
+    }
+    let rva = page.wrapping_add((record & 0xfff) as u32);
+    let size = 1u8 << (record >> 14);
+    let (rest, kind) = match (record >> 12) & 0x3 {
+        0 => (rest, Arm64XFixupKind::ZeroFill { size }),
+        1 => {
+            // The value takes a whole number of 16 bits records
+            let (rest, bytes) = context("ARM64X fixup value", take((size as usize).max(2)))(rest)?;
+            let mut value = [0u8; 8];
+            value[..size as usize].copy_from_slice(&bytes[..size as usize]);
+            let value = u64::from_le_bytes(value);
+            (rest, Arm64XFixupKind::Value { size, value })
+        }
+        2 => {
+            let (rest, value) = context("ARM64X fixup delta", le_u16)(rest)?;
+            let scale = if record & 0x8000 != 0 { 8 } else { 4 };
+            let delta = value as i64 * scale;
+            let delta = if record & 0x4000 != 0 { -delta } else { delta };
+            (rest, Arm64XFixupKind::Delta(delta))
+        }
+        _ => {
+            return Err(nom::Err::Error(E::add_context(
+                input,
+                "Unknown ARM64X fixup type",
+                E::from_error_kind(input, nom::error::ErrorKind::Verify),
+            )))
+        }
+    };
+
+    Ok((rest, Some(Arm64XFixup { rva, kind })))
+}
+
+/// `IMAGE_BDD_DYNAMIC_RELOCATION`, node of the binary decision diagram picking a function
+/// override.
+#[derive(Debug, Clone, Copy)]
+pub struct BddNode {
+    pub left: u16,
+    pub right: u16,
+    pub value: u32,
+}
+
+impl fmt::Display for BddNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}left: {}, right: {}, value: 0x{:x}\n",
+            self.left, self.right, self.value
+        )
+    }
+}
+
+impl<'a> Parse<'a> for BddNode {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (left, right, value)) =
+            context("BDD node", tuple((le_u16, le_u16, le_u32)))(input)?;
+
+        Ok((rest, Self { left, right, value }))
+    }
+}
+
+/// `IMAGE_BDD_INFO`
+#[derive(Debug)]
+pub struct BddInfo {
+    /// Offset from the start of the BDD infos, as found in `FunctionOverride::bdd_offset`.
+    pub offset: u32,
+    pub version: u32,
+    pub nodes: Vec<BddNode>,
+}
+
+impl fmt::Display for BddInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}offset: 0x{:x}\n", self.offset)?;
+        write!(f, "{offset}version: {}\n", self.version)?;
+        write!(f, "{offset}nodes:\n")?;
+        for node in &self.nodes {
+            write!(f, "{:width$}", node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Base relocation of a function override, in the `IMAGE_BASE_RELOCATION` format.
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionOverrideRelocation {
+    pub rva: u32,
+    /// `IMAGE_REL_BASED_*` type.
+    pub relocation_type: u8,
+}
+
+fn parse_function_override_relocation<'a, E>(
+    page: u32,
+    input: &'a [u8],
+) -> nom::IResult<&'a [u8], Option<FunctionOverrideRelocation>, E>
+where
+    E: NomError<'a>,
+{
+    let (rest, record) = context("Function override relocation", le_u16)(input)?;
+    // `IMAGE_REL_BASED_ABSOLUTE` only pads blocks
+    if record >> 12 == 0 {
+        return Ok((rest, None));
+    }
+
+    Ok((
+        rest,
+        Some(FunctionOverrideRelocation {
+            rva: page.wrapping_add((record & 0xfff) as u32),
+            relocation_type: (record >> 12) as u8,
+        }),
+    ))
+}
+
+/// `IMAGE_FUNCTION_OVERRIDE_DYNAMIC_RELOCATION`
+#[derive(Debug)]
+pub struct FunctionOverride {
+    pub original_rva: u32,
+    /// Offset of the `BddInfo` selecting the override.
+    pub bdd_offset: u32,
+    /// RVAs of the overriding functions.
+    pub rvas: Vec<u32>,
+    pub relocations: Vec<FunctionOverrideRelocation>,
+}
+
+impl fmt::Display for FunctionOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}original_rva: 0x{:x}\n", self.original_rva)?;
+        write!(f, "{offset}bdd_offset: 0x{:x}\n", self.bdd_offset)?;
+        write!(f, "{offset}rvas:\n")?;
+        for rva in &self.rvas {
+            write!(f, "{offset}  0x{:08x}\n", rva)?;
+        }
+        write!(f, "{offset}relocations:\n")?;
+        for relocation in &self.relocations {
+            write!(
+                f,
+                "{offset}  0x{:08x}: type {}\n",
+                relocation.rva, relocation.relocation_type
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Fixups of an `IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE` relocation.
+#[derive(Debug)]
+pub struct FunctionOverrides {
+    pub overrides: Vec<FunctionOverride>,
+    pub bdd_infos: Vec<BddInfo>,
+}
+
+impl FunctionOverrides {
+    pub fn bdd_info(&self, function_override: &FunctionOverride) -> Option<&BddInfo> {
+        self.bdd_infos
+            .iter()
+            .find(|bdd_info| bdd_info.offset == function_override.bdd_offset)
+    }
+}
+
+impl fmt::Display for FunctionOverrides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}overrides:\n")?;
+        for function_override in &self.overrides {
+            write!(f, "{:width$}\n", function_override)?;
+        }
+        write!(f, "{offset}bdd_infos:\n")?;
+        for bdd_info in &self.bdd_infos {
+            write!(f, "{:width$}\n", bdd_info)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses an `IMAGE_FUNCTION_OVERRIDE_HEADER` and what follows it.
+fn parse_function_overrides<'a, E>(input: &'a [u8]) -> Result<FunctionOverrides, nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    let (rest, func_override_size) = context("Function override header", le_u32)(input)?;
+    let (bdd_data, mut data) =
+        context("Function overrides", take(func_override_size as usize))(rest)?;
+
+    let mut overrides = Vec::new();
+    while !data.is_empty() {
+        let (rest, (original_rva, bdd_offset, rva_size, base_reloc_size)) =
+            context("Function override", tuple((le_u32, le_u32, le_u32, le_u32)))(data)?;
+        let (rest, rvas) = context(
+            "Function override RVAs",
+            count(le_u32, rva_size as usize / 4),
+        )(rest)?;
+        let (rest, relocations) =
+            context("Function override relocations", take(base_reloc_size))(rest)?;
+        data = rest;
+
+        overrides.push(FunctionOverride {
+            original_rva,
+            bdd_offset,
+            rvas,
+            relocations: parse_blocks(relocations, parse_function_override_relocation)?,
+        });
+    }
+
+    let mut bdd_infos = Vec::new();
+    let mut data = bdd_data;
+    while !data.is_empty() {
+        let offset = (bdd_data.len() - data.len()) as u32;
+        let (rest, (version, bdd_size)) = context("BDD info", tuple((le_u32, le_u32)))(data)?;
+        let (rest, nodes) = context("BDD nodes", take(bdd_size as usize))(rest)?;
+        let (_, nodes) = count(BddNode::parse, nodes.len() / 8)(nodes)?;
+        data = rest;
+
+        bdd_infos.push(BddInfo {
+            offset,
+            version,
+            nodes,
+        });
+    }
+
+    Ok(FunctionOverrides {
+        overrides,
+        bdd_infos,
+    })
+}
+
+/// `IMAGE_EPILOGUE_DYNAMIC_RELOCATION_HEADER`
+#[derive(Debug)]
+pub struct EpilogueHeader<'a> {
+    pub epilogue_count: u32,
+    pub epilogue_byte_count: u8,
+    pub branch_descriptor_element_size: u8,
+    pub branch_descriptor_count: u16,
+    pub branch_descriptors: &'a [u8],
+    pub branch_descriptor_bit_map: &'a [u8],
+}
+
+impl<'a> fmt::Display for EpilogueHeader<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}epilogue_count: {}\n", self.epilogue_count)?;
+        write!(
+            f,
+            "{offset}epilogue_byte_count: {}\n",
+            self.epilogue_byte_count
+        )?;
+        write!(
+            f,
+            "{offset}branch_descriptor_element_size: {}\n",
+            self.branch_descriptor_element_size
+        )?;
+        write!(
+            f,
+            "{offset}branch_descriptor_count: {}\n",
+            self.branch_descriptor_count
+        )?;
+        write!(
+            f,
+            "{offset}branch_descriptor_bit_map: 0x{:x} bytes\n",
+            self.branch_descriptor_bit_map.len()
+        )
+    }
+}
+
+impl<'a> Parse<'a> for EpilogueHeader<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            rest,
+            (
+                epilogue_count,
+                epilogue_byte_count,
+                branch_descriptor_element_size,
+                branch_descriptor_count,
+            ),
+        ) = context(
+            "Epilogue dynamic relocation header",
+            tuple((le_u32, le_u8, le_u8, le_u16)),
+        )(input)?;
+        let (branch_descriptor_bit_map, branch_descriptors) = context(
+            "Epilogue branch descriptors",
+            take(branch_descriptor_element_size as usize * branch_descriptor_count as usize),
+        )(rest)?;
+
+        Ok((
+            &[],
+            Self {
+                epilogue_count,
+                epilogue_byte_count,
+                branch_descriptor_element_size,
+                branch_descriptor_count,
+                branch_descriptors,
+                branch_descriptor_bit_map,
+            },
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub enum DynamicRelocationFixups<'a> {
+    /// Prologue written over the functions of the fixups, which are left undecoded.
+    GuardRfPrologue {
+        prologue: &'a [u8],
+        fixups: &'a [u8],
+    },
+    /// Epilogues located by the branch descriptors, the fixups being left undecoded.
+    GuardRfEpilogue {
+        header: EpilogueHeader<'a>,
+        fixups: &'a [u8],
+    },
+    ImportControlTransfer(Vec<ImportControlTransfer>),
+    IndirControlTransfer(Vec<IndirControlTransfer>),
+    SwitchableBranch(Vec<SwitchableBranch>),
+    Arm64X(Vec<Arm64XFixup>),
+    FunctionOverride(FunctionOverrides),
+    Arm64KernelImportCallTransfer(Vec<Arm64KernelImportCallTransfer>),
+    /// Fixups of an unknown symbol, or RF fixups without the header of version 2.
+    Raw(&'a [u8]),
+}
+
+impl<'a> DynamicRelocationFixups<'a> {
+    /// Decodes the fixups of `symbol`, `header` being the symbol specific header of version 2.
+    fn parse<E>(symbol: u64, header: &'a [u8], fixups: &'a [u8]) -> Result<Self, nom::Err<E>>
+    where
+        E: NomError<'a>,
+    {
+        Ok(match symbol {
+            IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE if !header.is_empty() => {
+                let (_, prologue_byte_count) =
+                    context("Prologue dynamic relocation header", le_u8)(header)?;
+                let (_, prologue) =
+                    context("Prologue bytes", take(prologue_byte_count))(&header[1..])?;
+                Self::GuardRfPrologue { prologue, fixups }
+            }
+            IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE if !header.is_empty() => {
+                let (_, header) = EpilogueHeader::parse(header)?;
+                Self::GuardRfEpilogue { header, fixups }
+            }
+            IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER => {
+                Self::ImportControlTransfer(parse_blocks(fixups, parse_import_control_transfer)?)
+            }
+            IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER => {
+                Self::IndirControlTransfer(parse_blocks(fixups, parse_indir_control_transfer)?)
+            }
+            IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH => {
+                Self::SwitchableBranch(parse_blocks(fixups, parse_switchable_branch)?)
+            }
+            IMAGE_DYNAMIC_RELOCATION_ARM64X => {
+                Self::Arm64X(parse_blocks(fixups, parse_arm64x_fixup)?)
+            }
+            IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE => {
+                Self::FunctionOverride(parse_function_overrides(fixups)?)
+            }
+            IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER => {
+                Self::Arm64KernelImportCallTransfer(parse_blocks(
+                    fixups,
+                    parse_arm64_kernel_import_call_transfer,
+                )?)
+            }
+            _ => Self::Raw(fixups),
+        })
+    }
+}
+
+/// `IMAGE_DYNAMIC_RELOCATION32`, `IMAGE_DYNAMIC_RELOCATION64` and their `_V2` counterparts.
+#[derive(Debug)]
+pub struct DynamicRelocation<'a> {
+    /// `IMAGE_DYNAMIC_RELOCATION_*` kind of the fixups.
+    pub symbol: u64,
+    /// Only set from version 2.
+    pub symbol_group: u32,
+    /// Only set from version 2.
+    pub flags: u32,
+    pub fixups: DynamicRelocationFixups<'a>,
+}
+
+impl<'a> fmt::Display for DynamicRelocation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}symbol: 0x{:x}\n", self.symbol)?;
+        if self.symbol_group != 0 || self.flags != 0 {
+            write!(f, "{offset}symbol_group: 0x{:x}\n", self.symbol_group)?;
+            write!(f, "{offset}flags: 0x{:x}\n", self.flags)?;
+        }
+        match &self.fixups {
+            DynamicRelocationFixups::GuardRfPrologue { prologue, fixups } => {
+                write!(f, "{offset}rf_prologue: {:02x?}\n", prologue)?;
+                write!(f, "{offset}fixups: 0x{:x} bytes\n", fixups.len())
+            }
+            DynamicRelocationFixups::GuardRfEpilogue { header, fixups } => {
+                write!(f, "{offset}rf_epilogue:\n{:width$}", header)?;
+                write!(f, "{offset}fixups: 0x{:x} bytes\n", fixups.len())
+            }
+            DynamicRelocationFixups::ImportControlTransfer(fixups) => {
+                write!(f, "{offset}import_control_transfers:\n")?;
+                for fixup in fixups {
+                    write!(f, "{:width$}", fixup)?;
+                }
+                Ok(())
+            }
+            DynamicRelocationFixups::IndirControlTransfer(fixups) => {
+                write!(f, "{offset}indir_control_transfers:\n")?;
+                for fixup in fixups {
+                    write!(f, "{:width$}", fixup)?;
+                }
+                Ok(())
+            }
+            DynamicRelocationFixups::SwitchableBranch(fixups) => {
+                write!(f, "{offset}switchable_branches:\n")?;
+                for fixup in fixups {
+                    write!(f, "{:width$}", fixup)?;
+                }
+                Ok(())
+            }
+            DynamicRelocationFixups::Arm64X(fixups) => {
+                write!(f, "{offset}arm64x_fixups:\n")?;
+                for fixup in fixups {
+                    write!(f, "{:width$}", fixup)?;
+                }
+                Ok(())
+            }
+            DynamicRelocationFixups::FunctionOverride(overrides) => {
+                write!(f, "{offset}function_overrides:\n{:width$}", overrides)
+            }
+            DynamicRelocationFixups::Arm64KernelImportCallTransfer(fixups) => {
+                write!(f, "{offset}arm64_kernel_import_call_transfers:\n")?;
+                for fixup in fixups {
+                    write!(f, "{:width$}", fixup)?;
+                }
+                Ok(())
+            }
+            DynamicRelocationFixups::Raw(data) => {
+                write!(f, "{offset}raw: 0x{:x} bytes\n", data.len())
+            }
+        }
+    }
+}
+
+/// `IMAGE_DYNAMIC_RELOCATION_TABLE`, listing the fixups the loader applies on top of base
+/// relocations.
+#[derive(Debug)]
+pub struct DynamicRelocationTable<'a> {
+    pub version: u32,
+    pub size: u32,
+    /// Relocations of the table, only decoded for versions 1 and 2.
+    pub relocations: Vec<DynamicRelocation<'a>>,
+}
+
+impl<'a> DynamicRelocationTable<'a> {
+    /// Fixups turning an ARM64X image into its x64 view.
+    pub fn arm64x_fixups(&self) -> impl Iterator<Item = &Arm64XFixup> {
+        self.relocations
+            .iter()
+            .filter_map(|relocation| match &relocation.fixups {
+                DynamicRelocationFixups::Arm64X(fixups) => Some(fixups),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Parses the table, whose symbols are 64 bits wide if `is_64` is set.
+    pub fn parse<E>(input: &'a [u8], is_64: bool) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let symbol = |input: &'a [u8]| -> nom::IResult<&'a [u8], u64, E> {
+            if is_64 {
+                le_u64(input)
+            } else {
+                le_u32(input).map(|(rest, symbol)| (rest, symbol as u64))
+            }
+        };
+
+        let (rest, (version, size)) =
+            context("Dynamic relocation table", tuple((le_u32, le_u32)))(input)?;
+        let (rest, mut data) = context("Dynamic relocations", take(size as usize))(rest)?;
+
+        let mut relocations = Vec::new();
+        match version {
+            1 => {
+                while !data.is_empty() {
+                    let (r, (symbol, base_reloc_size)) =
+                        context("Dynamic relocation", tuple((symbol, le_u32)))(data)?;
+                    let (r, fixups) =
+                        context("Dynamic relocation fixups", take(base_reloc_size))(r)?;
+                    data = r;
+
+                    relocations.push(DynamicRelocation {
+                        symbol,
+                        symbol_group: 0,
+                        flags: 0,
+                        fixups: DynamicRelocationFixups::parse(symbol, &[], fixups)?,
+                    });
+                }
+            }
+            2 => {
+                // Size of the fields common to every header
+                let common_size = if is_64 { 24 } else { 20 };
+                while !data.is_empty() {
+                    let (r, (header_size, fixup_info_size, symbol, symbol_group, flags)) =
+                        context(
+                            "Dynamic relocation v2",
+                            tuple((
+                                verify(le_u32, |header_size| *header_size >= common_size),
+                                le_u32,
+                                symbol,
+                                le_u32,
+                                le_u32,
+                            )),
+                        )(data)?;
+                    let (r, header) = context(
+                        "Dynamic relocation v2 header",
+                        take(header_size - common_size),
+                    )(r)?;
+                    let (r, fixups) =
+                        context("Dynamic relocation v2 fixups", take(fixup_info_size))(r)?;
+                    data = r;
+
+                    relocations.push(DynamicRelocation {
+                        symbol,
+                        symbol_group,
+                        flags,
+                        fixups: DynamicRelocationFixups::parse(symbol, header, fixups)?,
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        Ok((
+            rest,
+            Self {
+                version,
+                size,
+                relocations,
+            },
+        ))
+    }
+}
+
+impl<'a> fmt::Display for DynamicRelocationTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}version: {}\n", self.version)?;
+        write!(f, "{offset}size: 0x{:x}\n", self.size)?;
+        write!(f, "{offset}relocations:\n")?;
+        for relocation in &self.relocations {
+            write!(f, "{:width$}\n", relocation)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    /// Version 1 table of 64 bits symbols, each relocation being a single block of `records`.
+    fn table(relocations: &[(u64, u32, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (symbol, page, records) in relocations {
+            data.extend_from_slice(&symbol.to_le_bytes());
+            data.extend_from_slice(&(records.len() as u32 + 8).to_le_bytes());
+            data.extend_from_slice(&page.to_le_bytes());
+            data.extend_from_slice(&(records.len() as u32 + 8).to_le_bytes());
+            data.extend_from_slice(records);
+        }
+        let mut table = Vec::new();
+        table.extend_from_slice(&1u32.to_le_bytes());
+        table.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        table.extend_from_slice(&data);
+        table
+    }
+
+    /// Single block of `records` at `page`.
+    fn block(page: u32, records: &[u8]) -> Vec<u8> {
+        let mut block = page.to_le_bytes().to_vec();
+        block.extend_from_slice(&(records.len() as u32 + 8).to_le_bytes());
+        block.extend_from_slice(records);
+        block
+    }
+
+    /// Version 2 table, each relocation being a symbol, its header and fixups.
+    fn table_v2(is_64: bool, relocations: &[(u64, &[u8], &[u8])]) -> Vec<u8> {
+        let common_size = if is_64 { 24 } else { 20 };
+        let mut data = Vec::new();
+        for (index, (symbol, header, fixups)) in relocations.iter().enumerate() {
+            data.extend_from_slice(&(common_size + header.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(fixups.len() as u32).to_le_bytes());
+            if is_64 {
+                data.extend_from_slice(&symbol.to_le_bytes());
+            } else {
+                data.extend_from_slice(&(*symbol as u32).to_le_bytes());
+            }
+            // Symbol group and flags
+            data.extend_from_slice(&(index as u32).to_le_bytes());
+            data.extend_from_slice(&(0x10 + index as u32).to_le_bytes());
+            data.extend_from_slice(header);
+            data.extend_from_slice(fixups);
+        }
+        let mut table = Vec::new();
+        table.extend_from_slice(&2u32.to_le_bytes());
+        table.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        table.extend_from_slice(&data);
+        table
+    }
+
+    #[test]
+    fn arm64x_fixups() {
+        let records = [
+            // 8 bytes value at 0x008
+            &[0x08, 0xd0][..],
+            &0x1122_3344_5566_7788u64.to_le_bytes(),
+            // -2 * 8 at 0x010
+            &[0x10, 0xe0, 0x02, 0x00],
+            // 2 bytes zero fill at 0x020, and padding
+            &[0x20, 0x40, 0x00, 0x00],
+        ]
+        .concat();
+        let data = table(&[(IMAGE_DYNAMIC_RELOCATION_ARM64X, 0x3000, &records)]);
+        let (_, table) = DynamicRelocationTable::parse::<Error>(&data, true).unwrap();
+        let fixups: Vec<_> = table
+            .arm64x_fixups()
+            .map(|fixup| (fixup.rva, fixup.kind))
+            .collect();
+        assert_eq!(
+            fixups,
+            [
+                (
+                    0x3008,
+                    Arm64XFixupKind::Value {
+                        size: 8,
+                        value: 0x1122_3344_5566_7788
+                    }
+                ),
+                (0x3010, Arm64XFixupKind::Delta(-16)),
+                (0x3020, Arm64XFixupKind::ZeroFill { size: 2 }),
+            ]
+        );
+
+        let mut image = [0xffu8; 8];
+        image[..4].copy_from_slice(&0x100u32.to_le_bytes());
+        Arm64XFixup {
+            rva: 0,
+            kind: Arm64XFixupKind::Delta(-16),
+        }
+        .apply(&mut image);
+        assert_eq!(image, [0xf0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]);
+        Arm64XFixup {
+            rva: 0,
+            kind: Arm64XFixupKind::ZeroFill { size: 2 },
+        }
+        .apply(&mut image[5..]);
+        assert_eq!(image, [0xf0, 0, 0, 0, 0xff, 0, 0, 0xff]);
+        // Too close to the end of the data, left as is
+        Arm64XFixup {
+            rva: 0,
+            kind: Arm64XFixupKind::Value { size: 4, value: 0 },
+        }
+        .apply(&mut image[6..]);
+        assert_eq!(image, [0xf0, 0, 0, 0, 0xff, 0, 0, 0xff]);
+    }
+
+    #[test]
+    fn unknown_arm64x_fixup() {
+        let data = table(&[(IMAGE_DYNAMIC_RELOCATION_ARM64X, 0x3000, &[0x00, 0x30, 0, 0])]);
+        assert!(DynamicRelocationTable::parse::<Error>(&data, true).is_err());
+    }
+
+    #[test]
+    fn page_overflow() {
+        let data = table(&[
+            (
+                IMAGE_DYNAMIC_RELOCATION_ARM64X,
+                u32::MAX,
+                &[0x10, 0x00, 0x00, 0x00],
+            ),
+            (
+                IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER,
+                u32::MAX,
+                &0x0000_3008u32.to_le_bytes(),
+            ),
+            (
+                IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER,
+                0xffff_fff0,
+                &0x0000_0004u32.to_le_bytes(),
+            ),
+        ]);
+        let (_, table) = DynamicRelocationTable::parse::<Error>(&data, true).unwrap();
+        assert_eq!(table.relocations.len(), 3);
+        assert_eq!(table.arm64x_fixups().next().unwrap().rva, 0xf);
+        match &table.relocations[1].fixups {
+            DynamicRelocationFixups::ImportControlTransfer(fixups) => {
+                assert_eq!(fixups[0].rva, 7);
+                assert!(fixups[0].indirect_call);
+                assert_eq!(fixups[0].iat_index, 1);
+            }
+            fixups => panic!("unexpected fixups {:?}", fixups),
+        }
+        match &table.relocations[2].fixups {
+            DynamicRelocationFixups::Arm64KernelImportCallTransfer(fixups) => {
+                assert_eq!(fixups[0].rva, 0);
+            }
+            fixups => panic!("unexpected fixups {:?}", fixups),
+        }
+    }
+
+    #[test]
+    fn arm64_kernel_import_call_transfer() {
+        // Offset 0x155, blr, x21, delay loaded, IAT entry 0x5a5a
+        let record: u32 = 0x155 | 1 << 10 | 21 << 11 | 1 << 16 | 0x5a5a << 17;
+        let records = [record.to_le_bytes(), 0x7ffe_f800u32.to_le_bytes()].concat();
+        let data = table(&[(
+            IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER,
+            0x2000,
+            &records,
+        )]);
+        let (_, table) = DynamicRelocationTable::parse::<Error>(&data, true).unwrap();
+        match &table.relocations[0].fixups {
+            DynamicRelocationFixups::Arm64KernelImportCallTransfer(fixups) => {
+                assert_eq!(fixups.len(), 2);
+                assert_eq!(fixups[0].rva, 0x2554);
+                assert!(fixups[0].indirect_call);
+                assert_eq!(fixups[0].register_index, 21);
+                assert_eq!(fixups[0].import_type, 1);
+                assert_eq!(fixups[0].iat_index, 0x5a5a);
+                // br x31, static import, last IAT entry
+                assert_eq!(fixups[1].rva, 0x2000);
+                assert!(!fixups[1].indirect_call);
+                assert_eq!(fixups[1].register_index, 31);
+                assert_eq!(fixups[1].import_type, 0);
+                assert_eq!(fixups[1].iat_index, 0x3fff);
+            }
+            fixups => panic!("unexpected fixups {:?}", fixups),
+        }
+    }
+
+    #[test]
+    fn control_transfers() {
+        let data = table(&[
+            (
+                IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER,
+                0x4000,
+                // jmp, call with rex.w, call with a CFG check, and padding
+                &[0x10, 0x00, 0x20, 0x30, 0x30, 0x50, 0x00, 0x00],
+            ),
+            (
+                IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH,
+                0x5000,
+                &[0x44, 0xa0, 0x00, 0x00],
+            ),
+        ]);
+        let (_, table) = DynamicRelocationTable::parse::<Error>(&data, true).unwrap();
+        match &table.relocations[0].fixups {
+            DynamicRelocationFixups::IndirControlTransfer(fixups) => {
+                let bits: Vec<_> = fixups
+                    .iter()
+                    .map(|fixup| {
+                        (
+                            fixup.rva,
+                            fixup.indirect_call,
+                            fixup.rex_w_prefix,
+                            fixup.cfg_check,
+                        )
+                    })
+                    .collect();
+                assert_eq!(
+                    bits,
+                    [
+                        (0x4010, false, false, false),
+                        (0x4020, true, true, false),
+                        (0x4030, true, false, true),
+                    ]
+                );
+            }
+            fixups => panic!("unexpected fixups {:?}", fixups),
+        }
+        match &table.relocations[1].fixups {
+            DynamicRelocationFixups::SwitchableBranch(fixups) => {
+                assert_eq!(fixups.len(), 1);
+                assert_eq!(fixups[0].rva, 0x5044);
+                assert_eq!(fixups[0].register_number, 10);
+            }
+            fixups => panic!("unexpected fixups {:?}", fixups),
+        }
+    }
+
+    #[test]
+    fn symbols_32() {
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(
+            &(IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH as u32).to_le_bytes(),
+        );
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend(block(0x1000, &[]));
+        data.extend_from_slice(b"rest");
+
+        let (rest, table) = DynamicRelocationTable::parse::<Error>(&data, false).unwrap();
+        assert_eq!(rest, b"rest");
+        assert_eq!(table.relocations.len(), 1);
+        assert_eq!(
+            table.relocations[0].symbol,
+            IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH
+        );
+        assert!(matches!(
+            &table.relocations[0].fixups,
+            DynamicRelocationFixups::SwitchableBranch(fixups) if fixups.is_empty()
+        ));
+        // The same table read with 64 bits symbols runs past its size
+        assert!(DynamicRelocationTable::parse::<Error>(&data, true).is_err());
+    }
+
+    #[test]
+    fn version_2() {
+        let prologue = [4, 0x48, 0x8b, 0xc4, 0x90];
+        let epilogue = [
+            &3u32.to_le_bytes()[..],
+            &[5, 2],
+            &2u16.to_le_bytes(),
+            &[0xaa, 0xbb, 0xcc, 0xdd],
+            &[0x0f],
+        ]
+        .concat();
+        let fixups = block(0x1000, &[0x10, 0x00, 0x00, 0x00]);
+        for is_64 in [false, true] {
+            let data = table_v2(
+                is_64,
+                &[
+                    (
+                        IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE,
+                        &prologue,
+                        &fixups,
+                    ),
+                    (
+                        IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE,
+                        &epilogue,
+                        &fixups,
+                    ),
+                    (IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE, &[], &fixups),
+                    (0x1234, b"header", b"fixups"),
+                ],
+            );
+            let (_, table) = DynamicRelocationTable::parse::<Error>(&data, is_64).unwrap();
+            assert_eq!(table.version, 2);
+            assert_eq!(table.relocations.len(), 4);
+            for (index, relocation) in table.relocations.iter().enumerate() {
+                assert_eq!(relocation.symbol_group, index as u32);
+                assert_eq!(relocation.flags, 0x10 + index as u32);
+            }
+            match &table.relocations[0].fixups {
+                DynamicRelocationFixups::GuardRfPrologue { prologue, fixups } => {
+                    assert_eq!(*prologue, [0x48, 0x8b, 0xc4, 0x90]);
+                    assert_eq!(fixups.len(), 12);
+                }
+                fixups => panic!("unexpected fixups {:?}", fixups),
+            }
+            match &table.relocations[1].fixups {
+                DynamicRelocationFixups::GuardRfEpilogue { header, fixups } => {
+                    assert_eq!(header.epilogue_count, 3);
+                    assert_eq!(header.epilogue_byte_count, 5);
+                    assert_eq!(header.branch_descriptor_element_size, 2);
+                    assert_eq!(header.branch_descriptor_count, 2);
+                    assert_eq!(header.branch_descriptors, [0xaa, 0xbb, 0xcc, 0xdd]);
+                    assert_eq!(header.branch_descriptor_bit_map, [0x0f]);
+                    assert_eq!(fixups.len(), 12);
+                }
+                fixups => panic!("unexpected fixups {:?}", fixups),
+            }
+            // No header to decode the fixups with
+            assert!(matches!(
+                table.relocations[2].fixups,
+                DynamicRelocationFixups::Raw(fixups) if fixups.len() == 12
+            ));
+            assert_eq!(table.relocations[3].symbol, 0x1234);
+            assert!(matches!(
+                table.relocations[3].fixups,
+                DynamicRelocationFixups::Raw(b"fixups")
+            ));
+        }
+    }
+
+    #[test]
+    fn version_2_header_size() {
+        // Smaller than the fields common to every header
+        for (is_64, common_size) in [(false, 20u32), (true, 24)] {
+            let mut data = table_v2(is_64, &[(0x1234, &[], &[])]);
+            data[8..12].copy_from_slice(&(common_size - 1).to_le_bytes());
+            assert!(DynamicRelocationTable::parse::<Error>(&data, is_64).is_err());
+        }
+    }
+
+    #[test]
+    fn function_overrides() {
+        let relocations = block(0x6000, &[0x08, 0xa0, 0x00, 0x00]);
+        let mut overrides = Vec::new();
+        for (original_rva, bdd_offset, rvas) in [
+            (0x6000u32, 0u32, &[0x7000u32, 0x7100][..]),
+            (0x6100, 0x10, &[]),
+        ] {
+            overrides.extend_from_slice(&original_rva.to_le_bytes());
+            overrides.extend_from_slice(&bdd_offset.to_le_bytes());
+            overrides.extend_from_slice(&(rvas.len() as u32 * 4).to_le_bytes());
+            overrides.extend_from_slice(&(relocations.len() as u32).to_le_bytes());
+            for rva in rvas {
+                overrides.extend_from_slice(&rva.to_le_bytes());
+            }
+            overrides.extend_from_slice(&relocations);
+        }
+        let mut fixups = (overrides.len() as u32).to_le_bytes().to_vec();
+        fixups.extend(overrides);
+        // Two BDD infos, of one and no node
+        fixups.extend_from_slice(&1u32.to_le_bytes());
+        fixups.extend_from_slice(&8u32.to_le_bytes());
+        fixups.extend_from_slice(&[1, 0, 2, 0, 0x78, 0x56, 0x34, 0x12]);
+        fixups.extend_from_slice(&1u32.to_le_bytes());
+        fixups.extend_from_slice(&0u32.to_le_bytes());
+
+        let data = table_v2(
+            true,
+            &[(IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE, &[], &fixups)],
+        );
+        let (_, table) = DynamicRelocationTable::parse::<Error>(&data, true).unwrap();
+        let overrides = match &table.relocations[0].fixups {
+            DynamicRelocationFixups::FunctionOverride(overrides) => overrides,
+            fixups => panic!("unexpected fixups {:?}", fixups),
+        };
+        assert_eq!(overrides.overrides.len(), 2);
+        assert_eq!(overrides.overrides[0].original_rva, 0x6000);
+        assert_eq!(overrides.overrides[0].rvas, [0x7000, 0x7100]);
+        assert_eq!(overrides.overrides[0].relocations.len(), 1);
+        assert_eq!(overrides.overrides[0].relocations[0].rva, 0x6008);
+        assert_eq!(overrides.overrides[0].relocations[0].relocation_type, 10);
+        assert!(overrides.overrides[1].rvas.is_empty());
+
+        assert_eq!(overrides.bdd_infos.len(), 2);
+        let bdd_info = overrides.bdd_info(&overrides.overrides[0]).unwrap();
+        assert_eq!(bdd_info.offset, 0);
+        assert_eq!(bdd_info.version, 1);
+        assert_eq!(bdd_info.nodes.len(), 1);
+        assert_eq!(bdd_info.nodes[0].left, 1);
+        assert_eq!(bdd_info.nodes[0].right, 2);
+        assert_eq!(bdd_info.nodes[0].value, 0x1234_5678);
+        let bdd_info = overrides.bdd_info(&overrides.overrides[1]).unwrap();
+        assert_eq!(bdd_info.offset, 0x10);
+        assert!(bdd_info.nodes.is_empty());
+    }
+}
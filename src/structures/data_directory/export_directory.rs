@@ -0,0 +1,166 @@
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32};
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// `IMAGE_EXPORT_DIRECTORY`
+#[derive(Debug)]
+pub struct ExportDirectory {
+    pub characteristics: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub name: u32,
+    /// Ordinal of the first entry of the export address table.
+    pub base: u32,
+    pub number_of_functions: u32,
+    pub number_of_names: u32,
+    pub address_of_functions: u32,
+    pub address_of_names: u32,
+    pub address_of_name_ordinals: u32,
+}
+
+impl fmt::Display for ExportDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}characteristics: 0x{:x}\n", self.characteristics)?;
+        write!(f, "{offset}time_date_stamp: 0x{:x}\n", self.time_date_stamp)?;
+        write!(
+            f,
+            "{offset}version: {}.{}\n",
+            self.major_version, self.minor_version
+        )?;
+        write!(f, "{offset}name: 0x{:x}\n", self.name)?;
+        write!(f, "{offset}base: {}\n", self.base)?;
+        write!(
+            f,
+            "{offset}number_of_functions: 0x{:x}\n",
+            self.number_of_functions
+        )?;
+        write!(f, "{offset}number_of_names: 0x{:x}\n", self.number_of_names)?;
+        write!(
+            f,
+            "{offset}address_of_functions: 0x{:x}\n",
+            self.address_of_functions
+        )?;
+        write!(
+            f,
+            "{offset}address_of_names: 0x{:x}\n",
+            self.address_of_names
+        )?;
+        write!(
+            f,
+            "{offset}address_of_name_ordinals: 0x{:x}\n",
+            self.address_of_name_ordinals
+        )
+    }
+}
+
+impl<'a> Parse<'a> for ExportDirectory {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            rest,
+            (
+                characteristics,
+                time_date_stamp,
+                major_version,
+                minor_version,
+                name,
+                base,
+                number_of_functions,
+                number_of_names,
+                address_of_functions,
+                address_of_names,
+                address_of_name_ordinals,
+            ),
+        ) = context(
+            "Export directory",
+            tuple((
+                le_u32, le_u32, le_u16, le_u16, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
+                le_u32,
+            )),
+        )(input)?;
+
+        Ok((
+            rest,
+            Self {
+                characteristics,
+                time_date_stamp,
+                major_version,
+                minor_version,
+                name,
+                base,
+                number_of_functions,
+                number_of_names,
+                address_of_functions,
+                address_of_names,
+                address_of_name_ordinals,
+            },
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct Export<'a> {
+    pub ordinal: u32,
+    pub name: Option<&'a str>,
+    pub address: u32,
+    /// `dll.symbol` or `dll.#ordinal` the export is forwarded to, in which case `address` is
+    /// the RVA of this string.
+    pub forwarder: Option<&'a str>,
+}
+
+impl<'a> fmt::Display for Export<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}{} {}",
+            self.ordinal,
+            self.name.unwrap_or("[NONAME]")
+        )?;
+        match self.forwarder {
+            Some(forwarder) => write!(f, " -> {}\n", forwarder),
+            None => write!(f, " 0x{:x}\n", self.address),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExportTable<'a> {
+    pub directory: ExportDirectory,
+    pub name: &'a str,
+    /// Exports in address table order, unused entries skipped. Ordinals wrap past `u32::MAX`,
+    /// so they are not necessarily sorted.
+    pub exports: Vec<Export<'a>>,
+}
+
+impl<'a> ExportTable<'a> {
+    pub fn by_name(&self, name: &str) -> Option<&Export<'a>> {
+        self.exports.iter().find(|export| export.name == Some(name))
+    }
+
+    pub fn by_ordinal(&self, ordinal: u32) -> Option<&Export<'a>> {
+        self.exports.iter().find(|export| export.ordinal == ordinal)
+    }
+}
+
+impl<'a> fmt::Display for ExportTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}name: {}\n", self.name)?;
+        write!(f, "{offset}directory:\n{:width$}", self.directory)?;
+        write!(f, "{offset}exports:\n")?;
+        for export in &self.exports {
+            write!(f, "{:width$}", export)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,526 @@
+use nom::combinator::{map, opt};
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32, le_u64};
+use nom::sequence::tuple;
+
+use crate::NomError;
+
+use std::fmt;
+
+/// `IMAGE_LOAD_CONFIG_DIRECTORY32` and `IMAGE_LOAD_CONFIG_DIRECTORY64`, pointer sized fields
+/// being widened to `u64`.
+///
+/// Fields past `size` were not known to the linker and are left to 0.
+#[derive(Debug)]
+pub struct LoadConfigDirectory {
+    /// Size of the structure, which grows with each version of the linker.
+    pub size: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub global_flags_clear: u32,
+    pub global_flags_set: u32,
+    pub critical_section_default_timeout: u32,
+    pub de_commit_free_block_threshold: u64,
+    pub de_commit_total_free_threshold: u64,
+    pub lock_prefix_table: u64,
+    pub maximum_allocation_size: u64,
+    pub virtual_memory_threshold: u64,
+    pub process_affinity_mask: u64,
+    pub process_heap_flags: u32,
+    pub csd_version: u16,
+    pub dependent_load_flags: u16,
+    pub edit_list: u64,
+    pub security_cookie: u64,
+    pub se_handler_table: u64,
+    pub se_handler_count: u64,
+    pub guard_cf_check_function_pointer: u64,
+    pub guard_cf_dispatch_function_pointer: u64,
+    pub guard_cf_function_table: u64,
+    pub guard_cf_function_count: u64,
+    pub guard_flags: u32,
+    pub code_integrity_flags: u16,
+    pub code_integrity_catalog: u16,
+    pub code_integrity_catalog_offset: u32,
+    pub guard_address_taken_iat_entry_table: u64,
+    pub guard_address_taken_iat_entry_count: u64,
+    pub guard_long_jump_target_table: u64,
+    pub guard_long_jump_target_count: u64,
+    /// VA of the dynamic value relocation table, superseded by `dynamic_value_reloc_table_offset`.
+    pub dynamic_value_reloc_table: u64,
+    /// VA of the hybrid (CHPE) metadata of ARM64EC, ARM64X and x86 CHPE images.
+    pub chpe_metadata_pointer: u64,
+    pub guard_rf_failure_routine: u64,
+    pub guard_rf_failure_routine_function_pointer: u64,
+    /// Offset of the dynamic value relocation table in section `dynamic_value_reloc_table_section`.
+    pub dynamic_value_reloc_table_offset: u32,
+    /// 1-based index of the section holding the dynamic value relocation table.
+    pub dynamic_value_reloc_table_section: u16,
+    pub guard_rf_verify_stack_pointer_function_pointer: u64,
+    pub hot_patch_table_offset: u32,
+    pub enclave_configuration_pointer: u64,
+    pub volatile_metadata_pointer: u64,
+    pub guard_eh_continuation_table: u64,
+    pub guard_eh_continuation_count: u64,
+    pub guard_xfg_check_function_pointer: u64,
+    pub guard_xfg_dispatch_function_pointer: u64,
+    pub guard_xfg_table_dispatch_function_pointer: u64,
+    pub cast_guard_os_determined_failure_mode: u64,
+    pub guard_memcpy_function_pointer: u64,
+}
+
+impl fmt::Display for LoadConfigDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}size: 0x{:x}\n", self.size)?;
+        write!(f, "{offset}time_date_stamp: 0x{:x}\n", self.time_date_stamp)?;
+        write!(
+            f,
+            "{offset}version: {}.{}\n",
+            self.major_version, self.minor_version
+        )?;
+        write!(
+            f,
+            "{offset}global_flags_clear: 0x{:x}\n",
+            self.global_flags_clear
+        )?;
+        write!(
+            f,
+            "{offset}global_flags_set: 0x{:x}\n",
+            self.global_flags_set
+        )?;
+        write!(
+            f,
+            "{offset}critical_section_default_timeout: 0x{:x}\n",
+            self.critical_section_default_timeout
+        )?;
+        write!(
+            f,
+            "{offset}de_commit_free_block_threshold: 0x{:x}\n",
+            self.de_commit_free_block_threshold
+        )?;
+        write!(
+            f,
+            "{offset}de_commit_total_free_threshold: 0x{:x}\n",
+            self.de_commit_total_free_threshold
+        )?;
+        write!(
+            f,
+            "{offset}lock_prefix_table: 0x{:x}\n",
+            self.lock_prefix_table
+        )?;
+        write!(
+            f,
+            "{offset}maximum_allocation_size: 0x{:x}\n",
+            self.maximum_allocation_size
+        )?;
+        write!(
+            f,
+            "{offset}virtual_memory_threshold: 0x{:x}\n",
+            self.virtual_memory_threshold
+        )?;
+        write!(
+            f,
+            "{offset}process_affinity_mask: 0x{:x}\n",
+            self.process_affinity_mask
+        )?;
+        write!(
+            f,
+            "{offset}process_heap_flags: 0x{:x}\n",
+            self.process_heap_flags
+        )?;
+        write!(f, "{offset}csd_version: 0x{:x}\n", self.csd_version)?;
+        write!(
+            f,
+            "{offset}dependent_load_flags: 0x{:x}\n",
+            self.dependent_load_flags
+        )?;
+        write!(f, "{offset}edit_list: 0x{:x}\n", self.edit_list)?;
+        write!(f, "{offset}security_cookie: 0x{:x}\n", self.security_cookie)?;
+        write!(
+            f,
+            "{offset}se_handler_table: 0x{:x}\n",
+            self.se_handler_table
+        )?;
+        write!(
+            f,
+            "{offset}se_handler_count: 0x{:x}\n",
+            self.se_handler_count
+        )?;
+        write!(
+            f,
+            "{offset}guard_cf_check_function_pointer: 0x{:x}\n",
+            self.guard_cf_check_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}guard_cf_dispatch_function_pointer: 0x{:x}\n",
+            self.guard_cf_dispatch_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}guard_cf_function_table: 0x{:x}\n",
+            self.guard_cf_function_table
+        )?;
+        write!(
+            f,
+            "{offset}guard_cf_function_count: 0x{:x}\n",
+            self.guard_cf_function_count
+        )?;
+        write!(f, "{offset}guard_flags: 0x{:x}\n", self.guard_flags)?;
+        write!(
+            f,
+            "{offset}code_integrity_flags: 0x{:x}\n",
+            self.code_integrity_flags
+        )?;
+        write!(
+            f,
+            "{offset}code_integrity_catalog: 0x{:x}\n",
+            self.code_integrity_catalog
+        )?;
+        write!(
+            f,
+            "{offset}code_integrity_catalog_offset: 0x{:x}\n",
+            self.code_integrity_catalog_offset
+        )?;
+        write!(
+            f,
+            "{offset}guard_address_taken_iat_entry_table: 0x{:x}\n",
+            self.guard_address_taken_iat_entry_table
+        )?;
+        write!(
+            f,
+            "{offset}guard_address_taken_iat_entry_count: 0x{:x}\n",
+            self.guard_address_taken_iat_entry_count
+        )?;
+        write!(
+            f,
+            "{offset}guard_long_jump_target_table: 0x{:x}\n",
+            self.guard_long_jump_target_table
+        )?;
+        write!(
+            f,
+            "{offset}guard_long_jump_target_count: 0x{:x}\n",
+            self.guard_long_jump_target_count
+        )?;
+        write!(
+            f,
+            "{offset}dynamic_value_reloc_table: 0x{:x}\n",
+            self.dynamic_value_reloc_table
+        )?;
+        write!(
+            f,
+            "{offset}chpe_metadata_pointer: 0x{:x}\n",
+            self.chpe_metadata_pointer
+        )?;
+        write!(
+            f,
+            "{offset}guard_rf_failure_routine: 0x{:x}\n",
+            self.guard_rf_failure_routine
+        )?;
+        write!(
+            f,
+            "{offset}guard_rf_failure_routine_function_pointer: 0x{:x}\n",
+            self.guard_rf_failure_routine_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}dynamic_value_reloc_table_offset: 0x{:x}\n",
+            self.dynamic_value_reloc_table_offset
+        )?;
+        write!(
+            f,
+            "{offset}dynamic_value_reloc_table_section: 0x{:x}\n",
+            self.dynamic_value_reloc_table_section
+        )?;
+        write!(
+            f,
+            "{offset}guard_rf_verify_stack_pointer_function_pointer: 0x{:x}\n",
+            self.guard_rf_verify_stack_pointer_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}hot_patch_table_offset: 0x{:x}\n",
+            self.hot_patch_table_offset
+        )?;
+        write!(
+            f,
+            "{offset}enclave_configuration_pointer: 0x{:x}\n",
+            self.enclave_configuration_pointer
+        )?;
+        write!(
+            f,
+            "{offset}volatile_metadata_pointer: 0x{:x}\n",
+            self.volatile_metadata_pointer
+        )?;
+        write!(
+            f,
+            "{offset}guard_eh_continuation_table: 0x{:x}\n",
+            self.guard_eh_continuation_table
+        )?;
+        write!(
+            f,
+            "{offset}guard_eh_continuation_count: 0x{:x}\n",
+            self.guard_eh_continuation_count
+        )?;
+        write!(
+            f,
+            "{offset}guard_xfg_check_function_pointer: 0x{:x}\n",
+            self.guard_xfg_check_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}guard_xfg_dispatch_function_pointer: 0x{:x}\n",
+            self.guard_xfg_dispatch_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}guard_xfg_table_dispatch_function_pointer: 0x{:x}\n",
+            self.guard_xfg_table_dispatch_function_pointer
+        )?;
+        write!(
+            f,
+            "{offset}cast_guard_os_determined_failure_mode: 0x{:x}\n",
+            self.cast_guard_os_determined_failure_mode
+        )?;
+        write!(
+            f,
+            "{offset}guard_memcpy_function_pointer: 0x{:x}\n",
+            self.guard_memcpy_function_pointer
+        )
+    }
+}
+
+/// Parses `parser`, or defaults to 0 once the structure is exhausted.
+fn field<'a, O, E, F>(parser: F) -> impl FnMut(&'a [u8]) -> nom::IResult<&'a [u8], O, E>
+where
+    O: Default,
+    E: NomError<'a>,
+    F: FnMut(&'a [u8]) -> nom::IResult<&'a [u8], O, E>,
+{
+    map(opt(parser), Option::unwrap_or_default)
+}
+
+impl LoadConfigDirectory {
+    /// Parses the directory, whose pointers are 64 bits wide if `is_64` is set.
+    pub fn parse<'a, E>(input: &'a [u8], is_64: bool) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let pointer = |input: &'a [u8]| -> nom::IResult<&'a [u8], u64, E> {
+            if is_64 {
+                le_u64(input)
+            } else {
+                map(le_u32, u64::from)(input)
+            }
+        };
+
+        let (rest, size) = context("Load config directory", le_u32)(input)?;
+        // `size` includes itself
+        let len = (size as usize).saturating_sub(4).min(rest.len());
+        let (data, rest) = (&rest[..len], &rest[len..]);
+
+        let (data, time_date_stamp) = field(le_u32)(data)?;
+        let (data, major_version) = field(le_u16)(data)?;
+        let (data, minor_version) = field(le_u16)(data)?;
+        let (data, global_flags_clear) = field(le_u32)(data)?;
+        let (data, global_flags_set) = field(le_u32)(data)?;
+        let (data, critical_section_default_timeout) = field(le_u32)(data)?;
+        let (data, de_commit_free_block_threshold) = field(pointer)(data)?;
+        let (data, de_commit_total_free_threshold) = field(pointer)(data)?;
+        let (data, lock_prefix_table) = field(pointer)(data)?;
+        let (data, maximum_allocation_size) = field(pointer)(data)?;
+        let (data, virtual_memory_threshold) = field(pointer)(data)?;
+        // The 32 bits structure swaps these two fields
+        let (data, (process_affinity_mask, process_heap_flags)) = if is_64 {
+            field(tuple((pointer, le_u32)))(data)?
+        } else {
+            let (data, (process_heap_flags, process_affinity_mask)) =
+                field(tuple((le_u32, pointer)))(data)?;
+            (data, (process_affinity_mask, process_heap_flags))
+        };
+        let (data, csd_version) = field(le_u16)(data)?;
+        let (data, dependent_load_flags) = field(le_u16)(data)?;
+        let (data, edit_list) = field(pointer)(data)?;
+        let (data, security_cookie) = field(pointer)(data)?;
+        let (data, se_handler_table) = field(pointer)(data)?;
+        let (data, se_handler_count) = field(pointer)(data)?;
+        let (data, guard_cf_check_function_pointer) = field(pointer)(data)?;
+        let (data, guard_cf_dispatch_function_pointer) = field(pointer)(data)?;
+        let (data, guard_cf_function_table) = field(pointer)(data)?;
+        let (data, guard_cf_function_count) = field(pointer)(data)?;
+        let (data, guard_flags) = field(le_u32)(data)?;
+        let (data, code_integrity_flags) = field(le_u16)(data)?;
+        let (data, code_integrity_catalog) = field(le_u16)(data)?;
+        let (data, code_integrity_catalog_offset) = field(le_u32)(data)?;
+        let (data, _) = field(le_u32)(data)?;
+        let (data, guard_address_taken_iat_entry_table) = field(pointer)(data)?;
+        let (data, guard_address_taken_iat_entry_count) = field(pointer)(data)?;
+        let (data, guard_long_jump_target_table) = field(pointer)(data)?;
+        let (data, guard_long_jump_target_count) = field(pointer)(data)?;
+        let (data, dynamic_value_reloc_table) = field(pointer)(data)?;
+        let (data, chpe_metadata_pointer) = field(pointer)(data)?;
+        let (data, guard_rf_failure_routine) = field(pointer)(data)?;
+        let (data, guard_rf_failure_routine_function_pointer) = field(pointer)(data)?;
+        let (data, dynamic_value_reloc_table_offset) = field(le_u32)(data)?;
+        let (data, dynamic_value_reloc_table_section) = field(le_u16)(data)?;
+        let (data, _) = field(le_u16)(data)?;
+        let (data, guard_rf_verify_stack_pointer_function_pointer) = field(pointer)(data)?;
+        let (data, hot_patch_table_offset) = field(le_u32)(data)?;
+        let (data, _) = field(le_u32)(data)?;
+        let (data, enclave_configuration_pointer) = field(pointer)(data)?;
+        let (data, volatile_metadata_pointer) = field(pointer)(data)?;
+        let (data, guard_eh_continuation_table) = field(pointer)(data)?;
+        let (data, guard_eh_continuation_count) = field(pointer)(data)?;
+        let (data, guard_xfg_check_function_pointer) = field(pointer)(data)?;
+        let (data, guard_xfg_dispatch_function_pointer) = field(pointer)(data)?;
+        let (data, guard_xfg_table_dispatch_function_pointer) = field(pointer)(data)?;
+        let (data, cast_guard_os_determined_failure_mode) = field(pointer)(data)?;
+        let (_, guard_memcpy_function_pointer) = field(pointer)(data)?;
+
+        Ok((
+            rest,
+            Self {
+                size,
+                time_date_stamp,
+                major_version,
+                minor_version,
+                global_flags_clear,
+                global_flags_set,
+                critical_section_default_timeout,
+                de_commit_free_block_threshold,
+                de_commit_total_free_threshold,
+                lock_prefix_table,
+                maximum_allocation_size,
+                virtual_memory_threshold,
+                process_affinity_mask,
+                process_heap_flags,
+                csd_version,
+                dependent_load_flags,
+                edit_list,
+                security_cookie,
+                se_handler_table,
+                se_handler_count,
+                guard_cf_check_function_pointer,
+                guard_cf_dispatch_function_pointer,
+                guard_cf_function_table,
+                guard_cf_function_count,
+                guard_flags,
+                code_integrity_flags,
+                code_integrity_catalog,
+                code_integrity_catalog_offset,
+                guard_address_taken_iat_entry_table,
+                guard_address_taken_iat_entry_count,
+                guard_long_jump_target_table,
+                guard_long_jump_target_count,
+                dynamic_value_reloc_table,
+                chpe_metadata_pointer,
+                guard_rf_failure_routine,
+                guard_rf_failure_routine_function_pointer,
+                dynamic_value_reloc_table_offset,
+                dynamic_value_reloc_table_section,
+                guard_rf_verify_stack_pointer_function_pointer,
+                hot_patch_table_offset,
+                enclave_configuration_pointer,
+                volatile_metadata_pointer,
+                guard_eh_continuation_table,
+                guard_eh_continuation_count,
+                guard_xfg_check_function_pointer,
+                guard_xfg_dispatch_function_pointer,
+                guard_xfg_table_dispatch_function_pointer,
+                cast_guard_os_determined_failure_mode,
+                guard_memcpy_function_pointer,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    #[test]
+    fn layout_32() {
+        // Every 32 bits word holds its own offset
+        let mut data: Vec<u8> = (0..192u32)
+            .step_by(4)
+            .flat_map(|offset| (0x1000 + offset).to_le_bytes())
+            .collect();
+        data[..4].copy_from_slice(&192u32.to_le_bytes());
+        data.extend_from_slice(b"rest");
+
+        let (rest, load_config) = LoadConfigDirectory::parse::<Error>(&data, false).unwrap();
+        assert_eq!(rest, b"rest");
+        assert_eq!(load_config.size, 192);
+        assert_eq!(load_config.time_date_stamp, 0x1004);
+        assert_eq!(load_config.major_version, 0x1008);
+        assert_eq!(load_config.minor_version, 0);
+        assert_eq!(load_config.critical_section_default_timeout, 0x1014);
+        assert_eq!(load_config.virtual_memory_threshold, 0x1028);
+        assert_eq!(load_config.process_heap_flags, 0x102c);
+        assert_eq!(load_config.process_affinity_mask, 0x1030);
+        assert_eq!(load_config.csd_version, 0x1034);
+        assert_eq!(load_config.edit_list, 0x1038);
+        assert_eq!(load_config.security_cookie, 0x103c);
+        assert_eq!(load_config.se_handler_table, 0x1040);
+        assert_eq!(load_config.se_handler_count, 0x1044);
+        assert_eq!(load_config.guard_cf_function_count, 0x1054);
+        assert_eq!(load_config.guard_flags, 0x1058);
+        assert_eq!(load_config.code_integrity_flags, 0x105c);
+        assert_eq!(load_config.code_integrity_catalog_offset, 0x1060);
+        assert_eq!(load_config.guard_address_taken_iat_entry_table, 0x1068);
+        assert_eq!(load_config.guard_long_jump_target_count, 0x1074);
+        assert_eq!(load_config.dynamic_value_reloc_table, 0x1078);
+        assert_eq!(load_config.chpe_metadata_pointer, 0x107c);
+        assert_eq!(
+            load_config.guard_rf_failure_routine_function_pointer,
+            0x1084
+        );
+        assert_eq!(load_config.dynamic_value_reloc_table_offset, 0x1088);
+        assert_eq!(load_config.dynamic_value_reloc_table_section, 0x108c);
+        assert_eq!(
+            load_config.guard_rf_verify_stack_pointer_function_pointer,
+            0x1090
+        );
+        assert_eq!(load_config.hot_patch_table_offset, 0x1094);
+        assert_eq!(load_config.enclave_configuration_pointer, 0x109c);
+        assert_eq!(load_config.volatile_metadata_pointer, 0x10a0);
+        assert_eq!(load_config.guard_eh_continuation_count, 0x10a8);
+        assert_eq!(
+            load_config.guard_xfg_table_dispatch_function_pointer,
+            0x10b4
+        );
+        assert_eq!(load_config.cast_guard_os_determined_failure_mode, 0x10b8);
+        assert_eq!(load_config.guard_memcpy_function_pointer, 0x10bc);
+    }
+
+    #[test]
+    fn layout_64() {
+        let mut data: Vec<u8> = (0..320u32)
+            .step_by(8)
+            .flat_map(|offset| (0x1000 + offset as u64).to_le_bytes())
+            .collect();
+        data[..4].copy_from_slice(&320u32.to_le_bytes());
+
+        let (_, load_config) = LoadConfigDirectory::parse::<Error>(&data, true).unwrap();
+        assert_eq!(load_config.process_affinity_mask, 0x1040);
+        assert_eq!(load_config.process_heap_flags, 0x1048);
+        assert_eq!(load_config.chpe_metadata_pointer, 0x10c8);
+        assert_eq!(load_config.guard_memcpy_function_pointer, 0x1138);
+    }
+
+    #[test]
+    fn truncated() {
+        // Fields past the size are left to 0, and the data following it is not consumed
+        let mut data = vec![0xff; 0x60];
+        data[..4].copy_from_slice(&0x44u32.to_le_bytes());
+
+        let (rest, load_config) = LoadConfigDirectory::parse::<Error>(&data, false).unwrap();
+        assert_eq!(rest.len(), 0x1c);
+        assert_eq!(load_config.se_handler_table, 0xffff_ffff);
+        assert_eq!(load_config.se_handler_count, 0);
+        assert_eq!(load_config.guard_flags, 0);
+    }
+}
@@ -0,0 +1,366 @@
+use nom::bytes::complete::take;
+use nom::combinator::cond;
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::le_u32;
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// Raw ARM64 `.pdata` entry (`IMAGE_ARM64_RUNTIME_FUNCTION_ENTRY`).
+#[derive(Debug)]
+pub struct Arm64RuntimeFunction {
+    pub begin_address: u32,
+    pub unwind_data: u32,
+}
+
+impl Arm64RuntimeFunction {
+    /// Low two bits of `unwind_data`. 0 means `unwind_data` is the RVA of an `.xdata` record.
+    pub fn flag(&self) -> u8 {
+        (self.unwind_data & 0x3) as u8
+    }
+
+    pub fn xdata_rva(&self) -> Option<u32> {
+        if self.flag() == 0 {
+            Some(self.unwind_data)
+        } else {
+            None
+        }
+    }
+
+    /// Packed unwind data, for flags 1 and 2. Flag 3 is reserved.
+    pub fn packed(&self) -> Option<Arm64PackedUnwindData> {
+        if !matches!(self.flag(), 1 | 2) {
+            return None;
+        }
+        let data = self.unwind_data;
+        Some(Arm64PackedUnwindData {
+            flag: (data & 0x3) as u8,
+            function_length: ((data >> 2) & 0x7ff) * 4,
+            reg_f: ((data >> 13) & 0x7) as u8,
+            reg_i: ((data >> 16) & 0xf) as u8,
+            h: (data >> 20) & 0x1 != 0,
+            cr: ((data >> 21) & 0x3) as u8,
+            frame_size: ((data >> 23) & 0x1ff) * 16,
+        })
+    }
+}
+
+impl<'a> Parse<'a> for Arm64RuntimeFunction {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (begin_address, unwind_data)) =
+            context("ARM64 runtime function", tuple((le_u32, le_u32)))(input)?;
+
+        Ok((
+            rest,
+            Self {
+                begin_address,
+                unwind_data,
+            },
+        ))
+    }
+}
+
+/// Unwind data packed directly in the `.pdata` entry.
+#[derive(Debug)]
+pub struct Arm64PackedUnwindData {
+    /// 1 for packed unwind data, 2 for a packed fragment without prologue.
+    pub flag: u8,
+
+    /// Length of the function, in bytes.
+    pub function_length: u32,
+
+    /// Number of non-volatile FP registers (d8-d15) saved, minus one. 0 means none.
+    pub reg_f: u8,
+
+    /// Number of non-volatile integer registers (x19-x28) saved.
+    pub reg_i: u8,
+
+    /// Whether the function homes its integer parameter registers (x0-x7).
+    pub h: bool,
+
+    /// 0: unchained, 1: unchained with saved LR, 2: chained with PAC signed LR, 3: chained.
+    pub cr: u8,
+
+    /// Size of the stack frame, in bytes.
+    pub frame_size: u32,
+}
+
+impl fmt::Display for Arm64PackedUnwindData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}flag: {}\n", self.flag)?;
+        write!(f, "{offset}reg_f: {}\n", self.reg_f)?;
+        write!(f, "{offset}reg_i: {}\n", self.reg_i)?;
+        write!(f, "{offset}h: {}\n", self.h)?;
+        write!(f, "{offset}cr: {}\n", self.cr)?;
+        write!(f, "{offset}frame_size: 0x{:x}\n", self.frame_size)
+    }
+}
+
+#[derive(Debug)]
+pub struct Arm64EpilogScope {
+    /// Offset of the epilog relative to the start of the function, in bytes.
+    pub start_offset: u32,
+
+    /// Index of the first unwind code describing this epilog.
+    pub start_index: u16,
+}
+
+impl<'a> Parse<'a> for Arm64EpilogScope {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, scope) = context("ARM64 epilog scope", le_u32)(input)?;
+
+        Ok((
+            rest,
+            Self {
+                start_offset: (scope & 0x3ffff) * 4,
+                start_index: (scope >> 22) as u16,
+            },
+        ))
+    }
+}
+
+/// Unwind data stored in an `.xdata` record.
+#[derive(Debug)]
+pub struct Arm64XData<'a> {
+    /// Length of the function, in bytes.
+    pub function_length: u32,
+    pub version: u8,
+
+    /// Whether an exception handler follows the unwind codes.
+    pub x: bool,
+
+    /// Whether the single epilog is packed in the header, in which case `epilog_count` holds the
+    /// index of its first unwind code and `epilog_scopes` is empty.
+    pub e: bool,
+    pub epilog_count: u16,
+    pub code_words: u8,
+    pub epilog_scopes: Vec<Arm64EpilogScope>,
+    pub unwind_codes: &'a [u8],
+    pub exception_handler: Option<u32>,
+}
+
+impl<'a> fmt::Display for Arm64XData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}version: {}\n", self.version)?;
+        write!(f, "{offset}x: {}\n", self.x)?;
+        write!(f, "{offset}e: {}\n", self.e)?;
+        write!(f, "{offset}epilog_count: {}\n", self.epilog_count)?;
+        write!(f, "{offset}code_words: {}\n", self.code_words)?;
+        for scope in &self.epilog_scopes {
+            write!(
+                f,
+                "{offset}epilog: offset=0x{:x} index={}\n",
+                scope.start_offset, scope.start_index
+            )?;
+        }
+        if let Some(handler) = self.exception_handler {
+            write!(f, "{offset}exception_handler: 0x{:x}\n", handler)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for Arm64XData<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (mut rest, header) = context("ARM64 xdata header", le_u32)(input)?;
+
+        let function_length = (header & 0x3ffff) * 4;
+        let version = ((header >> 18) & 0x3) as u8;
+        let x = (header >> 20) & 0x1 != 0;
+        let e = (header >> 21) & 0x1 != 0;
+        let mut epilog_count = ((header >> 22) & 0x1f) as u16;
+        let mut code_words = (header >> 27) as u8;
+
+        if epilog_count == 0 && code_words == 0 {
+            let (r, extended) = context("ARM64 xdata extended header", le_u32)(rest)?;
+            epilog_count = extended as u16;
+            code_words = (extended >> 16) as u8;
+            rest = r;
+        }
+
+        let scopes_count = if e { 0 } else { epilog_count as usize };
+        let (rest, (epilog_scopes, unwind_codes, exception_handler)) = context(
+            "ARM64 xdata",
+            tuple((
+                count(Arm64EpilogScope::parse, scopes_count),
+                take(code_words as usize * 4),
+                cond(x, le_u32),
+            )),
+        )(rest)?;
+
+        Ok((
+            rest,
+            Self {
+                function_length,
+                version,
+                x,
+                e,
+                epilog_count,
+                code_words,
+                epilog_scopes,
+                unwind_codes,
+                exception_handler,
+            },
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub enum Arm64UnwindInfo<'a> {
+    Packed(Arm64PackedUnwindData),
+    Unpacked(Arm64XData<'a>),
+    /// `unwind_data` of an entry using the reserved flag, or whose `.xdata` record could not be
+    /// decoded.
+    Raw(u32),
+}
+
+/// ARM64 function described by the exception directory, whichever unwind form it uses.
+#[derive(Debug)]
+pub struct Arm64Function<'a> {
+    pub begin_address: u32,
+
+    /// Length of the function, in bytes.
+    pub function_length: u32,
+    pub unwind_info: Arm64UnwindInfo<'a>,
+}
+
+impl<'a> Arm64Function<'a> {
+    pub fn end_address(&self) -> u32 {
+        self.begin_address.saturating_add(self.function_length)
+    }
+}
+
+impl<'a> fmt::Display for Arm64Function<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(
+            f,
+            "{offset}0x{:08x}-0x{:08x}",
+            self.begin_address,
+            self.end_address()
+        )?;
+        match self.unwind_info {
+            Arm64UnwindInfo::Packed(ref packed) => write!(f, " (packed)\n{:width$}", packed),
+            Arm64UnwindInfo::Unpacked(ref xdata) => write!(f, " (xdata)\n{:width$}", xdata),
+            Arm64UnwindInfo::Raw(unwind_data) => write!(f, " (raw 0x{:08x})\n", unwind_data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    #[test]
+    fn packed_unwind_data() {
+        // flag 1, 16 instructions, 2 integer registers, chained, 64-byte frame
+        let unwind_data = 1 | (0x10 << 2) | (2 << 16) | (3 << 21) | (4 << 23);
+        let mut input = 0x1000u32.to_le_bytes().to_vec();
+        input.extend_from_slice(&u32::to_le_bytes(unwind_data));
+
+        let (rest, function) = Arm64RuntimeFunction::parse::<Error>(&input).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(function.begin_address, 0x1000);
+        assert_eq!(function.xdata_rva(), None);
+
+        let packed = function.packed().unwrap();
+        assert_eq!(packed.flag, 1);
+        assert_eq!(packed.function_length, 0x40);
+        assert_eq!(packed.reg_f, 0);
+        assert_eq!(packed.reg_i, 2);
+        assert!(!packed.h);
+        assert_eq!(packed.cr, 3);
+        assert_eq!(packed.frame_size, 0x40);
+    }
+
+    #[test]
+    fn reserved_flag() {
+        let function = Arm64RuntimeFunction {
+            begin_address: 0x1000,
+            unwind_data: 0xffff_ffff,
+        };
+        assert_eq!(function.flag(), 3);
+        assert!(function.packed().is_none());
+        assert_eq!(function.xdata_rva(), None);
+    }
+
+    #[test]
+    fn xdata_with_epilog_scope_and_handler() {
+        // 0x20 instructions, X set, one epilog scope, one code word
+        let header: u32 = 0x20 | (1 << 20) | (1 << 22) | (1 << 27);
+        let scope: u32 = 0x1c | (2 << 22);
+        let mut input = header.to_le_bytes().to_vec();
+        input.extend_from_slice(&scope.to_le_bytes());
+        input.extend_from_slice(&[0xe1, 0xe4, 0xe3, 0xe3]);
+        input.extend_from_slice(&0x2000u32.to_le_bytes());
+        input.push(0xcc);
+
+        let (rest, xdata) = Arm64XData::parse::<Error>(&input).unwrap();
+        assert_eq!(rest, &[0xcc]);
+        assert_eq!(xdata.function_length, 0x80);
+        assert_eq!(xdata.version, 0);
+        assert!(xdata.x);
+        assert!(!xdata.e);
+        assert_eq!(xdata.epilog_count, 1);
+        assert_eq!(xdata.code_words, 1);
+        assert_eq!(xdata.epilog_scopes.len(), 1);
+        assert_eq!(xdata.epilog_scopes[0].start_offset, 0x70);
+        assert_eq!(xdata.epilog_scopes[0].start_index, 2);
+        assert_eq!(xdata.unwind_codes, &[0xe1, 0xe4, 0xe3, 0xe3]);
+        assert_eq!(xdata.exception_handler, Some(0x2000));
+    }
+
+    #[test]
+    fn xdata_extended_header() {
+        // Both counts 0 in the header: 2 epilog scopes and 1 code word in the extension
+        let header: u32 = 0x8;
+        let extended: u32 = 2 | (1 << 16);
+        let mut input = header.to_le_bytes().to_vec();
+        input.extend_from_slice(&extended.to_le_bytes());
+        input.extend_from_slice(&4u32.to_le_bytes());
+        input.extend_from_slice(&(6u32 | (1 << 22)).to_le_bytes());
+        input.extend_from_slice(&[0xe4; 4]);
+
+        let (rest, xdata) = Arm64XData::parse::<Error>(&input).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(xdata.function_length, 0x20);
+        assert_eq!(xdata.epilog_count, 2);
+        assert_eq!(xdata.code_words, 1);
+        assert_eq!(xdata.epilog_scopes[1].start_offset, 0x18);
+        assert_eq!(xdata.epilog_scopes[1].start_index, 1);
+        assert_eq!(xdata.exception_handler, None);
+    }
+
+    #[test]
+    fn truncated_xdata() {
+        let header: u32 = 0x8 | (1 << 27);
+        assert!(Arm64XData::parse::<Error>(&header.to_le_bytes()).is_err());
+    }
+
+    #[test]
+    fn end_address_saturates() {
+        let function = Arm64Function {
+            begin_address: 0xffff_fff0,
+            function_length: 0x100,
+            unwind_info: Arm64UnwindInfo::Raw(3),
+        };
+        assert_eq!(function.end_address(), u32::MAX);
+    }
+}
@@ -31,10 +31,49 @@ pub struct DosHeader {
     pub e_lfanew: u32,
 }
 
+impl DosHeader {
+    pub const fn size() -> usize {
+        64
+    }
+
+    /// Size of the header, relocation table included, as declared by `e_cparhdr`.
+    pub fn header_size(&self) -> usize {
+        self.e_cparhdr as usize * 16
+    }
+
+    /// Size of the MS-DOS file image, header included. Anything past it is an overlay.
+    pub fn file_image_size(&self) -> usize {
+        let pages = self.e_cp as usize;
+        if self.e_cblp == 0 {
+            pages * 512
+        } else {
+            pages.saturating_sub(1) * 512 + self.e_cblp as usize
+        }
+    }
+
+    /// Size of the load module MS-DOS copies in memory.
+    pub fn load_image_size(&self) -> usize {
+        self.file_image_size().saturating_sub(self.header_size())
+    }
+}
+
 impl fmt::Display for DosHeader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
         write!(f, "{offset}magic: 0x{:04x}\n", self.e_magic)?;
+        write!(f, "{offset}cblp: 0x{:x}\n", self.e_cblp)?;
+        write!(f, "{offset}cp: 0x{:x}\n", self.e_cp)?;
+        write!(f, "{offset}crlc: 0x{:x}\n", self.e_crlc)?;
+        write!(f, "{offset}cparhdr: 0x{:x}\n", self.e_cparhdr)?;
+        write!(f, "{offset}minalloc: 0x{:x}\n", self.e_minalloc)?;
+        write!(f, "{offset}maxalloc: 0x{:x}\n", self.e_maxalloc)?;
+        write!(f, "{offset}ss:sp: {:04x}:{:04x}\n", self.e_ss, self.e_sp)?;
+        write!(f, "{offset}csum: 0x{:x}\n", self.e_csum)?;
+        write!(f, "{offset}cs:ip: {:04x}:{:04x}\n", self.e_cs, self.e_ip)?;
+        write!(f, "{offset}lfarlc: 0x{:x}\n", self.e_lfarlc)?;
+        write!(f, "{offset}ovno: 0x{:x}\n", self.e_ovno)?;
+        write!(f, "{offset}oemid: 0x{:x}\n", self.e_oemid)?;
+        write!(f, "{offset}oeminfo: 0x{:x}\n", self.e_oeminfo)?;
         write!(f, "{offset}lfanew: 0x{:x}\n", self.e_lfanew)
     }
 }
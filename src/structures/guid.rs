@@ -0,0 +1,53 @@
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::parsers::count_fixed;
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Guid {
+    pub data1: u32,
+    pub data2: u16,
+    pub data3: u16,
+    pub data4: [u8; 8],
+}
+
+impl Guid {
+    pub const fn new(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Self {
+        Self {
+            data1,
+            data2,
+            data3,
+            data4,
+        }
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-",
+            self.data1, self.data2, self.data3, self.data4[0], self.data4[1]
+        )?;
+        for b in &self.data4[2..] {
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for Guid {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (data1, data2, data3, data4)) =
+            context("GUID", tuple((le_u32, le_u16, le_u16, count_fixed(le_u8))))(input)?;
+
+        Ok((rest, Self::new(data1, data2, data3, data4)))
+    }
+}
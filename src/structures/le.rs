@@ -0,0 +1,365 @@
+use nom::error::context;
+use nom::multi::count;
+
+use crate::parsers::at;
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+use super::ne::{name_table, pascal_string};
+use super::{DosHeader, NeName};
+
+mod entry;
+pub use entry::{LeEntryKind, LeEntryPoint};
+
+mod fixup;
+pub use fixup::{LeFixup, LeFixupTarget};
+
+mod header;
+pub use header::LeHeader;
+
+mod object;
+pub use object::{LeObject, LePage};
+
+/// Linear executable: `LE` for VxDs and `LX` for 32-bit OS/2.
+#[derive(Debug)]
+pub struct LeExecutable<'a> {
+    pub(super) file: &'a [u8],
+    pub dos_header: DosHeader,
+    pub le_header: LeHeader,
+    pub objects: Vec<LeObject>,
+    /// Object page map, indexed by page number minus 1.
+    pub pages: Vec<LePage>,
+    pub fixups: Vec<LeFixup<'a>>,
+    pub entry_points: Vec<LeEntryPoint>,
+    /// Module name followed by the resident exported names.
+    pub resident_names: Vec<NeName<'a>>,
+    /// Module description followed by the non-resident exported names.
+    pub non_resident_names: Vec<NeName<'a>>,
+    pub import_modules: Vec<&'a [u8]>,
+}
+
+impl<'a> LeExecutable<'a> {
+    pub fn module_name(&self) -> Option<&'a [u8]> {
+        self.resident_names.first().map(|name| name.name)
+    }
+
+    pub fn description(&self) -> Option<&'a [u8]> {
+        self.non_resident_names.first().map(|name| name.name)
+    }
+
+    pub fn entry_point(&self, ordinal: u16) -> Option<&LeEntryPoint> {
+        self.entry_points
+            .iter()
+            .find(|entry| entry.ordinal == ordinal)
+    }
+
+    /// Pages of `object`, in order.
+    pub fn object_pages(&self, object: &LeObject) -> &[LePage] {
+        let start = (object.page_table_index as usize)
+            .saturating_sub(1)
+            .min(self.pages.len());
+        let end = (start + object.page_count as usize).min(self.pages.len());
+        &self.pages[start..end]
+    }
+
+    /// Data of `page` as stored in file, without iterated pages expansion.
+    pub fn page_data(&self, page: &LePage) -> Option<&'a [u8]> {
+        self.file.get(page.offset..)?.get(..page.size)
+    }
+}
+
+impl<'a> fmt::Display for LeExecutable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+
+        write!(f, "{offset}dos_header:\n{:width$}", self.dos_header)?;
+        write!(f, "{offset}le_header:\n{:width$}", self.le_header)?;
+        write!(f, "{offset}objects:\n")?;
+        for object in &self.objects {
+            write!(f, "{:width$}", object)?;
+        }
+        write!(f, "{offset}pages:\n")?;
+        for page in &self.pages {
+            write!(f, "{:width$}", page)?;
+        }
+        write!(f, "{offset}fixups: {}\n", self.fixups.len())?;
+        write!(f, "{offset}resident_names:\n")?;
+        for name in &self.resident_names {
+            write!(f, "{:width$}", name)?;
+        }
+        write!(f, "{offset}non_resident_names:\n")?;
+        for name in &self.non_resident_names {
+            write!(f, "{:width$}", name)?;
+        }
+        write!(f, "{offset}import_modules:\n")?;
+        for module in &self.import_modules {
+            write!(f, "{offset}  {}\n", String::from_utf8_lossy(module))?;
+        }
+        write!(f, "{offset}entry_points:\n")?;
+        for entry_point in &self.entry_points {
+            write!(f, "{:width$}", entry_point)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for LeExecutable<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (_, dos_header) = DosHeader::parse(input)?;
+        let le_data = at(input, dos_header.e_lfanew as usize)?;
+        let (_, le_header) = LeHeader::parse(le_data)?;
+
+        let (_, objects) = context(
+            "LE object table",
+            count(LeObject::parse, le_header.object_count as usize),
+        )(at(le_data, le_header.object_table_offset as usize)?)?;
+
+        let page_count = le_header.page_count as usize;
+        let data_pages_offset = le_header.data_pages_offset as usize;
+        let page_size = le_header.page_size as usize;
+        let mut page_map = at(le_data, le_header.object_page_table_offset as usize)?;
+        // Do not trust `page_count` before the page map is read
+        let entry_size = if le_header.is_lx { 8 } else { 4 };
+        let mut pages = Vec::with_capacity(page_count.min(page_map.len() / entry_size));
+        for idx in 0..page_count {
+            let (rest, page) = if le_header.is_lx {
+                LePage::parse_lx(
+                    page_map,
+                    data_pages_offset,
+                    le_header.last_page_size_or_page_shift,
+                )?
+            } else {
+                let last_page_size = if idx + 1 == page_count {
+                    Some(le_header.last_page_size_or_page_shift as usize)
+                } else {
+                    None
+                };
+                LePage::parse_le(page_map, data_pages_offset, page_size, last_page_size)?
+            };
+            pages.push(page);
+            page_map = rest;
+        }
+
+        let fixups = if le_header.fixup_page_table_offset != 0 {
+            fixup::fixups(
+                at(le_data, le_header.fixup_page_table_offset as usize)?,
+                page_count,
+                at(le_data, le_header.fixup_record_table_offset as usize)?,
+                at(le_data, le_header.import_procedure_table_offset as usize)?,
+            )?
+        } else {
+            Vec::new()
+        };
+
+        let (_, entry_points) = context("LE entry table", entry::entry_table)(at(
+            le_data,
+            le_header.entry_table_offset as usize,
+        )?)?;
+
+        let (_, resident_names) = context("LE resident name table", name_table)(at(
+            le_data,
+            le_header.resident_name_table_offset as usize,
+        )?)?;
+
+        let non_resident_names = if le_header.non_resident_name_table_size != 0 {
+            let (_, non_resident_names) = context("LE non-resident name table", name_table)(at(
+                input,
+                le_header.non_resident_name_table_offset as usize,
+            )?)?;
+            non_resident_names
+        } else {
+            Vec::new()
+        };
+
+        let (_, import_modules) =
+            context(
+                "LE import module table",
+                count(pascal_string, le_header.import_module_count as usize),
+            )(at(le_data, le_header.import_module_table_offset as usize)?)?;
+
+        Ok((
+            &[],
+            Self {
+                file: input,
+                dos_header,
+                le_header,
+                objects,
+                pages,
+                fixups,
+                entry_points,
+                resident_names,
+                non_resident_names,
+                import_modules,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    const LE: usize = 0x40;
+
+    /// Linear executable at 0x40 with one object of two pages, whose data starts at 0x200.
+    /// `page_map` holds the raw page map entries.
+    fn executable(
+        signature: &[u8; 2],
+        page_shift_or_last_page_size: u32,
+        page_map: &[u8],
+    ) -> Vec<u8> {
+        let mut file = vec![0u8; 0x400];
+        file[..2].copy_from_slice(b"MZ");
+        file[0x3c..0x40].copy_from_slice(&(LE as u32).to_le_bytes());
+
+        let put_u32 = |file: &mut Vec<u8>, offset: usize, value: u32| {
+            file[LE + offset..LE + offset + 4].copy_from_slice(&value.to_le_bytes())
+        };
+        file[LE..LE + 2].copy_from_slice(signature);
+        put_u32(&mut file, 0x14, 2);
+        put_u32(&mut file, 0x28, 0x100);
+        put_u32(&mut file, 0x2c, page_shift_or_last_page_size);
+        put_u32(&mut file, 0x40, 0xb0);
+        put_u32(&mut file, 0x44, 1);
+        put_u32(&mut file, 0x48, 0xc8);
+        put_u32(&mut file, 0x58, 0xd8);
+        put_u32(&mut file, 0x5c, 0xd9);
+        put_u32(&mut file, 0x70, 0xda);
+        put_u32(&mut file, 0x80, 0x200);
+
+        // Object table
+        put_u32(&mut file, 0xb0, 0x1000);
+        put_u32(&mut file, 0xb4, 0x10000);
+        put_u32(&mut file, 0xb8, 0x2005);
+        put_u32(&mut file, 0xbc, 1);
+        put_u32(&mut file, 0xc0, 2);
+
+        file[LE + 0xc8..LE + 0xc8 + page_map.len()].copy_from_slice(page_map);
+        file
+    }
+
+    #[test]
+    fn lx_page_offsets() {
+        let page_map = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, // offset 0, size 0x100
+            0x10, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, // offset 0x10 << 4, size 0x80
+        ];
+        let file = executable(b"LX", 4, &page_map);
+        let (_, le) = LeExecutable::parse::<Error>(&file).unwrap();
+        assert!(le.le_header.is_lx);
+        let pages = le.object_pages(&le.objects[0]);
+        assert_eq!(pages.len(), 2);
+        assert_eq!((pages[0].offset, pages[0].size), (0x200, 0x100));
+        assert_eq!((pages[1].offset, pages[1].size), (0x300, 0x80));
+        assert_eq!(le.page_data(&pages[1]).map(<[u8]>::len), Some(0x80));
+    }
+
+    #[test]
+    fn le_page_offsets() {
+        // 24-bit big endian page numbers, the last page being shorter
+        let page_map = [0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00];
+        let file = executable(b"LE", 0x80, &page_map);
+        let (_, le) = LeExecutable::parse::<Error>(&file).unwrap();
+        assert!(!le.le_header.is_lx);
+        assert_eq!((le.pages[0].offset, le.pages[0].size), (0x200, 0x100));
+        assert_eq!((le.pages[1].offset, le.pages[1].size), (0x300, 0x80));
+    }
+
+    #[test]
+    fn invalid_page_shift() {
+        let page_map = [0xff; 16];
+        assert!(LeExecutable::parse::<Error>(&executable(b"LX", 31, &page_map)).is_ok());
+        assert!(LeExecutable::parse::<Error>(&executable(b"LX", 32, &page_map)).is_err());
+        assert!(LeExecutable::parse::<Error>(&executable(b"LX", 64, &page_map)).is_err());
+        // Only LX reads it as a shift
+        assert!(LeExecutable::parse::<Error>(&executable(b"LE", 64, &page_map)).is_ok());
+    }
+
+    #[test]
+    fn huge_page_count() {
+        let mut file = executable(b"LX", 4, &[]);
+        file[LE + 0x14..LE + 0x18].copy_from_slice(&0xffff_fff0u32.to_le_bytes());
+        assert!(LeExecutable::parse::<Error>(&file).is_err());
+    }
+
+    #[test]
+    fn tables() {
+        let page_map = [0; 16];
+        let mut file = executable(b"LX", 4, &page_map);
+        let mut put = |offset: usize, data: &[u8]| {
+            file[offset..offset + data.len()].copy_from_slice(data);
+        };
+
+        // OS/2 DLL
+        put(LE + 0x0a, &1u16.to_le_bytes());
+        put(LE + 0x10, &0x8000u32.to_le_bytes());
+        for (offset, value) in [
+            (0x58, 0x100),
+            (0x5c, 0x110),
+            (0x68, 0x138),
+            (0x6c, 0x144),
+            (0x70, 0x120),
+            (0x74, 2),
+            (0x78, 0x130),
+            (0x88, 0x1c0),
+            (0x8c, 0x10),
+        ] {
+            put(LE + offset, &(value as u32).to_le_bytes());
+        }
+
+        put(LE + 0x100, b"\x04TEST\0\0\x05Entry\x01\0\0");
+        put(
+            LE + 0x110,
+            &[1, 0x03, 0x01, 0x00, 0x01, 0x10, 0x00, 0x00, 0x00, 0],
+        );
+        put(LE + 0x120, b"\x08DOSCALLS\x03MSG");
+        put(LE + 0x130, b"\0\x03Foo");
+        put(LE + 0x138, &[0, 0, 0, 0, 7, 0, 0, 0, 7, 0, 0, 0]);
+        put(LE + 0x144, &[0x07, 0x02, 0x10, 0x00, 0x01, 0x01, 0x00]);
+        put(0x1c0, b"\x04Desc\0\0\x05Other\x02\0\0");
+
+        let (_, le) = LeExecutable::parse::<Error>(&file).unwrap();
+        assert!(le.le_header.is_dll());
+        assert!(!le.le_header.is_vxd());
+        assert_eq!(le.le_header.os_name(), Some("OS/2"));
+
+        let object = &le.objects[0];
+        assert!(object.is_readable() && object.is_executable() && object.is_big());
+        assert!(!object.is_writable() && !object.is_discardable());
+
+        assert_eq!(le.module_name(), Some(&b"TEST"[..]));
+        assert_eq!(le.resident_names[1].name, b"Entry");
+        assert_eq!(le.description(), Some(&b"Desc"[..]));
+        assert_eq!(le.non_resident_names[1].ordinal, 2);
+        assert_eq!(le.import_modules, [&b"DOSCALLS"[..], &b"MSG"[..]]);
+
+        assert_eq!(le.entry_points.len(), 1);
+        assert!(matches!(
+            le.entry_point(1).unwrap().kind,
+            LeEntryKind::Entry32 { offset: 0x10 }
+        ));
+        assert!(le.entry_point(2).is_none());
+
+        assert_eq!(le.fixups.len(), 1);
+        assert!(matches!(
+            le.fixups[0].target,
+            LeFixupTarget::ImportName {
+                module: 1,
+                name: b"Foo"
+            }
+        ));
+
+        // Unterminated resident name table
+        let mut file = executable(b"LX", 4, &page_map);
+        file[LE + 0x58..LE + 0x5c].copy_from_slice(&0x3bbu32.to_le_bytes());
+        file[LE + 0x3bb..].copy_from_slice(b"\x04TEST");
+        assert!(LeExecutable::parse::<Error>(&file).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_u16, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::NomError;
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum LeEntryKind {
+    Entry16 {
+        offset: u16,
+    },
+    CallGate {
+        offset: u16,
+        selector: u16,
+    },
+    Entry32 {
+        offset: u32,
+    },
+    /// Forwarded to the `module`-th import module, by ordinal or by name offset in the import
+    /// procedure table depending on `flags`.
+    Forwarder {
+        module: u16,
+        value: u32,
+    },
+}
+
+#[derive(Debug)]
+pub struct LeEntryPoint {
+    pub ordinal: u16,
+    /// 1-based object number, unused for forwarders.
+    pub object: u16,
+    pub flags: u8,
+    pub kind: LeEntryKind,
+}
+
+impl LeEntryPoint {
+    pub fn is_exported(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+}
+
+impl fmt::Display for LeEntryPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}@{}: ", self.ordinal)?;
+        match self.kind {
+            LeEntryKind::Entry16 { offset } => write!(f, "{}:0x{:04x}", self.object, offset)?,
+            LeEntryKind::CallGate { offset, selector } => write!(
+                f,
+                "{}:0x{:04x} call gate 0x{:04x}",
+                self.object, offset, selector
+            )?,
+            LeEntryKind::Entry32 { offset } => write!(f, "{}:0x{:08x}", self.object, offset)?,
+            LeEntryKind::Forwarder { module, value } => {
+                write!(f, "forwarded to module {} (0x{:x})", module, value)?
+            }
+        }
+        if self.is_exported() {
+            write!(f, " exported")?;
+        }
+        write!(f, "\n")
+    }
+}
+
+/// Parses the bundles of the entry table, unused ordinals being skipped.
+pub(super) fn entry_table<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Vec<LeEntryPoint>, E>
+where
+    E: NomError<'a>,
+{
+    let mut entries = Vec::new();
+    let mut ordinal = 1u16;
+    let mut rest = input;
+
+    loop {
+        let (r, entry_count) = context("LE entry bundle", le_u8)(rest)?;
+        if entry_count == 0 {
+            return Ok((r, entries));
+        }
+        let (r, bundle_type) = context("LE entry bundle", le_u8)(r)?;
+        let entry_count = entry_count as usize;
+
+        // The high bit only tells parameter typing information is present
+        let bundle_type = bundle_type & 0x7f;
+        rest = if bundle_type == 0 {
+            r
+        } else {
+            let (r, object) = context("LE entry bundle object", le_u16)(r)?;
+            let (r, bundle) = match bundle_type {
+                1 => {
+                    let (r, bundle) = context(
+                        "LE 16-bit entries",
+                        count(tuple((le_u8, le_u16)), entry_count),
+                    )(r)?;
+                    let bundle: Vec<_> = bundle
+                        .into_iter()
+                        .map(|(flags, offset)| (flags, LeEntryKind::Entry16 { offset }))
+                        .collect();
+                    (r, bundle)
+                }
+                2 => {
+                    let (r, bundle) = context(
+                        "LE call gate entries",
+                        count(tuple((le_u8, le_u16, le_u16)), entry_count),
+                    )(r)?;
+                    let bundle: Vec<_> = bundle
+                        .into_iter()
+                        .map(|(flags, offset, selector)| {
+                            (flags, LeEntryKind::CallGate { offset, selector })
+                        })
+                        .collect();
+                    (r, bundle)
+                }
+                3 => {
+                    let (r, bundle) = context(
+                        "LE 32-bit entries",
+                        count(tuple((le_u8, le_u32)), entry_count),
+                    )(r)?;
+                    let bundle: Vec<_> = bundle
+                        .into_iter()
+                        .map(|(flags, offset)| (flags, LeEntryKind::Entry32 { offset }))
+                        .collect();
+                    (r, bundle)
+                }
+                4 => {
+                    let (r, bundle) = context(
+                        "LE forwarder entries",
+                        count(tuple((le_u8, le_u16, le_u32)), entry_count),
+                    )(r)?;
+                    let bundle: Vec<_> = bundle
+                        .into_iter()
+                        .map(|(flags, module, value)| {
+                            (flags, LeEntryKind::Forwarder { module, value })
+                        })
+                        .collect();
+                    (r, bundle)
+                }
+                _ => {
+                    return Err(nom::Err::Error(E::add_context(
+                        r,
+                        "Unknown LE entry bundle type",
+                        E::from_error_kind(r, nom::error::ErrorKind::Switch),
+                    )))
+                }
+            };
+            for (idx, (flags, kind)) in bundle.into_iter().enumerate() {
+                entries.push(LeEntryPoint {
+                    ordinal: ordinal.wrapping_add(idx as u16),
+                    object,
+                    flags,
+                    kind,
+                });
+            }
+            r
+        };
+        ordinal = ordinal.wrapping_add(entry_count as u16);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    #[test]
+    fn bundles() {
+        let data = [
+            // 16-bit entry in object 1, then an unused ordinal
+            &[1, 0x01, 0x01, 0x00, 0x03, 0x10, 0x00][..],
+            &[1, 0x00],
+            // Call gate in object 2
+            &[1, 0x02, 0x02, 0x00, 0x01, 0x20, 0x00, 0x34, 0x12],
+            // Two 32-bit entries in object 3, with parameter typing information
+            &[2, 0x83, 0x03, 0x00],
+            &[0x01, 0x78, 0x56, 0x34, 0x12, 0x00, 0x30, 0x00, 0x00, 0x00],
+            // Forwarders to the second import module, by ordinal and by name
+            &[2, 0x04, 0x00, 0x00],
+            &[0x01, 0x02, 0x00, 0x07, 0x00, 0x00, 0x00],
+            &[0x00, 0x02, 0x00, 0x10, 0x00, 0x00, 0x00],
+            &[0, 0xaa],
+        ]
+        .concat();
+        let (rest, entries) = entry_table::<Error>(&data).unwrap();
+        assert_eq!(rest, [0xaa]);
+
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.ordinal,
+                    entry.object,
+                    entry.is_exported(),
+                    &entry.kind,
+                )
+            })
+            .collect();
+        assert!(matches!(
+            entries[..],
+            [
+                (1, 1, true, LeEntryKind::Entry16 { offset: 0x10 }),
+                (
+                    3,
+                    2,
+                    true,
+                    LeEntryKind::CallGate {
+                        offset: 0x20,
+                        selector: 0x1234
+                    }
+                ),
+                (
+                    4,
+                    3,
+                    true,
+                    LeEntryKind::Entry32 {
+                        offset: 0x1234_5678
+                    }
+                ),
+                (5, 3, false, LeEntryKind::Entry32 { offset: 0x30 }),
+                (
+                    6,
+                    0,
+                    true,
+                    LeEntryKind::Forwarder {
+                        module: 2,
+                        value: 7
+                    }
+                ),
+                (
+                    7,
+                    0,
+                    false,
+                    LeEntryKind::Forwarder {
+                        module: 2,
+                        value: 0x10
+                    }
+                ),
+            ]
+        ));
+    }
+
+    #[test]
+    fn unknown_bundle_type() {
+        assert!(entry_table::<Error>(&[1, 0x05, 0x01, 0x00, 0x00, 0x00, 0x00, 0]).is_err());
+        // Missing terminating bundle
+        assert!(entry_table::<Error>(&[1, 0x00]).is_err());
+    }
+
+    #[test]
+    fn ordinal_overflow() {
+        let mut data = [255, 0x00].repeat(256);
+        data.extend_from_slice(&[254, 0x00]);
+        data.extend_from_slice(&[2, 0x01, 0x01, 0x00, 0x01, 0x10, 0x00, 0x01, 0x20, 0x00]);
+        data.extend_from_slice(&[1, 0x01, 0x01, 0x00, 0x01, 0x30, 0x00, 0]);
+        let (_, entries) = entry_table::<Error>(&data).unwrap();
+        let ordinals: Vec<_> = entries.iter().map(|entry| entry.ordinal).collect();
+        assert_eq!(ordinals, [0xffff, 0, 1]);
+    }
+}
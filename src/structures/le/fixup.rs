@@ -0,0 +1,342 @@
+use nom::combinator::cond;
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_i16, le_u16, le_u32, le_u8};
+
+use crate::parsers::at;
+use crate::structures::ne::pascal_string;
+use crate::NomError;
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum LeFixupTarget<'a> {
+    Internal { object: u16, offset: Option<u32> },
+    ImportOrdinal { module: u16, ordinal: u32 },
+    ImportName { module: u16, name: &'a [u8] },
+    EntryTable { ordinal: u16 },
+}
+
+impl<'a> fmt::Display for LeFixupTarget<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Internal {
+                object,
+                offset: Some(offset),
+            } => write!(f, "{}:0x{:x}", object, offset),
+            Self::Internal {
+                object,
+                offset: None,
+            } => write!(f, "{}", object),
+            Self::ImportOrdinal { module, ordinal } => write!(f, "module {}@{}", module, ordinal),
+            Self::ImportName { module, name } => {
+                write!(f, "module {}!{}", module, String::from_utf8_lossy(name))
+            }
+            Self::EntryTable { ordinal } => write!(f, "entry @{}", ordinal),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LeFixup<'a> {
+    /// 0-based index of the page the fixup applies to.
+    pub page: usize,
+    pub source_type: u8,
+    pub target_flags: u8,
+    /// Offsets in the page of the patched locations, which may start in the previous page.
+    pub source_offsets: Vec<i16>,
+    pub target: LeFixupTarget<'a>,
+    pub additive: Option<u32>,
+}
+
+impl<'a> LeFixup<'a> {
+    /// Kind of patched location: byte, 16-bit selector, 16:16 pointer, 16-bit offset, …
+    pub fn source_kind(&self) -> u8 {
+        self.source_type & 0x0f
+    }
+
+    fn parse<E>(
+        input: &'a [u8],
+        page: usize,
+        import_procedures: &'a [u8],
+    ) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, source_type) = context("LE fixup source type", le_u8)(input)?;
+        let (rest, target_flags) = context("LE fixup target flags", le_u8)(rest)?;
+
+        let has_source_list = source_type & 0x20 != 0;
+        let (rest, source_list_count) = if has_source_list {
+            context("LE fixup source count", le_u8)(rest)?
+        } else {
+            (rest, 0)
+        };
+        let (rest, single_source) = cond(!has_source_list, le_i16)(rest)?;
+
+        let wide_index = target_flags & 0x40 != 0;
+        let wide_offset = target_flags & 0x10 != 0;
+        let index = |input: &'a [u8]| -> nom::IResult<&'a [u8], u16, E> {
+            if wide_index {
+                le_u16(input)
+            } else {
+                le_u8(input).map(|(rest, index)| (rest, index as u16))
+            }
+        };
+        let offset = |input: &'a [u8]| -> nom::IResult<&'a [u8], u32, E> {
+            if wide_offset {
+                le_u32(input)
+            } else {
+                le_u16(input).map(|(rest, offset)| (rest, offset as u32))
+            }
+        };
+
+        let (rest, target) = match target_flags & 0x03 {
+            0 => {
+                let (rest, object) = context("LE fixup object", index)(rest)?;
+                // Selector fixups have no offset
+                let (rest, target_offset) = cond(
+                    source_type & 0x0f != 0x02,
+                    context("LE fixup offset", offset),
+                )(rest)?;
+                (
+                    rest,
+                    LeFixupTarget::Internal {
+                        object,
+                        offset: target_offset,
+                    },
+                )
+            }
+            1 => {
+                let (rest, module) = context("LE fixup module", index)(rest)?;
+                let (rest, ordinal) = if target_flags & 0x80 != 0 {
+                    le_u8(rest).map(|(rest, ordinal)| (rest, ordinal as u32))?
+                } else {
+                    context("LE fixup ordinal", offset)(rest)?
+                };
+                (rest, LeFixupTarget::ImportOrdinal { module, ordinal })
+            }
+            2 => {
+                let (rest, module) = context("LE fixup module", index)(rest)?;
+                let (rest, name_offset) = context("LE fixup name", offset)(rest)?;
+                let (_, name) = pascal_string(at(import_procedures, name_offset as usize)?)?;
+                (rest, LeFixupTarget::ImportName { module, name })
+            }
+            _ => {
+                let (rest, ordinal) = context("LE fixup entry", index)(rest)?;
+                (rest, LeFixupTarget::EntryTable { ordinal })
+            }
+        };
+
+        let (rest, additive) = if target_flags & 0x04 != 0 {
+            let (rest, additive) = if target_flags & 0x20 != 0 {
+                le_u32(rest)?
+            } else {
+                le_u16(rest).map(|(rest, additive)| (rest, additive as u32))?
+            };
+            (rest, Some(additive))
+        } else {
+            (rest, None)
+        };
+
+        let (rest, source_offsets) = match single_source {
+            Some(source_offset) => (rest, vec![source_offset]),
+            None => context(
+                "LE fixup source list",
+                count(le_i16, source_list_count as usize),
+            )(rest)?,
+        };
+
+        Ok((
+            rest,
+            Self {
+                page,
+                source_type,
+                target_flags,
+                source_offsets,
+                target,
+                additive,
+            },
+        ))
+    }
+}
+
+impl<'a> fmt::Display for LeFixup<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}page {} type 0x{:02x}",
+            self.page, self.source_type
+        )?;
+        for source_offset in &self.source_offsets {
+            write!(f, " 0x{:x}", source_offset)?;
+        }
+        write!(f, " -> {}", self.target)?;
+        if let Some(additive) = self.additive {
+            write!(f, " + 0x{:x}", additive)?;
+        }
+        write!(f, "\n")
+    }
+}
+
+/// Parses the fixup records of every page, `page_table` holding one offset in `records` per
+/// page and a final one marking the end of the last page records.
+pub(super) fn fixups<'a, E>(
+    page_table: &'a [u8],
+    page_count: usize,
+    records: &'a [u8],
+    import_procedures: &'a [u8],
+) -> Result<Vec<LeFixup<'a>>, nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    let (_, offsets) = context("LE fixup page table", count(le_u32, page_count + 1))(page_table)?;
+
+    let mut fixups = Vec::new();
+    for (page, bounds) in offsets.windows(2).enumerate() {
+        let (start, end) = (bounds[0] as usize, bounds[1] as usize);
+        let mut page_records = records.get(start..end).ok_or_else(|| {
+            nom::Err::Error(E::add_context(
+                records,
+                "LE fixup records are out of table",
+                E::from_error_kind(records, nom::error::ErrorKind::Eof),
+            ))
+        })?;
+        while !page_records.is_empty() {
+            let (rest, fixup) = LeFixup::parse(page_records, page, import_procedures)?;
+            fixups.push(fixup);
+            page_records = rest;
+        }
+    }
+    Ok(fixups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    const IMPORT_PROCEDURES: &[u8] = b"\0\x03Foo";
+
+    fn records() -> (Vec<u8>, Vec<u8>) {
+        let first_page = [
+            // 32-bit offset to object 1
+            &[0x07, 0x10, 0x10, 0x00, 0x01, 0x34, 0x12, 0x00, 0x00][..],
+            // Selector of object 2, starting in the previous page
+            &[0x02, 0x00, 0xfe, 0xff, 0x02],
+            // Import by 8-bit ordinal
+            &[0x07, 0x81, 0x20, 0x00, 0x01, 0x05],
+        ]
+        .concat();
+        let second_page = [
+            // Import by name with a 32-bit additive, patched at two locations
+            &[0x27, 0x26, 0x02, 0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00][..],
+            &[0x30, 0x00, 0x40, 0x00],
+            // Entry table ordinal, with a 16-bit index
+            &[0x07, 0x43, 0x50, 0x00, 0x02, 0x01],
+        ]
+        .concat();
+        (first_page, second_page)
+    }
+
+    #[test]
+    fn records_per_page() {
+        let (first_page, second_page) = records();
+        let records = [&first_page[..], &second_page].concat();
+        let page_table: Vec<u8> = [0, first_page.len(), records.len()]
+            .iter()
+            .flat_map(|offset| (*offset as u32).to_le_bytes())
+            .collect();
+
+        let fixups = fixups::<Error>(&page_table, 2, &records, IMPORT_PROCEDURES).unwrap();
+        let fixups: Vec<_> = fixups
+            .iter()
+            .map(|fixup| {
+                (
+                    fixup.page,
+                    fixup.source_kind(),
+                    &fixup.source_offsets[..],
+                    &fixup.target,
+                    fixup.additive,
+                )
+            })
+            .collect();
+        assert!(matches!(
+            fixups[..],
+            [
+                (
+                    0,
+                    0x07,
+                    [0x10],
+                    LeFixupTarget::Internal {
+                        object: 1,
+                        offset: Some(0x1234)
+                    },
+                    None
+                ),
+                (
+                    0,
+                    0x02,
+                    [-2],
+                    LeFixupTarget::Internal {
+                        object: 2,
+                        offset: None
+                    },
+                    None
+                ),
+                (
+                    0,
+                    0x07,
+                    [0x20],
+                    LeFixupTarget::ImportOrdinal {
+                        module: 1,
+                        ordinal: 5
+                    },
+                    None
+                ),
+                (
+                    1,
+                    0x07,
+                    [0x30, 0x40],
+                    LeFixupTarget::ImportName {
+                        module: 1,
+                        name: b"Foo"
+                    },
+                    Some(0x100)
+                ),
+                (
+                    1,
+                    0x07,
+                    [0x50],
+                    LeFixupTarget::EntryTable { ordinal: 0x102 },
+                    None
+                ),
+            ]
+        ));
+    }
+
+    #[test]
+    fn invalid_records() {
+        let (first_page, _) = records();
+        let page_table: Vec<u8> = [0u32, 0x100]
+            .iter()
+            .flat_map(|offset| offset.to_le_bytes())
+            .collect();
+        assert!(fixups::<Error>(&page_table, 1, &first_page, IMPORT_PROCEDURES).is_err());
+        // Missing the final offset
+        assert!(fixups::<Error>(&page_table[..4], 1, &first_page, IMPORT_PROCEDURES).is_err());
+
+        // Truncated record
+        let page_table: Vec<u8> = [0u32, 8]
+            .iter()
+            .flat_map(|offset| offset.to_le_bytes())
+            .collect();
+        assert!(fixups::<Error>(&page_table, 1, &first_page, IMPORT_PROCEDURES).is_err());
+
+        // Name past the end of the import procedure table
+        let (_, second_page) = records();
+        assert!(LeFixup::parse::<Error>(&second_page, 0, b"\0").is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// Header of linear executables, found at `e_lfanew`. Unless noted otherwise, table offsets
+/// are relative to the header.
+#[derive(Debug)]
+pub struct LeHeader {
+    /// `LX` header (OS/2 2.x) rather than `LE` (VxD, OS/2 1.x mixed mode).
+    pub is_lx: bool,
+    pub byte_order: u8,
+    pub word_order: u8,
+    pub format_level: u32,
+    pub cpu_type: u16,
+    pub os_type: u16,
+    pub module_version: u32,
+    pub module_flags: u32,
+    pub page_count: u32,
+    pub eip_object: u32,
+    pub eip: u32,
+    pub esp_object: u32,
+    pub esp: u32,
+    pub page_size: u32,
+    /// Size of the last page for `LE`, shift applied to page offsets for `LX`.
+    pub last_page_size_or_page_shift: u32,
+    pub fixup_section_size: u32,
+    pub fixup_section_checksum: u32,
+    pub loader_section_size: u32,
+    pub loader_section_checksum: u32,
+    pub object_table_offset: u32,
+    pub object_count: u32,
+    pub object_page_table_offset: u32,
+    pub object_iterated_pages_offset: u32,
+    pub resource_table_offset: u32,
+    pub resource_count: u32,
+    pub resident_name_table_offset: u32,
+    pub entry_table_offset: u32,
+    pub module_directives_offset: u32,
+    pub module_directives_count: u32,
+    pub fixup_page_table_offset: u32,
+    pub fixup_record_table_offset: u32,
+    pub import_module_table_offset: u32,
+    pub import_module_count: u32,
+    pub import_procedure_table_offset: u32,
+    pub per_page_checksum_offset: u32,
+    /// File offset.
+    pub data_pages_offset: u32,
+    pub preload_page_count: u32,
+    /// File offset.
+    pub non_resident_name_table_offset: u32,
+    pub non_resident_name_table_size: u32,
+    pub non_resident_name_table_checksum: u32,
+    pub auto_data_segment_object: u32,
+    /// File offset.
+    pub debug_info_offset: u32,
+    pub debug_info_size: u32,
+    pub instance_preload_count: u32,
+    pub instance_demand_count: u32,
+    pub heap_size: u32,
+}
+
+impl LeHeader {
+    /// Windows 386 virtual device driver.
+    pub fn is_vxd(&self) -> bool {
+        self.os_type == 4
+    }
+
+    pub fn is_dll(&self) -> bool {
+        self.module_flags & 0x00038000 == 0x00008000
+    }
+
+    pub fn os_name(&self) -> Option<&'static str> {
+        Some(match self.os_type {
+            1 => "OS/2",
+            2 => "Windows",
+            3 => "DOS 4.x",
+            4 => "Windows 386",
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for LeHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}signature: {}\n",
+            if self.is_lx { "LX" } else { "LE" }
+        )?;
+        write!(f, "{offset}cpu_type: 0x{:x}\n", self.cpu_type)?;
+        match self.os_name() {
+            Some(os_name) => write!(f, "{offset}os_type: {}\n", os_name)?,
+            None => write!(f, "{offset}os_type: 0x{:x}\n", self.os_type)?,
+        }
+        write!(f, "{offset}module_version: 0x{:x}\n", self.module_version)?;
+        write!(f, "{offset}module_flags: 0x{:08x}\n", self.module_flags)?;
+        write!(f, "{offset}page_count: {}\n", self.page_count)?;
+        write!(f, "{offset}page_size: 0x{:x}\n", self.page_size)?;
+        write!(f, "{offset}eip: {}:0x{:x}\n", self.eip_object, self.eip)?;
+        write!(f, "{offset}esp: {}:0x{:x}\n", self.esp_object, self.esp)?;
+        write!(f, "{offset}object_count: {}\n", self.object_count)?;
+        write!(
+            f,
+            "{offset}import_module_count: {}\n",
+            self.import_module_count
+        )?;
+        write!(
+            f,
+            "{offset}data_pages_offset: 0x{:x}\n",
+            self.data_pages_offset
+        )
+    }
+}
+
+impl<'a> Parse<'a> for LeHeader {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            rest,
+            (
+                signature,
+                byte_order,
+                word_order,
+                format_level,
+                cpu_type,
+                os_type,
+                module_version,
+                module_flags,
+                page_count,
+                eip_object,
+                eip,
+                esp_object,
+                esp,
+                page_size,
+                last_page_size_or_page_shift,
+            ),
+        ) = context(
+            "LE header",
+            tuple((
+                alt((tag(b"LE"), tag(b"LX"))),
+                le_u8,
+                le_u8,
+                le_u32,
+                le_u16,
+                le_u16,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+                le_u32,
+            )),
+        )(input)?;
+        let is_lx = signature == b"LX";
+        if is_lx && last_page_size_or_page_shift >= 32 {
+            let e = E::from_error_kind(input, nom::error::ErrorKind::Verify);
+            return Err(nom::Err::Error(E::add_context(input, "LX page shift", e)));
+        }
+        let (
+            rest,
+            (
+                fixup_section_size,
+                fixup_section_checksum,
+                loader_section_size,
+                loader_section_checksum,
+                object_table_offset,
+                object_count,
+                object_page_table_offset,
+                object_iterated_pages_offset,
+                resource_table_offset,
+                resource_count,
+                resident_name_table_offset,
+                entry_table_offset,
+                module_directives_offset,
+                module_directives_count,
+                fixup_page_table_offset,
+                fixup_record_table_offset,
+                import_module_table_offset,
+                import_module_count,
+                import_procedure_table_offset,
+                per_page_checksum_offset,
+            ),
+        ) = context(
+            "LE header",
+            tuple((
+                le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
+                le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
+            )),
+        )(rest)?;
+        let (
+            rest,
+            (
+                data_pages_offset,
+                preload_page_count,
+                non_resident_name_table_offset,
+                non_resident_name_table_size,
+                non_resident_name_table_checksum,
+                auto_data_segment_object,
+                debug_info_offset,
+                debug_info_size,
+                instance_preload_count,
+                instance_demand_count,
+                heap_size,
+            ),
+        ) = context(
+            "LE header",
+            tuple((
+                le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
+                le_u32,
+            )),
+        )(rest)?;
+
+        Ok((
+            rest,
+            Self {
+                is_lx,
+                byte_order,
+                word_order,
+                format_level,
+                cpu_type,
+                os_type,
+                module_version,
+                module_flags,
+                page_count,
+                eip_object,
+                eip,
+                esp_object,
+                esp,
+                page_size,
+                last_page_size_or_page_shift,
+                fixup_section_size,
+                fixup_section_checksum,
+                loader_section_size,
+                loader_section_checksum,
+                object_table_offset,
+                object_count,
+                object_page_table_offset,
+                object_iterated_pages_offset,
+                resource_table_offset,
+                resource_count,
+                resident_name_table_offset,
+                entry_table_offset,
+                module_directives_offset,
+                module_directives_count,
+                fixup_page_table_offset,
+                fixup_record_table_offset,
+                import_module_table_offset,
+                import_module_count,
+                import_procedure_table_offset,
+                per_page_checksum_offset,
+                data_pages_offset,
+                preload_page_count,
+                non_resident_name_table_offset,
+                non_resident_name_table_size,
+                non_resident_name_table_checksum,
+                auto_data_segment_object,
+                debug_info_offset,
+                debug_info_size,
+                instance_preload_count,
+                instance_demand_count,
+                heap_size,
+            },
+        ))
+    }
+}
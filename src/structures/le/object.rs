@@ -0,0 +1,168 @@
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+#[derive(Debug)]
+pub struct LeObject {
+    pub virtual_size: u32,
+    pub base_address: u32,
+    pub flags: u32,
+    /// 1-based index of the first page of the object in the page map.
+    pub page_table_index: u32,
+    pub page_count: u32,
+    pub reserved: u32,
+}
+
+impl LeObject {
+    pub fn is_readable(&self) -> bool {
+        self.flags & 0x0001 != 0
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.flags & 0x0002 != 0
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.flags & 0x0004 != 0
+    }
+
+    pub fn is_discardable(&self) -> bool {
+        self.flags & 0x0010 != 0
+    }
+
+    /// 32-bit object, as opposed to a 16:16 one.
+    pub fn is_big(&self) -> bool {
+        self.flags & 0x2000 != 0
+    }
+}
+
+impl fmt::Display for LeObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}base: 0x{:08x}, size: 0x{:x}, pages: {}+{}, flags: 0x{:04x} {}{}{}\n",
+            self.base_address,
+            self.virtual_size,
+            self.page_table_index,
+            self.page_count,
+            self.flags,
+            if self.is_readable() { "R" } else { "-" },
+            if self.is_writable() { "W" } else { "-" },
+            if self.is_executable() { "X" } else { "-" },
+        )
+    }
+}
+
+impl<'a> Parse<'a> for LeObject {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (virtual_size, base_address, flags, page_table_index, page_count, reserved)) =
+            context(
+                "LE object",
+                tuple((le_u32, le_u32, le_u32, le_u32, le_u32, le_u32)),
+            )(input)?;
+
+        Ok((
+            rest,
+            Self {
+                virtual_size,
+                base_address,
+                flags,
+                page_table_index,
+                page_count,
+                reserved,
+            },
+        ))
+    }
+}
+
+/// Entry of the object page map.
+#[derive(Debug)]
+pub struct LePage {
+    /// File offset of the page data.
+    pub offset: usize,
+    pub size: usize,
+    pub flags: u16,
+}
+
+impl LePage {
+    /// Legal physical page, as opposed to iterated, invalid or zero-filled ones.
+    pub fn is_physical(&self) -> bool {
+        self.flags == 0
+    }
+
+    pub fn is_zero_filled(&self) -> bool {
+        self.flags == 3
+    }
+
+    /// Parses an `LE` page map entry: a 24-bit big endian page number and a flags byte.
+    pub(super) fn parse_le<'a, E>(
+        input: &'a [u8],
+        data_pages_offset: usize,
+        page_size: usize,
+        last_page_size: Option<usize>,
+    ) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (high, low, flags)) = context("LE page", tuple((le_u16, le_u8, le_u8)))(input)?;
+        let number = (high.swap_bytes() as usize) << 8 | low as usize;
+
+        Ok((
+            rest,
+            Self {
+                offset: data_pages_offset + number.saturating_sub(1) * page_size,
+                size: last_page_size.unwrap_or(page_size),
+                flags: flags as u16,
+            },
+        ))
+    }
+
+    /// Parses an `LX` page map entry.
+    pub(super) fn parse_lx<'a, E>(
+        input: &'a [u8],
+        data_pages_offset: usize,
+        page_shift: u32,
+    ) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (offset, size, flags)) =
+            context("LX page", tuple((le_u32, le_u16, le_u16)))(input)?;
+        // `page_shift` was checked to be below 32 by the header
+        let offset = usize::try_from((offset as u64) << page_shift)
+            .ok()
+            .and_then(|offset| data_pages_offset.checked_add(offset))
+            .ok_or_else(|| {
+                let e = E::from_error_kind(input, nom::error::ErrorKind::TooLarge);
+                nom::Err::Error(E::add_context(input, "LX page offset", e))
+            })?;
+
+        Ok((
+            rest,
+            Self {
+                offset,
+                size: size as usize,
+                flags,
+            },
+        ))
+    }
+}
+
+impl fmt::Display for LePage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}offset: 0x{:x}, size: 0x{:x}, flags: 0x{:x}\n",
+            self.offset, self.size, self.flags
+        )
+    }
+}
@@ -0,0 +1,190 @@
+use nom::bytes::complete::take;
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::le_u16;
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+use super::DosHeader;
+
+/// Far pointer to a segment value MS-DOS patches with the load segment.
+#[derive(Debug)]
+pub struct MzRelocation {
+    pub offset: u16,
+    pub segment: u16,
+}
+
+impl MzRelocation {
+    /// Offset of the patched word in the load module.
+    pub fn linear_address(&self) -> usize {
+        (self.segment as usize) * 16 + self.offset as usize
+    }
+}
+
+impl fmt::Display for MzRelocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}:{:04x}", self.segment, self.offset)
+    }
+}
+
+impl<'a> Parse<'a> for MzRelocation {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (offset, segment)) = context("MZ relocation", tuple((le_u16, le_u16)))(input)?;
+
+        Ok((rest, Self { offset, segment }))
+    }
+}
+
+/// MS-DOS executable, without any new executable header.
+#[derive(Debug)]
+pub struct MzExecutable<'a> {
+    pub dos_header: DosHeader,
+    pub relocations: Vec<MzRelocation>,
+
+    /// Load module, the part of the file image following the header.
+    pub image: &'a [u8],
+
+    /// Data appended to the file image.
+    pub overlay: &'a [u8],
+}
+
+impl<'a> fmt::Display for MzExecutable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+
+        write!(f, "{offset}dos_header:\n{:width$}", self.dos_header)?;
+        write!(f, "{offset}image_size: 0x{:x}\n", self.image.len())?;
+        write!(f, "{offset}overlay_size: 0x{:x}\n", self.overlay.len())?;
+        write!(f, "{offset}relocations:\n")?;
+        for relocation in &self.relocations {
+            write!(f, "{offset}  {}\n", relocation)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for MzExecutable<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (_, dos_header) = DosHeader::parse(input)?;
+
+        let (relocation_table, _) = take(dos_header.e_lfarlc as usize)(input)?;
+        let (_, relocations) = context(
+            "MZ relocations",
+            count(MzRelocation::parse, dos_header.e_crlc as usize),
+        )(relocation_table)?;
+
+        // Truncated files are tolerated, MS-DOS loads whatever is present.
+        let file_image_size = dos_header.file_image_size().min(input.len());
+        let (overlay, file_image) = take(file_image_size)(input)?;
+        let (image, _) = context("MZ header", take(dos_header.header_size()))(file_image)?;
+
+        Ok((
+            &[],
+            Self {
+                dos_header,
+                relocations,
+                image,
+                overlay,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::Executable;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    /// 0x500 bytes file with a 0x60 bytes header, and two relocations at 0x40.
+    fn program(e_cblp: u16, e_cp: u16) -> Vec<u8> {
+        let mut file: Vec<u8> = (0..0x500).map(|idx| idx as u8).collect();
+        file[..0x40].fill(0);
+        file[..2].copy_from_slice(b"MZ");
+        file[2..4].copy_from_slice(&e_cblp.to_le_bytes());
+        file[4..6].copy_from_slice(&e_cp.to_le_bytes());
+        file[6..8].copy_from_slice(&2u16.to_le_bytes());
+        file[8..10].copy_from_slice(&6u16.to_le_bytes());
+        file[0x18..0x1a].copy_from_slice(&0x40u16.to_le_bytes());
+        file[0x40..0x48].copy_from_slice(&[0x10, 0, 0x02, 0, 0xfe, 0xff, 0x00, 0x10]);
+        file
+    }
+
+    #[test]
+    fn image_sizes() {
+        let file = program(0x10, 3);
+        let (_, mz) = MzExecutable::parse::<Error>(&file).unwrap();
+        assert_eq!(mz.dos_header.file_image_size(), 0x410);
+        assert_eq!(mz.dos_header.load_image_size(), 0x3b0);
+        assert_eq!(mz.image, &file[0x60..0x410]);
+        assert_eq!(mz.overlay, &file[0x410..]);
+
+        // The last page is a full one
+        let file = program(0, 2);
+        let (_, mz) = MzExecutable::parse::<Error>(&file).unwrap();
+        assert_eq!(mz.dos_header.file_image_size(), 0x400);
+        assert_eq!(mz.image.len(), 0x3a0);
+        assert_eq!(mz.overlay.len(), 0x100);
+
+        // Truncated file image
+        let file = program(0x20, 4);
+        let (_, mz) = MzExecutable::parse::<Error>(&file).unwrap();
+        assert_eq!(mz.dos_header.file_image_size(), 0x620);
+        assert_eq!(mz.dos_header.load_image_size(), 0x5c0);
+        assert_eq!(mz.image, &file[0x60..]);
+        assert!(mz.overlay.is_empty());
+
+        // Header larger than the file image
+        let mut file = program(0x10, 1);
+        file[8..10].copy_from_slice(&0x10u16.to_le_bytes());
+        assert_eq!(
+            DosHeader::parse::<Error>(&file)
+                .unwrap()
+                .1
+                .load_image_size(),
+            0
+        );
+        assert!(MzExecutable::parse::<Error>(&file).is_err());
+    }
+
+    #[test]
+    fn relocations() {
+        let file = program(0x10, 3);
+        let (_, mz) = MzExecutable::parse::<Error>(&file).unwrap();
+        assert_eq!(mz.relocations.len(), 2);
+        assert_eq!(mz.relocations[0].linear_address(), 0x30);
+        assert_eq!(mz.relocations[0].to_string(), "0002:0010");
+        assert_eq!(mz.relocations[1].linear_address(), 0x1_fffe);
+        assert_eq!(mz.relocations[1].to_string(), "1000:fffe");
+
+        // Relocation table running past the end of the file
+        let mut file = program(0x10, 3);
+        file[6..8].copy_from_slice(&0x140u16.to_le_bytes());
+        assert!(MzExecutable::parse::<Error>(&file).is_err());
+        let mut file = program(0x10, 3);
+        file[0x18..0x1a].copy_from_slice(&0x4fe0u16.to_le_bytes());
+        assert!(MzExecutable::parse::<Error>(&file).is_err());
+    }
+
+    #[test]
+    fn garbage_lfanew() {
+        for e_lfanew in [0x80u32, 0x4fe, 0xffff_ffff] {
+            let mut file = program(0x10, 3);
+            file[0x3c..0x40].copy_from_slice(&e_lfanew.to_le_bytes());
+            let (_, executable) = Executable::parse::<Error>(&file).unwrap();
+            assert!(matches!(executable, Executable::Mz(_)));
+            assert_eq!(executable.dos_header().e_lfanew, e_lfanew);
+        }
+    }
+}
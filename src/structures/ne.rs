@@ -0,0 +1,368 @@
+use nom::bytes::complete::take;
+use nom::error::context;
+use nom::multi::{count, length_data};
+use nom::number::complete::{le_u16, le_u8};
+
+use crate::parsers::at;
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+use super::DosHeader;
+
+mod entry;
+pub use entry::NeEntryPoint;
+
+mod header;
+pub use header::NeHeader;
+
+mod resource;
+pub use resource::{NeResource, NeResourceId, NeResourceTable, NeResourceType};
+
+mod segment;
+pub use segment::NeSegment;
+
+/// Length-prefixed string, as found in every NE name table.
+pub(crate) fn pascal_string<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], &'a [u8], E>
+where
+    E: NomError<'a>,
+{
+    context("Pascal string", length_data(le_u8))(input)
+}
+
+#[derive(Debug)]
+pub struct NeName<'a> {
+    pub name: &'a [u8],
+    pub ordinal: u16,
+}
+
+impl<'a> fmt::Display for NeName<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}@{}: {}\n",
+            self.ordinal,
+            String::from_utf8_lossy(self.name)
+        )
+    }
+}
+
+/// Parses a resident or non-resident name table, up to its empty terminating entry.
+pub(super) fn name_table<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Vec<NeName<'a>>, E>
+where
+    E: NomError<'a>,
+{
+    let mut names = Vec::new();
+    let mut rest = input;
+    loop {
+        let (r, name) = pascal_string(rest)?;
+        if name.is_empty() {
+            return Ok((r, names));
+        }
+        let (r, ordinal) = context("Name ordinal", le_u16)(r)?;
+        names.push(NeName { name, ordinal });
+        rest = r;
+    }
+}
+
+/// Segmented executable, used by 16-bit Windows and OS/2 1.x.
+#[derive(Debug)]
+pub struct NeExecutable<'a> {
+    pub(super) file: &'a [u8],
+    pub dos_header: DosHeader,
+    pub ne_header: NeHeader,
+    pub segments: Vec<NeSegment>,
+    pub resource_table: Option<NeResourceTable<'a>>,
+    /// Module name followed by the names exported while the module is loaded.
+    pub resident_names: Vec<NeName<'a>>,
+    /// Module description followed by the names only exported by ordinal at run time.
+    pub non_resident_names: Vec<NeName<'a>>,
+    /// Names of the imported modules, in module reference order.
+    pub module_references: Vec<&'a [u8]>,
+    pub entry_points: Vec<NeEntryPoint>,
+}
+
+impl<'a> NeExecutable<'a> {
+    pub fn module_name(&self) -> Option<&'a [u8]> {
+        self.resident_names.first().map(|name| name.name)
+    }
+
+    pub fn description(&self) -> Option<&'a [u8]> {
+        self.non_resident_names.first().map(|name| name.name)
+    }
+
+    pub fn entry_point(&self, ordinal: u16) -> Option<&NeEntryPoint> {
+        self.entry_points
+            .iter()
+            .find(|entry| entry.ordinal == ordinal)
+    }
+
+    /// Exported names with their entry point, from both name tables.
+    pub fn exports(&self) -> impl Iterator<Item = (&'a [u8], &NeEntryPoint)> + '_ {
+        self.resident_names
+            .iter()
+            .skip(1)
+            .chain(self.non_resident_names.iter().skip(1))
+            .filter_map(|name| Some((name.name, self.entry_point(name.ordinal)?)))
+    }
+
+    /// Data of the 1-based `segment`, as stored in file.
+    pub fn segment_data(&self, segment: u16) -> Option<&'a [u8]> {
+        let segment = self.segments.get((segment as usize).checked_sub(1)?)?;
+        let offset = segment.file_offset(self.ne_header.alignment_shift());
+        self.file.get(offset..)?.get(..segment.file_size())
+    }
+}
+
+impl<'a> fmt::Display for NeExecutable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+
+        write!(f, "{offset}dos_header:\n{:width$}", self.dos_header)?;
+        write!(f, "{offset}ne_header:\n{:width$}", self.ne_header)?;
+        write!(f, "{offset}segments:\n")?;
+        for segment in &self.segments {
+            write!(f, "{:width$}", segment)?;
+        }
+        if let Some(ref resource_table) = self.resource_table {
+            write!(f, "{offset}resource_table:\n{:width$}", resource_table)?;
+        }
+        write!(f, "{offset}resident_names:\n")?;
+        for name in &self.resident_names {
+            write!(f, "{:width$}", name)?;
+        }
+        write!(f, "{offset}non_resident_names:\n")?;
+        for name in &self.non_resident_names {
+            write!(f, "{:width$}", name)?;
+        }
+        write!(f, "{offset}module_references:\n")?;
+        for module in &self.module_references {
+            write!(f, "{offset}  {}\n", String::from_utf8_lossy(module))?;
+        }
+        write!(f, "{offset}entry_points:\n")?;
+        for entry_point in &self.entry_points {
+            write!(f, "{:width$}", entry_point)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for NeExecutable<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (_, dos_header) = DosHeader::parse(input)?;
+        let ne_data = at(input, dos_header.e_lfanew as usize)?;
+        let (_, ne_header) = NeHeader::parse(ne_data)?;
+
+        let (_, segments) = context(
+            "NE segment table",
+            count(NeSegment::parse, ne_header.segment_count as usize),
+        )(at(ne_data, ne_header.segment_table_offset as usize)?)?;
+
+        // An empty resource table is advertised by pointing it at the resident name table.
+        let resource_table =
+            if ne_header.resource_table_offset != ne_header.resident_name_table_offset {
+                let (_, resource_table) =
+                    NeResourceTable::parse(at(ne_data, ne_header.resource_table_offset as usize)?)?;
+                Some(resource_table)
+            } else {
+                None
+            };
+
+        let (_, resident_names) = context("NE resident name table", name_table)(at(
+            ne_data,
+            ne_header.resident_name_table_offset as usize,
+        )?)?;
+
+        let non_resident_names = if ne_header.non_resident_name_table_size != 0 {
+            let (_, non_resident_names) = context("NE non-resident name table", name_table)(at(
+                input,
+                ne_header.non_resident_name_table_offset as usize,
+            )?)?;
+            non_resident_names
+        } else {
+            Vec::new()
+        };
+
+        let (_, module_offsets) = context(
+            "NE module reference table",
+            count(le_u16, ne_header.module_reference_count as usize),
+        )(at(
+            ne_data,
+            ne_header.module_reference_table_offset as usize,
+        )?)?;
+        let imported_names = at(ne_data, ne_header.imported_name_table_offset as usize)?;
+        let mut module_references = Vec::with_capacity(module_offsets.len());
+        for module_offset in module_offsets {
+            let (_, name) = context("NE imported name", pascal_string)(at(
+                imported_names,
+                module_offset as usize,
+            )?)?;
+            module_references.push(name);
+        }
+
+        let (_, entry_data) = context("NE entry table", take(ne_header.entry_table_size as usize))(
+            at(ne_data, ne_header.entry_table_offset as usize)?,
+        )?;
+        let (_, entry_points) = entry::entry_table(entry_data)?;
+
+        Ok((
+            &[],
+            Self {
+                file: input,
+                dos_header,
+                ne_header,
+                segments,
+                resource_table,
+                resident_names,
+                non_resident_names,
+                module_references,
+                entry_points,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    /// NE executable at 0x40 with two segments, at sectors 0x10 and 0x11 of `1 << shift`
+    /// bytes, and no resources, imports or entry points.
+    fn executable(alignment_shift: u16) -> Vec<u8> {
+        let mut file = vec![0u8; 0x120];
+        file[..2].copy_from_slice(b"MZ");
+        file[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+
+        let ne = 0x40;
+        let put_u16 = |file: &mut Vec<u8>, offset: usize, value: u16| {
+            file[ne + offset..ne + offset + 2].copy_from_slice(&value.to_le_bytes())
+        };
+        file[ne..ne + 2].copy_from_slice(b"NE");
+        put_u16(&mut file, 0x04, 0x58);
+        put_u16(&mut file, 0x1c, 2);
+        put_u16(&mut file, 0x22, 0x40);
+        put_u16(&mut file, 0x24, 0x50);
+        put_u16(&mut file, 0x26, 0x50);
+        put_u16(&mut file, 0x28, 0x58);
+        put_u16(&mut file, 0x2a, 0x58);
+        put_u16(&mut file, 0x32, alignment_shift);
+
+        // Segment table
+        put_u16(&mut file, 0x40, 0x10);
+        put_u16(&mut file, 0x42, 0x10);
+        put_u16(&mut file, 0x48, 0x11);
+
+        // Resident name table
+        file[ne + 0x50..ne + 0x55].copy_from_slice(b"\x04TEST");
+
+        file[0x100..0x110].copy_from_slice(b"segment one data");
+        file
+    }
+
+    #[test]
+    fn segment_offsets() {
+        let file = executable(4);
+        let (_, ne) = NeExecutable::parse::<Error>(&file).unwrap();
+        assert_eq!(ne.module_name(), Some(&b"TEST"[..]));
+        assert_eq!(ne.segments.len(), 2);
+        assert_eq!(
+            ne.segments[0].file_offset(ne.ne_header.alignment_shift()),
+            0x100
+        );
+        assert_eq!(ne.segments[0].file_size(), 0x10);
+        assert_eq!(ne.segment_data(1), Some(&b"segment one data"[..]));
+
+        // 64K segment, past the end of the file
+        assert_eq!(ne.segments[1].file_offset(4), 0x110);
+        assert_eq!(ne.segments[1].file_size(), 0x10000);
+        assert_eq!(ne.segment_data(2), None);
+        assert_eq!(ne.segment_data(0), None);
+        assert_eq!(ne.segment_data(3), None);
+    }
+
+    #[test]
+    fn default_alignment_shift() {
+        let file = executable(0);
+        let (_, ne) = NeExecutable::parse::<Error>(&file).unwrap();
+        assert_eq!(ne.ne_header.alignment_shift(), 9);
+        assert_eq!(ne.segment_data(1), None);
+    }
+
+    #[test]
+    fn invalid_alignment_shift() {
+        assert!(NeExecutable::parse::<Error>(&executable(31)).is_ok());
+        assert!(NeExecutable::parse::<Error>(&executable(32)).is_err());
+        assert!(NeExecutable::parse::<Error>(&executable(0xffff)).is_err());
+    }
+
+    #[test]
+    fn tables() {
+        let mut file = executable(4);
+        file.resize(0x300, 0);
+        let header = 0x40;
+        let mut put = |offset: usize, data: &[u8]| {
+            file[offset..offset + data.len()].copy_from_slice(data);
+        };
+
+        // Entry table, module references, non-resident names and resource table
+        put(header + 0x04, &0x170u16.to_le_bytes());
+        put(header + 0x06, &16u16.to_le_bytes());
+        put(header + 0x1e, &2u16.to_le_bytes());
+        put(header + 0x20, &0x20u16.to_le_bytes());
+        put(header + 0x24, &0x100u16.to_le_bytes());
+        put(header + 0x26, &0x140u16.to_le_bytes());
+        put(header + 0x28, &0x150u16.to_le_bytes());
+        put(header + 0x2a, &0x154u16.to_le_bytes());
+        put(header + 0x2c, &0x280u32.to_le_bytes());
+
+        put(header + 0x100, &[4, 0, 0x03, 0x80, 1, 0, 0, 0, 0, 0]);
+        put(
+            header + 0x10a,
+            &[0x10, 0, 1, 0, 0x30, 0x1c, 0x01, 0x80, 0, 0, 0, 0, 0, 0],
+        );
+        put(header + 0x140, b"\x04TEST\0\0\x04Func\x01\0\0");
+        put(header + 0x150, &[1, 0, 8, 0]);
+        put(header + 0x154, b"\0\x06KERNEL\x04USER");
+        put(
+            header + 0x170,
+            &[
+                1, 0x01, 0x01, 0x10, 0x00, 1, 0x00, 1, 0xff, 0x01, 0xcd, 0x3f, 0x02, 0x20, 0x00, 0,
+            ],
+        );
+        put(0x280, b"\x0bDescription\0\0\x05Other\x03\0\0");
+
+        let (_, ne) = NeExecutable::parse::<Error>(&file).unwrap();
+        let resource_table = ne.resource_table.as_ref().unwrap();
+        assert_eq!(resource_table.types.len(), 1);
+        let resource = &resource_table.types[0].resources[0];
+        assert_eq!(resource.data(&file), Some(&b"segment one data"[..]));
+
+        assert_eq!(ne.module_name(), Some(&b"TEST"[..]));
+        assert_eq!(ne.resident_names.len(), 2);
+        assert_eq!(ne.description(), Some(&b"Description"[..]));
+        assert_eq!(ne.non_resident_names.len(), 2);
+        assert_eq!(ne.module_references, [&b"KERNEL"[..], &b"USER"[..]]);
+
+        assert_eq!(ne.entry_points.len(), 2);
+        let exports: Vec<_> = ne
+            .exports()
+            .map(|(name, entry)| (name, entry.ordinal, entry.segment, entry.offset))
+            .collect();
+        assert_eq!(
+            exports,
+            [(&b"Func"[..], 1, 1, 0x10), (&b"Other"[..], 3, 2, 0x20)]
+        );
+        assert!(ne.entry_point(2).is_none());
+
+        // Module reference past the end of the imported names
+        file[header + 0x152..header + 0x154].copy_from_slice(&0x1000u16.to_le_bytes());
+        assert!(NeExecutable::parse::<Error>(&file).is_err());
+    }
+}
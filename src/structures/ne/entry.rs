@@ -0,0 +1,183 @@
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_u16, le_u8};
+use nom::sequence::tuple;
+
+use crate::NomError;
+
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NeEntryPoint {
+    pub ordinal: u16,
+    /// Segment number, or 0xfe when `offset` is a constant rather than an address.
+    pub segment: u8,
+    pub offset: u16,
+    pub flags: u8,
+    pub movable: bool,
+}
+
+impl NeEntryPoint {
+    pub fn is_exported(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Entry uses the shared data segment of the module.
+    pub fn is_shared_data(&self) -> bool {
+        self.flags & 0x02 != 0
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.segment == 0xfe
+    }
+}
+
+impl fmt::Display for NeEntryPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}@{}: {:02x}:{:04x}{}{}\n",
+            self.ordinal,
+            self.segment,
+            self.offset,
+            if self.movable { " movable" } else { "" },
+            if self.is_exported() { " exported" } else { "" }
+        )
+    }
+}
+
+/// Parses the bundles of the entry table, unused ordinals being skipped.
+pub(super) fn entry_table<'a, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Vec<NeEntryPoint>, E>
+where
+    E: NomError<'a>,
+{
+    let mut entries = Vec::new();
+    let mut ordinal = 1u16;
+    let mut rest = input;
+
+    // The terminating bundle is not always accounted for in the table size.
+    while !rest.is_empty() {
+        let (r, entry_count) = context("NE entry bundle", le_u8)(rest)?;
+        if entry_count == 0 {
+            return Ok((r, entries));
+        }
+        let (r, indicator) = context("NE entry bundle", le_u8)(r)?;
+
+        rest = match indicator {
+            0x00 => r,
+            0xff => {
+                let (r, bundle) = context(
+                    "NE movable entries",
+                    count(tuple((le_u8, le_u16, le_u8, le_u16)), entry_count as usize),
+                )(r)?;
+                for (idx, (flags, _int_3f, segment, offset)) in bundle.into_iter().enumerate() {
+                    entries.push(NeEntryPoint {
+                        ordinal: ordinal.wrapping_add(idx as u16),
+                        segment,
+                        offset,
+                        flags,
+                        movable: true,
+                    });
+                }
+                r
+            }
+            segment => {
+                let (r, bundle) = context(
+                    "NE fixed entries",
+                    count(tuple((le_u8, le_u16)), entry_count as usize),
+                )(r)?;
+                for (idx, (flags, offset)) in bundle.into_iter().enumerate() {
+                    entries.push(NeEntryPoint {
+                        ordinal: ordinal.wrapping_add(idx as u16),
+                        segment,
+                        offset,
+                        flags,
+                        movable: false,
+                    });
+                }
+                r
+            }
+        };
+        ordinal = ordinal.wrapping_add(entry_count as u16);
+    }
+
+    Ok((rest, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn entries(data: &[u8]) -> Vec<(u16, u8, u16, u8, bool)> {
+        let (_, entries) = entry_table::<Error>(data).unwrap();
+        entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.ordinal,
+                    entry.segment,
+                    entry.offset,
+                    entry.flags,
+                    entry.movable,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bundles() {
+        let data = [
+            // Two fixed entries in segment 1
+            &[2, 0x01, 0x01, 0x10, 0x00, 0x03, 0x20, 0x00][..],
+            // Two unused ordinals
+            &[2, 0x00],
+            // A movable entry in segment 2, and a constant
+            &[2, 0xff, 0x01, 0xcd, 0x3f, 0x02, 0x30, 0x00],
+            &[0x00, 0xcd, 0x3f, 0xfe, 0x34, 0x12],
+            &[0],
+            // Past the end of the table
+            &[1, 0x01, 0x01, 0x40, 0x00],
+        ]
+        .concat();
+        assert_eq!(
+            entries(&data),
+            [
+                (1, 1, 0x10, 1, false),
+                (2, 1, 0x20, 3, false),
+                (5, 2, 0x30, 1, true),
+                (6, 0xfe, 0x1234, 0, true),
+            ]
+        );
+
+        let (rest, entries) = entry_table::<Error>(&data).unwrap();
+        assert_eq!(rest, &data[data.len() - 5..]);
+        assert!(entries[0].is_exported());
+        assert!(entries[1].is_shared_data());
+        assert!(!entries[2].is_constant());
+        assert!(entries[3].is_constant());
+    }
+
+    #[test]
+    fn unterminated() {
+        assert_eq!(
+            entries(&[1, 0x01, 0x01, 0x10, 0x00]),
+            [(1, 1, 0x10, 1, false)]
+        );
+        assert!(entry_table::<Error>(&[2, 0x01, 0x01, 0x10, 0x00]).is_err());
+        assert!(entry_table::<Error>(&[1]).is_err());
+    }
+
+    #[test]
+    fn ordinal_overflow() {
+        // Unused bundles up to ordinal 0xffff, then entries wrapping around
+        let mut data = [255, 0x00].repeat(256);
+        data.extend_from_slice(&[254, 0x00]);
+        data.extend_from_slice(&[2, 0x01, 0x01, 0x10, 0x00, 0x01, 0x20, 0x00]);
+        data.extend_from_slice(&[1, 0x01, 0x01, 0x30, 0x00, 0]);
+        let ordinals: Vec<_> = entries(&data).iter().map(|entry| entry.0).collect();
+        assert_eq!(ordinals, [0xffff, 0, 1]);
+    }
+}
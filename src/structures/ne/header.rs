@@ -0,0 +1,232 @@
+use nom::bytes::complete::tag;
+use nom::combinator::verify;
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// `IMAGE_OS2_HEADER`, found at `e_lfanew`. Table offsets are relative to the header, except
+/// for the non-resident name table which is a file offset.
+#[derive(Debug)]
+pub struct NeHeader {
+    pub linker_version: u8,
+    pub linker_revision: u8,
+    pub entry_table_offset: u16,
+    pub entry_table_size: u16,
+    pub crc: u32,
+    pub flags: u16,
+    pub auto_data_segment: u16,
+    pub heap_size: u16,
+    pub stack_size: u16,
+    pub cs_ip: u32,
+    pub ss_sp: u32,
+    pub segment_count: u16,
+    pub module_reference_count: u16,
+    pub non_resident_name_table_size: u16,
+    pub segment_table_offset: u16,
+    pub resource_table_offset: u16,
+    pub resident_name_table_offset: u16,
+    pub module_reference_table_offset: u16,
+    pub imported_name_table_offset: u16,
+    pub non_resident_name_table_offset: u32,
+    pub movable_entry_count: u16,
+    pub alignment_shift: u16,
+    pub resource_segment_count: u16,
+    pub target_os: u8,
+    pub other_flags: u8,
+    pub fast_load_offset: u16,
+    pub fast_load_size: u16,
+    pub min_code_swap_size: u16,
+    pub expected_windows_version: u16,
+}
+
+impl NeHeader {
+    pub const fn size() -> usize {
+        64
+    }
+
+    /// Library module: instances share a single data segment and there is no stack.
+    pub fn is_dll(&self) -> bool {
+        self.flags & 0x8000 != 0
+    }
+
+    /// Shift applied to the sector numbers of the segment table, 0 meaning the default of 9.
+    pub fn alignment_shift(&self) -> u16 {
+        if self.alignment_shift == 0 {
+            9
+        } else {
+            self.alignment_shift
+        }
+    }
+}
+
+impl fmt::Display for NeHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}linker_version: {}.{}\n",
+            self.linker_version, self.linker_revision
+        )?;
+        write!(f, "{offset}crc: 0x{:x}\n", self.crc)?;
+        write!(f, "{offset}flags: 0x{:04x}\n", self.flags)?;
+        write!(f, "{offset}auto_data_segment: {}\n", self.auto_data_segment)?;
+        write!(f, "{offset}heap_size: 0x{:x}\n", self.heap_size)?;
+        write!(f, "{offset}stack_size: 0x{:x}\n", self.stack_size)?;
+        write!(
+            f,
+            "{offset}cs:ip: {:04x}:{:04x}\n",
+            self.cs_ip >> 16,
+            self.cs_ip & 0xffff
+        )?;
+        write!(
+            f,
+            "{offset}ss:sp: {:04x}:{:04x}\n",
+            self.ss_sp >> 16,
+            self.ss_sp & 0xffff
+        )?;
+        write!(f, "{offset}segment_count: {}\n", self.segment_count)?;
+        write!(
+            f,
+            "{offset}module_reference_count: {}\n",
+            self.module_reference_count
+        )?;
+        write!(
+            f,
+            "{offset}movable_entry_count: {}\n",
+            self.movable_entry_count
+        )?;
+        write!(f, "{offset}alignment_shift: {}\n", self.alignment_shift())?;
+        write!(f, "{offset}target_os: 0x{:x}\n", self.target_os)?;
+        write!(
+            f,
+            "{offset}expected_windows_version: {}.{}\n",
+            self.expected_windows_version >> 8,
+            self.expected_windows_version & 0xff
+        )
+    }
+}
+
+impl<'a> Parse<'a> for NeHeader {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            rest,
+            (
+                _,
+                linker_version,
+                linker_revision,
+                entry_table_offset,
+                entry_table_size,
+                crc,
+                flags,
+                auto_data_segment,
+                heap_size,
+                stack_size,
+                cs_ip,
+                ss_sp,
+                segment_count,
+                module_reference_count,
+                non_resident_name_table_size,
+                segment_table_offset,
+                resource_table_offset,
+            ),
+        ) = context(
+            "NE header",
+            tuple((
+                tag(b"NE"),
+                le_u8,
+                le_u8,
+                le_u16,
+                le_u16,
+                le_u32,
+                le_u16,
+                le_u16,
+                le_u16,
+                le_u16,
+                le_u32,
+                le_u32,
+                le_u16,
+                le_u16,
+                le_u16,
+                le_u16,
+                le_u16,
+            )),
+        )(input)?;
+        let (
+            rest,
+            (
+                resident_name_table_offset,
+                module_reference_table_offset,
+                imported_name_table_offset,
+                non_resident_name_table_offset,
+                movable_entry_count,
+                alignment_shift,
+                resource_segment_count,
+                target_os,
+                other_flags,
+                fast_load_offset,
+                fast_load_size,
+                min_code_swap_size,
+                expected_windows_version,
+            ),
+        ) = context(
+            "NE header",
+            tuple((
+                le_u16,
+                le_u16,
+                le_u16,
+                le_u32,
+                le_u16,
+                verify(le_u16, |shift| *shift < 32),
+                le_u16,
+                le_u8,
+                le_u8,
+                le_u16,
+                le_u16,
+                le_u16,
+                le_u16,
+            )),
+        )(rest)?;
+
+        Ok((
+            rest,
+            Self {
+                linker_version,
+                linker_revision,
+                entry_table_offset,
+                entry_table_size,
+                crc,
+                flags,
+                auto_data_segment,
+                heap_size,
+                stack_size,
+                cs_ip,
+                ss_sp,
+                segment_count,
+                module_reference_count,
+                non_resident_name_table_size,
+                segment_table_offset,
+                resource_table_offset,
+                resident_name_table_offset,
+                module_reference_table_offset,
+                imported_name_table_offset,
+                non_resident_name_table_offset,
+                movable_entry_count,
+                alignment_shift,
+                resource_segment_count,
+                target_os,
+                other_flags,
+                fast_load_offset,
+                fast_load_size,
+                min_code_swap_size,
+                expected_windows_version,
+            },
+        ))
+    }
+}
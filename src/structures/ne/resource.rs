@@ -0,0 +1,249 @@
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_u16, le_u32};
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use super::pascal_string;
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum NeResourceId<'a> {
+    Integer(u16),
+    Name(&'a [u8]),
+}
+
+impl<'a> NeResourceId<'a> {
+    /// Resolves an id word, names being stored at an offset from the start of `table`.
+    fn resolve<E>(table: &'a [u8], id: u16) -> Result<Self, nom::Err<E>>
+    where
+        E: NomError<'a>,
+    {
+        if id & 0x8000 != 0 {
+            return Ok(Self::Integer(id & 0x7fff));
+        }
+        let (name, _) = context("NE resource name", take(id as usize))(table)?;
+        let (_, name) = pascal_string(name)?;
+        Ok(Self::Name(name))
+    }
+
+    /// Name of the `RT_*` predefined type, when used as a type id.
+    pub fn type_name(&self) -> Option<&'static str> {
+        let id = match self {
+            Self::Integer(id) => id,
+            Self::Name(_) => return None,
+        };
+        Some(match id {
+            1 => "RT_CURSOR",
+            2 => "RT_BITMAP",
+            3 => "RT_ICON",
+            4 => "RT_MENU",
+            5 => "RT_DIALOG",
+            6 => "RT_STRING",
+            7 => "RT_FONTDIR",
+            8 => "RT_FONT",
+            9 => "RT_ACCELERATOR",
+            10 => "RT_RCDATA",
+            11 => "RT_MESSAGETABLE",
+            12 => "RT_GROUP_CURSOR",
+            14 => "RT_GROUP_ICON",
+            16 => "RT_VERSION",
+            _ => return None,
+        })
+    }
+}
+
+impl<'a> fmt::Display for NeResourceId<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(id) => write!(f, "{}", id),
+            Self::Name(name) => write!(f, "{}", String::from_utf8_lossy(name)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct NeResource<'a> {
+    pub id: NeResourceId<'a>,
+    /// File offset of the resource data.
+    pub offset: usize,
+    pub length: usize,
+    pub flags: u16,
+}
+
+impl<'a> NeResource<'a> {
+    pub fn data(&self, file: &'a [u8]) -> Option<&'a [u8]> {
+        file.get(self.offset..)?.get(..self.length)
+    }
+}
+
+impl<'a> fmt::Display for NeResource<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}{}: offset: 0x{:x}, length: 0x{:x}, flags: 0x{:04x}\n",
+            self.id, self.offset, self.length, self.flags
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct NeResourceType<'a> {
+    pub type_id: NeResourceId<'a>,
+    pub resources: Vec<NeResource<'a>>,
+}
+
+impl<'a> fmt::Display for NeResourceType<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        match self.type_id.type_name() {
+            Some(type_name) => write!(f, "{offset}{}:\n", type_name)?,
+            None => write!(f, "{offset}{}:\n", self.type_id)?,
+        }
+        for resource in &self.resources {
+            write!(f, "{:width$}", resource)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct NeResourceTable<'a> {
+    pub alignment_shift: u16,
+    pub types: Vec<NeResourceType<'a>>,
+}
+
+impl<'a> fmt::Display for NeResourceTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default();
+        for resource_type in &self.types {
+            write!(f, "{:width$}", resource_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for NeResourceTable<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (mut rest, alignment_shift) =
+            context("NE resource alignment", verify(le_u16, |shift| *shift < 32))(input)?;
+
+        let mut types = Vec::new();
+        loop {
+            let (r, type_id) = context("NE resource type", le_u16)(rest)?;
+            if type_id == 0 {
+                rest = r;
+                break;
+            }
+            let (r, (resource_count, _reserved)) =
+                context("NE resource type", tuple((le_u16, le_u32)))(r)?;
+            let (r, entries) = context(
+                "NE resources",
+                count(
+                    tuple((le_u16, le_u16, le_u16, le_u16, le_u16, le_u16)),
+                    resource_count as usize,
+                ),
+            )(r)?;
+
+            let mut resources = Vec::with_capacity(entries.len());
+            for (offset, length, flags, id, _handle, _usage) in entries {
+                resources.push(NeResource {
+                    id: NeResourceId::resolve(input, id)?,
+                    offset: (offset as usize) << alignment_shift,
+                    length: (length as usize) << alignment_shift,
+                    flags,
+                });
+            }
+            types.push(NeResourceType {
+                type_id: NeResourceId::resolve(input, type_id)?,
+                resources,
+            });
+            rest = r;
+        }
+
+        Ok((
+            rest,
+            Self {
+                alignment_shift,
+                types,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    /// Resource table with an icon of id 1 and a `HELLO` resource of type `DATA`.
+    fn table(alignment_shift: u16) -> Vec<u8> {
+        let mut data = alignment_shift.to_le_bytes().to_vec();
+        for (type_id, offset, length, flags, id) in [
+            (0x8003u16, 0x10u16, 1u16, 0x1c30u16, 0x8001u16),
+            (0x2c, 0x11, 2, 0, 0x31),
+        ] {
+            data.extend_from_slice(&type_id.to_le_bytes());
+            data.extend_from_slice(&1u16.to_le_bytes());
+            data.extend_from_slice(&[0; 4]);
+            for word in [offset, length, flags, id, 0, 0] {
+                data.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(b"\x04DATA\x05HELLO");
+        data
+    }
+
+    #[test]
+    fn resources() {
+        let data = table(4);
+        let (rest, table) = NeResourceTable::parse::<Error>(&data).unwrap();
+        assert_eq!(rest, b"\x04DATA\x05HELLO");
+        assert_eq!(table.alignment_shift, 4);
+        assert_eq!(table.types.len(), 2);
+
+        let icons = &table.types[0];
+        assert_eq!(icons.type_id.type_name(), Some("RT_ICON"));
+        assert!(matches!(icons.resources[0].id, NeResourceId::Integer(1)));
+        assert_eq!(icons.resources[0].offset, 0x100);
+        assert_eq!(icons.resources[0].length, 0x10);
+        assert_eq!(icons.resources[0].flags, 0x1c30);
+
+        let data_type = &table.types[1];
+        assert!(matches!(data_type.type_id, NeResourceId::Name(b"DATA")));
+        assert_eq!(data_type.type_id.type_name(), None);
+        let hello = &data_type.resources[0];
+        assert!(matches!(hello.id, NeResourceId::Name(b"HELLO")));
+        assert_eq!(hello.offset, 0x110);
+        assert_eq!(hello.length, 0x20);
+
+        let file: Vec<u8> = (0..0x130).map(|idx| idx as u8).collect();
+        assert_eq!(hello.data(&file), Some(&file[0x110..]));
+        assert_eq!(icons.resources[0].data(&file[..0x108]), None);
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert!(NeResourceTable::parse::<Error>(&table(32)).is_err());
+
+        // Name past the end of the table
+        let mut data = table(4);
+        data[2 + 8 + 12..2 + 8 + 12 + 2].copy_from_slice(&0x100u16.to_le_bytes());
+        assert!(NeResourceTable::parse::<Error>(&data).is_err());
+
+        // Missing terminating type
+        let data = table(4);
+        assert!(NeResourceTable::parse::<Error>(&data[..2 + 20]).is_err());
+    }
+}
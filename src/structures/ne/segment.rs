@@ -0,0 +1,95 @@
+use nom::error::context;
+use nom::number::complete::le_u16;
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+#[derive(Debug)]
+pub struct NeSegment {
+    /// Position of the segment data in file, in sectors. 0 means there is no data.
+    pub sector: u16,
+    /// Size of the segment data in file, 0 meaning 64K when `sector` is not 0.
+    pub length: u16,
+    pub flags: u16,
+    /// Size allocated for the segment, 0 meaning 64K.
+    pub min_alloc: u16,
+}
+
+impl NeSegment {
+    pub fn is_data(&self) -> bool {
+        self.flags & 0x0001 != 0
+    }
+
+    pub fn is_movable(&self) -> bool {
+        self.flags & 0x0010 != 0
+    }
+
+    pub fn is_preload(&self) -> bool {
+        self.flags & 0x0040 != 0
+    }
+
+    /// Relocation records immediately follow the segment data.
+    pub fn has_relocations(&self) -> bool {
+        self.flags & 0x0100 != 0
+    }
+
+    pub fn is_discardable(&self) -> bool {
+        self.flags & 0x1000 != 0
+    }
+
+    pub fn file_offset(&self, alignment_shift: u16) -> usize {
+        (self.sector as usize) << alignment_shift
+    }
+
+    pub fn file_size(&self) -> usize {
+        match (self.sector, self.length) {
+            (0, _) => 0,
+            (_, 0) => 0x10000,
+            (_, length) => length as usize,
+        }
+    }
+
+    pub fn alloc_size(&self) -> usize {
+        match self.min_alloc {
+            0 => 0x10000,
+            min_alloc => min_alloc as usize,
+        }
+    }
+}
+
+impl fmt::Display for NeSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}{} sector: 0x{:x}, file_size: 0x{:x}, alloc_size: 0x{:x}, flags: 0x{:04x}\n",
+            if self.is_data() { "DATA" } else { "CODE" },
+            self.sector,
+            self.file_size(),
+            self.alloc_size(),
+            self.flags
+        )
+    }
+}
+
+impl<'a> Parse<'a> for NeSegment {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (sector, length, flags, min_alloc)) =
+            context("NE segment", tuple((le_u16, le_u16, le_u16, le_u16)))(input)?;
+
+        Ok((
+            rest,
+            Self {
+                sector,
+                length,
+                flags,
+                min_alloc,
+            },
+        ))
+    }
+}
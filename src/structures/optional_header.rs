@@ -1,5 +1,5 @@
 use nom::branch::alt;
-use nom::combinator::{map, verify};
+use nom::combinator::{map, peek, verify};
 use nom::error::context;
 use nom::multi::length_count;
 use nom::number::complete::{le_u16, le_u32, le_u64, le_u8};
@@ -216,7 +216,10 @@ impl<'a> Parse<'a> for OptionalHeader32 {
             tuple((
                 tuple((
                     verify(OptionalHeaderMagic::parse, |magic| {
-                        magic == &OptionalHeaderMagic::Header32
+                        matches!(
+                            magic,
+                            OptionalHeaderMagic::Header32 | OptionalHeaderMagic::Unknown(_)
+                        )
                     }),
                     le_u8,
                     le_u8,
@@ -510,7 +513,10 @@ impl<'a> Parse<'a> for OptionalHeader64 {
             tuple((
                 tuple((
                     verify(OptionalHeaderMagic::parse, |magic| {
-                        magic == &OptionalHeaderMagic::Header64
+                        matches!(
+                            magic,
+                            OptionalHeaderMagic::Header64 | OptionalHeaderMagic::Unknown(_)
+                        )
                     }),
                     le_u8,
                     le_u8,
@@ -627,6 +633,45 @@ impl OptionalHeader {
         }
     }
 
+    pub fn image_base(&self) -> u64 {
+        match self {
+            Self::I386(ref i386) => i386.image_base as u64,
+            Self::AMD64(ref amd64) => amd64.image_base,
+        }
+    }
+
+    pub fn check_sum(&self) -> u32 {
+        match self {
+            Self::I386(ref i386) => i386.check_sum,
+            Self::AMD64(ref amd64) => amd64.check_sum,
+        }
+    }
+
+    pub fn size_of_headers(&self) -> usize {
+        match self {
+            Self::I386(ref i386) => i386.size_of_headers as usize,
+            Self::AMD64(ref amd64) => amd64.size_of_headers as usize,
+        }
+    }
+
+    /// Parses the header in the layout of its magic, or of `size` for an unknown magic, `size`
+    /// being `size_of_optional_header` from the file header.
+    pub fn parse_sized<'a, E>(input: &'a [u8], size: usize) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (_, magic) = peek(OptionalHeaderMagic::parse)(input)?;
+        match magic {
+            OptionalHeaderMagic::Unknown(_) if size == OptionalHeader64::size() => {
+                context(
+                    "Optional header",
+                    map(OptionalHeader64::parse, |oh| Self::AMD64(oh)),
+                )(input)
+            }
+            _ => Self::parse(input),
+        }
+    }
+
     pub fn get_data_directory(&self, idx: ImageDataDirectoryIndex) -> Option<&DataDirectory> {
         let data_dir = match self {
             Self::I386(ref oh32) => oh32.data_directory.get(idx as usize)?,
@@ -1,3 +1,4 @@
+use nom::bytes::complete::take;
 use nom::combinator::verify;
 use nom::error::context;
 use nom::multi::count;
@@ -53,15 +54,20 @@ impl<'a> Parse<'a> for PeHeader<'a> {
     where
         E: NomError<'a>,
     {
-        let (rest, (signature, file_header, optional_header)) = context(
+        let (rest, (signature, file_header)) = context(
             "PE header",
             tuple((
                 verify(be_u32, |magic| &magic.to_be_bytes() == b"PE\0\0"),
                 FileHeader::parse,
-                OptionalHeader::parse,
             )),
         )(input)?;
-        if optional_header.size() != file_header.size_of_optional_header as usize {
+        let size_of_optional_header = file_header.size_of_optional_header as usize;
+        let (optional_header_rest, optional_header) = context("PE header", |input| {
+            OptionalHeader::parse_sized(input, size_of_optional_header)
+        })(rest)?;
+        // Fewer data directories make for a smaller optional header, sections always start
+        // `size_of_optional_header` bytes after its beginning.
+        if rest.len() - optional_header_rest.len() > size_of_optional_header {
             let e = E::from_error_kind(input, nom::error::ErrorKind::Verify);
             return Err(nom::Err::Failure(E::add_context(
                 input,
@@ -69,6 +75,7 @@ impl<'a> Parse<'a> for PeHeader<'a> {
                 e,
             )));
         }
+        let (rest, _) = take(size_of_optional_header)(rest)?;
         let (_rest, sections) = context(
             "PE header/sections",
             count(
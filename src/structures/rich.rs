@@ -0,0 +1,583 @@
+use md5::{Digest, Md5};
+use nom::error::context;
+use nom::number::complete::le_u32;
+use nom::sequence::tuple;
+
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+/// "Rich"
+const RICH_MAGIC: u32 = 0x6863_6952;
+
+/// "DanS"
+const DANS_MAGIC: u32 = 0x536e_6144;
+
+/// Known `@comp.id` product identifiers, with the Visual Studio release shipping them.
+const PRODUCTS: &[(u16, &str, Option<&str>)] = &[
+    (0x0000, "Unknown", None),
+    (0x0001, "Import0", None),
+    (0x0002, "Linker510", Some("Visual Studio 97")),
+    (0x0003, "Cvtomf510", Some("Visual Studio 97")),
+    (0x0004, "Linker600", Some("Visual Studio 6.0")),
+    (0x0005, "Cvtomf600", Some("Visual Studio 6.0")),
+    (0x0006, "Cvtres500", Some("Visual Studio 97")),
+    (0x0007, "Utc11_Basic", Some("Visual Studio 97")),
+    (0x0008, "Utc11_C", Some("Visual Studio 97")),
+    (0x0009, "Utc12_Basic", Some("Visual Studio 6.0")),
+    (0x000a, "Utc12_C", Some("Visual Studio 6.0")),
+    (0x000b, "Utc12_CPP", Some("Visual Studio 6.0")),
+    (0x000c, "AliasObj60", Some("Visual Studio 6.0")),
+    (0x000d, "VisualBasic60", Some("Visual Studio 6.0")),
+    (0x000e, "Masm613", Some("Visual Studio 6.0")),
+    (0x000f, "Masm710", Some("Visual Studio 2003")),
+    (0x0010, "Linker511", Some("Visual Studio 97")),
+    (0x0011, "Cvtomf511", Some("Visual Studio 97")),
+    (0x0012, "Masm614", Some("Visual Studio 6.0")),
+    (0x0013, "Linker512", Some("Visual Studio 97")),
+    (0x0014, "Cvtomf512", Some("Visual Studio 97")),
+    (0x0015, "Utc12_C_Std", Some("Visual Studio 6.0")),
+    (0x0016, "Utc12_CPP_Std", Some("Visual Studio 6.0")),
+    (0x0017, "Utc12_C_Book", Some("Visual Studio 6.0")),
+    (0x0018, "Utc12_CPP_Book", Some("Visual Studio 6.0")),
+    (0x0019, "Implib700", Some("Visual Studio 2002")),
+    (0x001a, "Cvtomf700", Some("Visual Studio 2002")),
+    (0x001b, "Utc13_Basic", Some("Visual Studio 2002")),
+    (0x001c, "Utc13_C", Some("Visual Studio 2002")),
+    (0x001d, "Utc13_CPP", Some("Visual Studio 2002")),
+    (0x001e, "Linker610", Some("Visual Studio 6.0")),
+    (0x001f, "Cvtomf610", Some("Visual Studio 6.0")),
+    (0x0020, "Linker601", Some("Visual Studio 6.0")),
+    (0x0021, "Cvtomf601", Some("Visual Studio 6.0")),
+    (0x0022, "Utc12_1_Basic", Some("Visual Studio 6.0")),
+    (0x0023, "Utc12_1_C", Some("Visual Studio 6.0")),
+    (0x0024, "Utc12_1_CPP", Some("Visual Studio 6.0")),
+    (0x0025, "Linker620", Some("Visual Studio 6.0")),
+    (0x0026, "Cvtomf620", Some("Visual Studio 6.0")),
+    (0x0027, "AliasObj70", Some("Visual Studio 2002")),
+    (0x0028, "Linker621", Some("Visual Studio 6.0")),
+    (0x0029, "Cvtomf621", Some("Visual Studio 6.0")),
+    (0x002a, "Masm615", Some("Visual Studio 6.0")),
+    (0x002b, "Utc13_LTCG_C", Some("Visual Studio 2002")),
+    (0x002c, "Utc13_LTCG_CPP", Some("Visual Studio 2002")),
+    (0x002d, "Masm620", Some("Visual Studio 6.0")),
+    (0x002e, "ILAsm100", None),
+    (0x002f, "Utc12_2_Basic", Some("Visual Studio 6.0")),
+    (0x0030, "Utc12_2_C", Some("Visual Studio 6.0")),
+    (0x0031, "Utc12_2_CPP", Some("Visual Studio 6.0")),
+    (0x0032, "Utc12_2_C_Std", Some("Visual Studio 6.0")),
+    (0x0033, "Utc12_2_CPP_Std", Some("Visual Studio 6.0")),
+    (0x0034, "Utc12_2_C_Book", Some("Visual Studio 6.0")),
+    (0x0035, "Utc12_2_CPP_Book", Some("Visual Studio 6.0")),
+    (0x0036, "Implib622", Some("Visual Studio 6.0")),
+    (0x0037, "Cvtomf622", Some("Visual Studio 6.0")),
+    (0x0038, "Cvtres501", Some("Visual Studio 97")),
+    (0x0039, "Utc13_C_Std", Some("Visual Studio 2002")),
+    (0x003a, "Utc13_CPP_Std", Some("Visual Studio 2002")),
+    (0x003b, "Cvtpgd1300", Some("Visual Studio 2002")),
+    (0x003c, "Linker622", Some("Visual Studio 6.0")),
+    (0x003d, "Linker700", Some("Visual Studio 2002")),
+    (0x003e, "Export622", Some("Visual Studio 6.0")),
+    (0x003f, "Export700", Some("Visual Studio 2002")),
+    (0x0040, "Masm700", Some("Visual Studio 2002")),
+    (0x0041, "Utc13_POGO_I_C", Some("Visual Studio 2002")),
+    (0x0042, "Utc13_POGO_I_CPP", Some("Visual Studio 2002")),
+    (0x0043, "Utc13_POGO_O_C", Some("Visual Studio 2002")),
+    (0x0044, "Utc13_POGO_O_CPP", Some("Visual Studio 2002")),
+    (0x0045, "Cvtres700", Some("Visual Studio 2002")),
+    (0x0046, "Cvtres710p", Some("Visual Studio 2003")),
+    (0x0047, "Linker710p", Some("Visual Studio 2003")),
+    (0x0048, "Cvtomf710p", Some("Visual Studio 2003")),
+    (0x0049, "Export710p", Some("Visual Studio 2003")),
+    (0x004a, "Implib710p", Some("Visual Studio 2003")),
+    (0x004b, "Masm710p", Some("Visual Studio 2003")),
+    (0x004c, "Utc1310p_C", Some("Visual Studio 2003")),
+    (0x004d, "Utc1310p_CPP", Some("Visual Studio 2003")),
+    (0x004e, "Utc1310p_C_Std", Some("Visual Studio 2003")),
+    (0x004f, "Utc1310p_CPP_Std", Some("Visual Studio 2003")),
+    (0x0050, "Utc1310p_LTCG_C", Some("Visual Studio 2003")),
+    (0x0051, "Utc1310p_LTCG_CPP", Some("Visual Studio 2003")),
+    (0x0052, "Utc1310p_POGO_I_C", Some("Visual Studio 2003")),
+    (0x0053, "Utc1310p_POGO_I_CPP", Some("Visual Studio 2003")),
+    (0x0054, "Utc1310p_POGO_O_C", Some("Visual Studio 2003")),
+    (0x0055, "Utc1310p_POGO_O_CPP", Some("Visual Studio 2003")),
+    (0x0056, "Linker624", Some("Visual Studio 6.0")),
+    (0x0057, "Cvtomf624", Some("Visual Studio 6.0")),
+    (0x0058, "Export624", Some("Visual Studio 6.0")),
+    (0x0059, "Implib624", Some("Visual Studio 6.0")),
+    (0x005a, "Linker710", Some("Visual Studio 2003")),
+    (0x005b, "Cvtomf710", Some("Visual Studio 2003")),
+    (0x005c, "Export710", Some("Visual Studio 2003")),
+    (0x005d, "Implib710", Some("Visual Studio 2003")),
+    (0x005e, "Cvtres710", Some("Visual Studio 2003")),
+    (0x005f, "Utc1310_C", Some("Visual Studio 2003")),
+    (0x0060, "Utc1310_CPP", Some("Visual Studio 2003")),
+    (0x0061, "Utc1310_C_Std", Some("Visual Studio 2003")),
+    (0x0062, "Utc1310_CPP_Std", Some("Visual Studio 2003")),
+    (0x0063, "Utc1310_LTCG_C", Some("Visual Studio 2003")),
+    (0x0064, "Utc1310_LTCG_CPP", Some("Visual Studio 2003")),
+    (0x0065, "Utc1310_POGO_I_C", Some("Visual Studio 2003")),
+    (0x0066, "Utc1310_POGO_I_CPP", Some("Visual Studio 2003")),
+    (0x0067, "Utc1310_POGO_O_C", Some("Visual Studio 2003")),
+    (0x0068, "Utc1310_POGO_O_CPP", Some("Visual Studio 2003")),
+    (0x0069, "AliasObj710", Some("Visual Studio 2003")),
+    (0x006a, "AliasObj710p", Some("Visual Studio 2003")),
+    (0x006b, "Cvtpgd1310", Some("Visual Studio 2003")),
+    (0x006c, "Cvtpgd1310p", Some("Visual Studio 2003")),
+    (0x006d, "Utc1400_C", Some("Visual Studio 2005")),
+    (0x006e, "Utc1400_CPP", Some("Visual Studio 2005")),
+    (0x006f, "Utc1400_C_Std", Some("Visual Studio 2005")),
+    (0x0070, "Utc1400_CPP_Std", Some("Visual Studio 2005")),
+    (0x0071, "Utc1400_LTCG_C", Some("Visual Studio 2005")),
+    (0x0072, "Utc1400_LTCG_CPP", Some("Visual Studio 2005")),
+    (0x0073, "Utc1400_POGO_I_C", Some("Visual Studio 2005")),
+    (0x0074, "Utc1400_POGO_I_CPP", Some("Visual Studio 2005")),
+    (0x0075, "Utc1400_POGO_O_C", Some("Visual Studio 2005")),
+    (0x0076, "Utc1400_POGO_O_CPP", Some("Visual Studio 2005")),
+    (0x0077, "Cvtpgd1400", Some("Visual Studio 2005")),
+    (0x0078, "Linker800", Some("Visual Studio 2005")),
+    (0x0079, "Cvtomf800", Some("Visual Studio 2005")),
+    (0x007a, "Export800", Some("Visual Studio 2005")),
+    (0x007b, "Implib800", Some("Visual Studio 2005")),
+    (0x007c, "Cvtres800", Some("Visual Studio 2005")),
+    (0x007d, "Masm800", Some("Visual Studio 2005")),
+    (0x007e, "AliasObj800", Some("Visual Studio 2005")),
+    (0x007f, "PhoenixPrerelease", None),
+    (0x0080, "Utc1400_CVTCIL_C", Some("Visual Studio 2005")),
+    (0x0081, "Utc1400_CVTCIL_CPP", Some("Visual Studio 2005")),
+    (0x0082, "Utc1400_LTCG_MSIL", Some("Visual Studio 2005")),
+    (0x0083, "Utc1500_C", Some("Visual Studio 2008")),
+    (0x0084, "Utc1500_CPP", Some("Visual Studio 2008")),
+    (0x0085, "Utc1500_C_Std", Some("Visual Studio 2008")),
+    (0x0086, "Utc1500_CPP_Std", Some("Visual Studio 2008")),
+    (0x0087, "Utc1500_CVTCIL_C", Some("Visual Studio 2008")),
+    (0x0088, "Utc1500_CVTCIL_CPP", Some("Visual Studio 2008")),
+    (0x0089, "Utc1500_LTCG_C", Some("Visual Studio 2008")),
+    (0x008a, "Utc1500_LTCG_CPP", Some("Visual Studio 2008")),
+    (0x008b, "Utc1500_LTCG_MSIL", Some("Visual Studio 2008")),
+    (0x008c, "Utc1500_POGO_I_C", Some("Visual Studio 2008")),
+    (0x008d, "Utc1500_POGO_I_CPP", Some("Visual Studio 2008")),
+    (0x008e, "Utc1500_POGO_O_C", Some("Visual Studio 2008")),
+    (0x008f, "Utc1500_POGO_O_CPP", Some("Visual Studio 2008")),
+    (0x0090, "Cvtpgd1500", Some("Visual Studio 2008")),
+    (0x0091, "Linker900", Some("Visual Studio 2008")),
+    (0x0092, "Export900", Some("Visual Studio 2008")),
+    (0x0093, "Implib900", Some("Visual Studio 2008")),
+    (0x0094, "Cvtres900", Some("Visual Studio 2008")),
+    (0x0095, "Masm900", Some("Visual Studio 2008")),
+    (0x0096, "AliasObj900", Some("Visual Studio 2008")),
+    (0x0097, "Resource", None),
+    (0x0098, "AliasObj1000", Some("Visual Studio 2010")),
+    (0x0099, "Cvtpgd1600", Some("Visual Studio 2010")),
+    (0x009a, "Cvtres1000", Some("Visual Studio 2010")),
+    (0x009b, "Export1000", Some("Visual Studio 2010")),
+    (0x009c, "Implib1000", Some("Visual Studio 2010")),
+    (0x009d, "Linker1000", Some("Visual Studio 2010")),
+    (0x009e, "Masm1000", Some("Visual Studio 2010")),
+    (0x009f, "Phx1600_C", Some("Visual Studio 2010")),
+    (0x00a0, "Phx1600_CPP", Some("Visual Studio 2010")),
+    (0x00a1, "Phx1600_CVTCIL_C", Some("Visual Studio 2010")),
+    (0x00a2, "Phx1600_CVTCIL_CPP", Some("Visual Studio 2010")),
+    (0x00a3, "Phx1600_LTCG_C", Some("Visual Studio 2010")),
+    (0x00a4, "Phx1600_LTCG_CPP", Some("Visual Studio 2010")),
+    (0x00a5, "Phx1600_LTCG_MSIL", Some("Visual Studio 2010")),
+    (0x00a6, "Phx1600_POGO_I_C", Some("Visual Studio 2010")),
+    (0x00a7, "Phx1600_POGO_I_CPP", Some("Visual Studio 2010")),
+    (0x00a8, "Phx1600_POGO_O_C", Some("Visual Studio 2010")),
+    (0x00a9, "Phx1600_POGO_O_CPP", Some("Visual Studio 2010")),
+    (0x00aa, "Utc1600_C", Some("Visual Studio 2010")),
+    (0x00ab, "Utc1600_CPP", Some("Visual Studio 2010")),
+    (0x00ac, "Utc1600_CVTCIL_C", Some("Visual Studio 2010")),
+    (0x00ad, "Utc1600_CVTCIL_CPP", Some("Visual Studio 2010")),
+    (0x00ae, "Utc1600_LTCG_C", Some("Visual Studio 2010")),
+    (0x00af, "Utc1600_LTCG_CPP", Some("Visual Studio 2010")),
+    (0x00b0, "Utc1600_LTCG_MSIL", Some("Visual Studio 2010")),
+    (0x00b1, "Utc1600_POGO_I_C", Some("Visual Studio 2010")),
+    (0x00b2, "Utc1600_POGO_I_CPP", Some("Visual Studio 2010")),
+    (0x00b3, "Utc1600_POGO_O_C", Some("Visual Studio 2010")),
+    (0x00b4, "Utc1600_POGO_O_CPP", Some("Visual Studio 2010")),
+    (0x00b5, "AliasObj1010", Some("Visual Studio 2010 SP1")),
+    (0x00b6, "Cvtpgd1610", Some("Visual Studio 2010 SP1")),
+    (0x00b7, "Cvtres1010", Some("Visual Studio 2010 SP1")),
+    (0x00b8, "Export1010", Some("Visual Studio 2010 SP1")),
+    (0x00b9, "Implib1010", Some("Visual Studio 2010 SP1")),
+    (0x00ba, "Linker1010", Some("Visual Studio 2010 SP1")),
+    (0x00bb, "Masm1010", Some("Visual Studio 2010 SP1")),
+    (0x00bc, "Utc1610_C", Some("Visual Studio 2010 SP1")),
+    (0x00bd, "Utc1610_CPP", Some("Visual Studio 2010 SP1")),
+    (0x00be, "Utc1610_CVTCIL_C", Some("Visual Studio 2010 SP1")),
+    (0x00bf, "Utc1610_CVTCIL_CPP", Some("Visual Studio 2010 SP1")),
+    (0x00c0, "Utc1610_LTCG_C", Some("Visual Studio 2010 SP1")),
+    (0x00c1, "Utc1610_LTCG_CPP", Some("Visual Studio 2010 SP1")),
+    (0x00c2, "Utc1610_LTCG_MSIL", Some("Visual Studio 2010 SP1")),
+    (0x00c3, "Utc1610_POGO_I_C", Some("Visual Studio 2010 SP1")),
+    (0x00c4, "Utc1610_POGO_I_CPP", Some("Visual Studio 2010 SP1")),
+    (0x00c5, "Utc1610_POGO_O_C", Some("Visual Studio 2010 SP1")),
+    (0x00c6, "Utc1610_POGO_O_CPP", Some("Visual Studio 2010 SP1")),
+    (0x00c7, "AliasObj1100", Some("Visual Studio 2012")),
+    (0x00c8, "Cvtpgd1700", Some("Visual Studio 2012")),
+    (0x00c9, "Cvtres1100", Some("Visual Studio 2012")),
+    (0x00ca, "Export1100", Some("Visual Studio 2012")),
+    (0x00cb, "Implib1100", Some("Visual Studio 2012")),
+    (0x00cc, "Linker1100", Some("Visual Studio 2012")),
+    (0x00cd, "Masm1100", Some("Visual Studio 2012")),
+    (0x00ce, "Utc1700_C", Some("Visual Studio 2012")),
+    (0x00cf, "Utc1700_CPP", Some("Visual Studio 2012")),
+    (0x00d0, "Utc1700_CVTCIL_C", Some("Visual Studio 2012")),
+    (0x00d1, "Utc1700_CVTCIL_CPP", Some("Visual Studio 2012")),
+    (0x00d2, "Utc1700_LTCG_C", Some("Visual Studio 2012")),
+    (0x00d3, "Utc1700_LTCG_CPP", Some("Visual Studio 2012")),
+    (0x00d4, "Utc1700_LTCG_MSIL", Some("Visual Studio 2012")),
+    (0x00d5, "Utc1700_POGO_I_C", Some("Visual Studio 2012")),
+    (0x00d6, "Utc1700_POGO_I_CPP", Some("Visual Studio 2012")),
+    (0x00d7, "Utc1700_POGO_O_C", Some("Visual Studio 2012")),
+    (0x00d8, "Utc1700_POGO_O_CPP", Some("Visual Studio 2012")),
+    (0x00d9, "AliasObj1200", Some("Visual Studio 2013")),
+    (0x00da, "Cvtpgd1800", Some("Visual Studio 2013")),
+    (0x00db, "Cvtres1200", Some("Visual Studio 2013")),
+    (0x00dc, "Export1200", Some("Visual Studio 2013")),
+    (0x00dd, "Implib1200", Some("Visual Studio 2013")),
+    (0x00de, "Linker1200", Some("Visual Studio 2013")),
+    (0x00df, "Masm1200", Some("Visual Studio 2013")),
+    (0x00e0, "Utc1800_C", Some("Visual Studio 2013")),
+    (0x00e1, "Utc1800_CPP", Some("Visual Studio 2013")),
+    (0x00e2, "Utc1800_CVTCIL_C", Some("Visual Studio 2013")),
+    (0x00e3, "Utc1800_CVTCIL_CPP", Some("Visual Studio 2013")),
+    (0x00e4, "Utc1800_LTCG_C", Some("Visual Studio 2013")),
+    (0x00e5, "Utc1800_LTCG_CPP", Some("Visual Studio 2013")),
+    (0x00e6, "Utc1800_LTCG_MSIL", Some("Visual Studio 2013")),
+    (0x00e7, "Utc1800_POGO_I_C", Some("Visual Studio 2013")),
+    (0x00e8, "Utc1800_POGO_I_CPP", Some("Visual Studio 2013")),
+    (0x00e9, "Utc1800_POGO_O_C", Some("Visual Studio 2013")),
+    (0x00ea, "Utc1800_POGO_O_CPP", Some("Visual Studio 2013")),
+    (0x00eb, "AliasObj1210", Some("Visual Studio 2013")),
+    (0x00ec, "Cvtpgd1810", Some("Visual Studio 2013")),
+    (0x00ed, "Cvtres1210", Some("Visual Studio 2013")),
+    (0x00ee, "Export1210", Some("Visual Studio 2013")),
+    (0x00ef, "Implib1210", Some("Visual Studio 2013")),
+    (0x00f0, "Linker1210", Some("Visual Studio 2013")),
+    (0x00f1, "Masm1210", Some("Visual Studio 2013")),
+    (0x00f2, "Utc1810_C", Some("Visual Studio 2013")),
+    (0x00f3, "Utc1810_CPP", Some("Visual Studio 2013")),
+    (0x00f4, "Utc1810_CVTCIL_C", Some("Visual Studio 2013")),
+    (0x00f5, "Utc1810_CVTCIL_CPP", Some("Visual Studio 2013")),
+    (0x00f6, "Utc1810_LTCG_C", Some("Visual Studio 2013")),
+    (0x00f7, "Utc1810_LTCG_CPP", Some("Visual Studio 2013")),
+    (0x00f8, "Utc1810_LTCG_MSIL", Some("Visual Studio 2013")),
+    (0x00f9, "Utc1810_POGO_I_C", Some("Visual Studio 2013")),
+    (0x00fa, "Utc1810_POGO_I_CPP", Some("Visual Studio 2013")),
+    (0x00fb, "Utc1810_POGO_O_C", Some("Visual Studio 2013")),
+    (0x00fc, "Utc1810_POGO_O_CPP", Some("Visual Studio 2013")),
+    (0x00fd, "AliasObj1400", Some("Visual Studio 2015+")),
+    (0x00fe, "Cvtpgd1900", Some("Visual Studio 2015+")),
+    (0x00ff, "Cvtres1400", Some("Visual Studio 2015+")),
+    (0x0100, "Export1400", Some("Visual Studio 2015+")),
+    (0x0101, "Implib1400", Some("Visual Studio 2015+")),
+    (0x0102, "Linker1400", Some("Visual Studio 2015+")),
+    (0x0103, "Masm1400", Some("Visual Studio 2015+")),
+    (0x0104, "Utc1900_C", Some("Visual Studio 2015+")),
+    (0x0105, "Utc1900_CPP", Some("Visual Studio 2015+")),
+    (0x0106, "Utc1900_CVTCIL_C", Some("Visual Studio 2015+")),
+    (0x0107, "Utc1900_CVTCIL_CPP", Some("Visual Studio 2015+")),
+    (0x0108, "Utc1900_LTCG_C", Some("Visual Studio 2015+")),
+    (0x0109, "Utc1900_LTCG_CPP", Some("Visual Studio 2015+")),
+    (0x010a, "Utc1900_LTCG_MSIL", Some("Visual Studio 2015+")),
+    (0x010b, "Utc1900_POGO_I_C", Some("Visual Studio 2015+")),
+    (0x010c, "Utc1900_POGO_I_CPP", Some("Visual Studio 2015+")),
+    (0x010d, "Utc1900_POGO_O_C", Some("Visual Studio 2015+")),
+    (0x010e, "Utc1900_POGO_O_CPP", Some("Visual Studio 2015+")),
+];
+
+/// One `@comp.id` of the Rich header: a tool, and how many objects it produced.
+#[derive(Debug)]
+pub struct RichEntry {
+    pub product_id: u16,
+    pub build_number: u16,
+    pub count: u32,
+}
+
+impl RichEntry {
+    pub fn comp_id(&self) -> u32 {
+        (self.product_id as u32) << 16 | self.build_number as u32
+    }
+
+    fn product(&self) -> Option<&'static (u16, &'static str, Option<&'static str>)> {
+        PRODUCTS
+            .binary_search_by_key(&self.product_id, |(id, _, _)| *id)
+            .ok()
+            .map(|idx| &PRODUCTS[idx])
+    }
+
+    pub fn product_name(&self) -> Option<&'static str> {
+        self.product().map(|(_, name, _)| *name)
+    }
+
+    /// Visual Studio release the tool comes from.
+    pub fn toolchain(&self) -> Option<&'static str> {
+        let toolchain = self.product()?.2?;
+        if toolchain != "Visual Studio 2015+" {
+            return Some(toolchain);
+        }
+        // Product ids did not change after VS2015, only the build numbers did
+        Some(match self.build_number {
+            0..=24999 => "Visual Studio 2015",
+            25000..=27507 => "Visual Studio 2017",
+            27508..=30704 => "Visual Studio 2019",
+            _ => "Visual Studio 2022",
+        })
+    }
+}
+
+impl fmt::Display for RichEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.product_name() {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "0x{:04x}", self.product_id)?,
+        }
+        write!(f, " build {} count {}", self.build_number, self.count)?;
+        if let Some(toolchain) = self.toolchain() {
+            write!(f, " ({})", toolchain)?;
+        }
+        Ok(())
+    }
+}
+
+/// Undocumented header left by Microsoft linkers in the DOS stub, listing the tools that
+/// produced the linked objects. Everything from `DanS` to `Rich` is XOR-ed with `key`.
+#[derive(Debug)]
+pub struct RichHeader<'a> {
+    /// Bytes preceding the header, covered by its checksum.
+    pub dos_data: &'a [u8],
+
+    /// Masked header, from `DanS` up to the `Rich` marker and key included.
+    pub raw: &'a [u8],
+    pub key: u32,
+    pub entries: Vec<RichEntry>,
+}
+
+impl<'a> RichHeader<'a> {
+    /// File offset of the header.
+    pub fn offset(&self) -> usize {
+        self.dos_data.len()
+    }
+
+    /// Computes the checksum the linker uses as `key`.
+    pub fn compute_checksum(&self) -> u32 {
+        let mut checksum = self.offset() as u32;
+        for (idx, b) in self.dos_data.iter().enumerate() {
+            // `e_lfanew` is not known yet when the linker computes the checksum
+            if (0x3c..0x40).contains(&idx) {
+                continue;
+            }
+            checksum = checksum.wrapping_add((*b as u32).rotate_left(idx as u32));
+        }
+        for entry in &self.entries {
+            checksum = checksum.wrapping_add(entry.comp_id().rotate_left(entry.count));
+        }
+        checksum
+    }
+
+    pub fn checksum_valid(&self) -> bool {
+        self.key == self.compute_checksum()
+    }
+
+    /// Whether the 3 dwords following `DanS` unmask to zero, as the linker writes them.
+    pub fn padding_valid(&self) -> bool {
+        self.raw[4..16]
+            .chunks_exact(4)
+            .all(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) == self.key)
+    }
+
+    /// A header that was copied from another binary, or edited, no longer matches its
+    /// checksum.
+    pub fn is_forged(&self) -> bool {
+        !self.checksum_valid() || !self.padding_valid()
+    }
+
+    /// Unmasked header, from `DanS` to the last entry.
+    pub fn clear_data(&self) -> Vec<u8> {
+        let key = self.key.to_le_bytes();
+        self.raw[..self.raw.len() - 8]
+            .iter()
+            .enumerate()
+            .map(|(idx, b)| b ^ key[idx % 4])
+            .collect()
+    }
+
+    /// MD5 of the unmasked header.
+    pub fn clear_text_hash(&self) -> Vec<u8> {
+        Md5::digest(&self.clear_data()[..]).to_vec()
+    }
+
+    /// MD5 of the masked header, which also depends on the key.
+    pub fn masked_hash(&self) -> Vec<u8> {
+        Md5::digest(&self.raw[..self.raw.len() - 8]).to_vec()
+    }
+}
+
+impl<'a> fmt::Display for RichHeader<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}offset: 0x{:x}\n", self.offset())?;
+        write!(f, "{offset}key: 0x{:08x}\n", self.key)?;
+        write!(f, "{offset}checksum_valid: {}\n", self.checksum_valid())?;
+        write!(f, "{offset}clear_text_hash: ")?;
+        for b in self.clear_text_hash() {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, "\n{offset}masked_hash: ")?;
+        for b in self.masked_hash() {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, "\n")?;
+        write!(f, "{offset}entries:\n")?;
+        for entry in &self.entries {
+            write!(f, "{offset}  {}\n", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for RichHeader<'a> {
+    /// `input` is the beginning of the file, up to `e_lfanew`.
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let dword = |offset: usize| {
+            input
+                .get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        };
+        let not_found = || {
+            nom::Err::Error(E::add_context(
+                input,
+                "Rich header",
+                E::from_error_kind(input, nom::error::ErrorKind::TagBits),
+            ))
+        };
+
+        let rich_offset = (0x40..input.len().saturating_sub(7))
+            .step_by(4)
+            .find(|offset| dword(*offset) == Some(RICH_MAGIC))
+            .ok_or_else(not_found)?;
+        let key = dword(rich_offset + 4).ok_or_else(not_found)?;
+        let dans_offset = (0x40..rich_offset)
+            .step_by(4)
+            .rev()
+            .find(|offset| dword(*offset).map(|d| d ^ key) == Some(DANS_MAGIC))
+            .filter(|offset| offset + 16 <= rich_offset)
+            .ok_or_else(not_found)?;
+
+        // `DanS` is followed by 3 masked zeros
+        let mut entries = Vec::new();
+        let mut data = &input[dans_offset + 16..rich_offset];
+        while !data.is_empty() {
+            let (rest, (comp_id, count)) =
+                context("Rich header entry", tuple((le_u32, le_u32)))(data)?;
+            let comp_id = comp_id ^ key;
+            entries.push(RichEntry {
+                product_id: (comp_id >> 16) as u16,
+                build_number: comp_id as u16,
+                count: count ^ key,
+            });
+            data = rest;
+        }
+
+        Ok((
+            &input[rich_offset + 8..],
+            Self {
+                dos_data: &input[..dans_offset],
+                raw: &input[dans_offset..rich_offset + 8],
+                key,
+                entries,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    const KEY: u32 = 0x90f0_fe57;
+
+    /// DOS header and stub with `e_lfanew` at 0x100, followed by a Rich header masked with
+    /// `key`.
+    fn dos_data(key: u32, entries: &[(u32, u32)]) -> Vec<u8> {
+        let mut data = vec![0u8; 0x80];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x3c..0x40].copy_from_slice(&0x100u32.to_le_bytes());
+        data[0x40..0x4e].copy_from_slice(&[
+            0x0e, 0x1f, 0xba, 0x0e, 0x00, 0xb4, 0x09, 0xcd, 0x21, 0xb8, 0x01, 0x4c, 0xcd, 0x21,
+        ]);
+        let message = b"This program cannot be run in DOS mode.\r\r\n$";
+        data[0x4e..0x4e + message.len()].copy_from_slice(message);
+
+        for dword in [DANS_MAGIC, 0, 0, 0] {
+            data.extend_from_slice(&(dword ^ key).to_le_bytes());
+        }
+        for (comp_id, count) in entries {
+            data.extend_from_slice(&(comp_id ^ key).to_le_bytes());
+            data.extend_from_slice(&(count ^ key).to_le_bytes());
+        }
+        data.extend_from_slice(&RICH_MAGIC.to_le_bytes());
+        data.extend_from_slice(&key.to_le_bytes());
+        data.resize(0x100, 0);
+        data
+    }
+
+    #[test]
+    fn checksum() {
+        let data = dos_data(KEY, &[(0x0104_784b, 12), (0x0102_784b, 1)]);
+        let (_, rich_header) = RichHeader::parse::<Error>(&data).unwrap();
+        assert_eq!(rich_header.offset(), 0x80);
+        assert_eq!(rich_header.key, KEY);
+        assert_eq!(rich_header.entries.len(), 2);
+        assert_eq!(rich_header.entries[0].product_name(), Some("Utc1900_C"));
+        assert_eq!(rich_header.entries[0].build_number, 30795);
+        assert_eq!(rich_header.entries[0].count, 12);
+        assert_eq!(
+            rich_header.entries[1].toolchain(),
+            Some("Visual Studio 2022")
+        );
+        assert_eq!(rich_header.compute_checksum(), KEY);
+        assert!(rich_header.checksum_valid());
+        assert!(rich_header.padding_valid());
+        assert!(!rich_header.is_forged());
+        assert_eq!(&rich_header.clear_data()[..4], b"DanS");
+    }
+
+    #[test]
+    fn forged() {
+        // Entry edited after linking
+        let data = dos_data(KEY, &[(0x0104_784b, 13), (0x0102_784b, 1)]);
+        let (_, rich_header) = RichHeader::parse::<Error>(&data).unwrap();
+        assert!(!rich_header.checksum_valid());
+        assert!(rich_header.is_forged());
+
+        // `e_lfanew` is not part of the checksum
+        let mut data = dos_data(KEY, &[(0x0104_784b, 12), (0x0102_784b, 1)]);
+        data[0x3c] = 0xf8;
+        let (_, rich_header) = RichHeader::parse::<Error>(&data).unwrap();
+        assert!(rich_header.checksum_valid());
+
+        // Padding that does not unmask to zero
+        let mut data = dos_data(KEY, &[(0x0104_784b, 12), (0x0102_784b, 1)]);
+        data[0x84] ^= 1;
+        let (_, rich_header) = RichHeader::parse::<Error>(&data).unwrap();
+        assert!(rich_header.checksum_valid());
+        assert!(!rich_header.padding_valid());
+        assert!(rich_header.is_forged());
+    }
+
+    #[test]
+    fn missing() {
+        let mut data = dos_data(KEY, &[(0x0104_784b, 12)]);
+        data[0x80] ^= 1;
+        assert!(RichHeader::parse::<Error>(&data).is_err());
+        assert!(RichHeader::parse::<Error>(&data[..0x80]).is_err());
+    }
+}
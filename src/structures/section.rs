@@ -1,11 +1,11 @@
-use nom::bytes::complete::{take, take_until1};
-use nom::combinator::{map_opt, map_parser};
+use nom::bytes::complete::take;
+use nom::combinator::map_opt;
 use nom::error::context;
 use nom::number::complete::{le_u16, le_u32};
 use nom::sequence::tuple;
 
 use crate::enums::SectionCharacteristics;
-use crate::structures::Name;
+use crate::structures::{Name, StringTable};
 use crate::{NomError, Parse};
 
 use std::fmt;
@@ -75,23 +75,47 @@ where
 {
     let (rest, raw_name) = context(
         "Section name",
-        map_opt(map_parser(take(8usize), take_until1(&b"\0"[..])), |s| {
-            std::str::from_utf8(s).ok()
+        // Names are nul-padded, but 8-character names take the whole field.
+        map_opt(take(8usize), |s: &'a [u8]| {
+            std::str::from_utf8(s.split(|b| *b == 0).next().unwrap_or_default()).ok()
         }),
     )(input)?;
-    if raw_name.starts_with('/') {
-        match u64::from_str_radix(&raw_name[1..], 10) {
-            Ok(rva) => Ok((rest, Name::Rva(rva))),
-            Err(_) => Err(nom::Err::Failure(E::from_error_kind(
-                &input[1..],
-                nom::error::ErrorKind::Digit,
-            ))),
-        }
+    // Long names are stored in the string table, the name field holding their offset
+    let offset = if let Some(encoded) = raw_name.strip_prefix("//") {
+        decode_base64_offset(encoded)
+    } else if let Some(decimal) = raw_name.strip_prefix('/') {
+        u64::from_str_radix(decimal, 10).ok()
     } else {
-        Ok((rest, Name::String(raw_name)))
+        return Ok((rest, Name::String(raw_name)));
+    };
+    match offset {
+        Some(offset) => Ok((rest, Name::Rva(offset))),
+        None => Err(nom::Err::Failure(E::from_error_kind(
+            &input[1..],
+            nom::error::ErrorKind::Digit,
+        ))),
     }
 }
 
+/// Decodes the `//BASE64` form used for string table offsets too large for 7 decimal digits.
+/// Digits are most significant first, without padding.
+fn decode_base64_offset(encoded: &str) -> Option<u64> {
+    if encoded.is_empty() {
+        return None;
+    }
+    encoded.bytes().try_fold(0u64, |offset, digit| {
+        let value = match digit {
+            b'A'..=b'Z' => digit - b'A',
+            b'a'..=b'z' => digit - b'a' + 26,
+            b'0'..=b'9' => digit - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some((offset << 6) | value as u64)
+    })
+}
+
 impl<'a> Parse<'a> for SectionHeader<'a> {
     fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
     where
@@ -146,6 +170,14 @@ impl<'a> Parse<'a> for SectionHeader<'a> {
 }
 
 impl<'a> SectionHeader<'a> {
+    /// Section name, looking long names up in the COFF string table.
+    pub fn resolve_name(&self, string_table: Option<&StringTable<'a>>) -> Option<&'a str> {
+        match self.name {
+            Name::String(name) => Some(name),
+            Name::Rva(offset) => string_table?.get(offset as usize),
+        }
+    }
+
     pub fn contains(&self, rva: u64) -> bool {
         let virtual_size = self.physical_address as u64;
         let start = self.virtual_address as u64;
@@ -159,3 +191,48 @@ impl<'a> SectionHeader<'a> {
         rva as usize - self.virtual_address as usize
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn section(name: &[u8; 8]) -> Vec<u8> {
+        let mut data = name.to_vec();
+        data.resize(40, 0);
+        data
+    }
+
+    #[test]
+    fn long_names() {
+        let mut string_table = 17u32.to_le_bytes().to_vec();
+        string_table.extend_from_slice(b".debug_info\0\0");
+        let (_, string_table) = StringTable::parse::<Error>(&string_table).unwrap();
+
+        for (name, expected) in [
+            (b".text\0\0\0", Some(".text")),
+            (b".rdata$z", Some(".rdata$z")),
+            (b"/4\0\0\0\0\0\0", Some(".debug_info")),
+            (b"/11\0\0\0\0\0", Some("info")),
+            (b"//AAAAAE", Some(".debug_info")),
+            (b"//AAAAgA", None),
+        ] {
+            let data = section(name);
+            let (_, section) = SectionHeader::parse::<Error>(&data).unwrap();
+            assert_eq!(section.resolve_name(Some(&string_table)), expected);
+        }
+
+        let data = section(b"/4\0\0\0\0\0\0");
+        let (_, section) = SectionHeader::parse::<Error>(&data).unwrap();
+        assert_eq!(section.resolve_name(None), None);
+    }
+
+    #[test]
+    fn invalid_long_names() {
+        for name in [b"/4x\0\0\0\0\0", b"//\0\0\0\0\0\0", b"//AA-A\0\0"] {
+            let data = section(name);
+            assert!(SectionHeader::parse::<Error>(&data).is_err());
+        }
+    }
+}
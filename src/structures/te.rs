@@ -0,0 +1,270 @@
+use nom::bytes::complete::tag;
+use nom::combinator::map;
+use nom::error::context;
+use nom::multi::count;
+use nom::number::complete::{le_u16, le_u32, le_u64, le_u8};
+use nom::sequence::tuple;
+
+use crate::enums::{FileMachine, SubSystem};
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+use super::{DataDirectory, SectionHeader};
+
+/// `EFI_TE_IMAGE_HEADER`, replacing the DOS, file and optional headers of a PE32 image.
+#[derive(Debug)]
+pub struct TeHeader {
+    pub machine: FileMachine,
+    pub number_of_sections: u8,
+    pub subsystem: SubSystem,
+    /// Size of the original headers this one replaces, section headers excluded.
+    pub stripped_size: u16,
+    pub address_of_entry_point: u32,
+    pub base_of_code: u32,
+    pub image_base: u64,
+    pub base_relocation_table: DataDirectory,
+    pub debug: DataDirectory,
+}
+
+impl TeHeader {
+    pub const fn size() -> usize {
+        40
+    }
+
+    /// File offset of the given RVA. Section headers keep the values of the original image, so
+    /// their RVAs and file offsets alike must go through this.
+    pub fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        (rva as usize + Self::size()).checked_sub(self.stripped_size as usize)
+    }
+}
+
+impl fmt::Display for TeHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}machine: {}\n", self.machine)?;
+        write!(
+            f,
+            "{offset}number_of_sections: 0x{:x}\n",
+            self.number_of_sections
+        )?;
+        write!(f, "{offset}subsystem: {}\n", self.subsystem)?;
+        write!(f, "{offset}stripped_size: 0x{:x}\n", self.stripped_size)?;
+        write!(
+            f,
+            "{offset}address_of_entry_point: 0x{:x}\n",
+            self.address_of_entry_point
+        )?;
+        write!(f, "{offset}base_of_code: 0x{:x}\n", self.base_of_code)?;
+        write!(f, "{offset}image_base: 0x{:x}\n", self.image_base)?;
+        write!(
+            f,
+            "{offset}base_relocation_table:\n{:width$}",
+            self.base_relocation_table
+        )?;
+        write!(f, "{offset}debug:\n{:width$}", self.debug)
+    }
+}
+
+impl<'a> Parse<'a> for TeHeader {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            rest,
+            (
+                _,
+                machine,
+                number_of_sections,
+                subsystem,
+                stripped_size,
+                address_of_entry_point,
+                base_of_code,
+                image_base,
+                base_relocation_table,
+                debug,
+            ),
+        ) = context(
+            "TE header",
+            tuple((
+                tag(b"VZ"),
+                FileMachine::parse,
+                le_u8,
+                context(
+                    "SubSystem",
+                    map(le_u8, |subsystem| SubSystem::from(subsystem as u16)),
+                ),
+                le_u16,
+                le_u32,
+                le_u32,
+                le_u64,
+                DataDirectory::parse,
+                DataDirectory::parse,
+            )),
+        )(input)?;
+
+        Ok((
+            rest,
+            Self {
+                machine,
+                number_of_sections,
+                subsystem,
+                stripped_size,
+                address_of_entry_point,
+                base_of_code,
+                image_base,
+                base_relocation_table,
+                debug,
+            },
+        ))
+    }
+}
+
+/// Terse Executable, the stripped down PE32 format used by UEFI PI firmware.
+#[derive(Debug)]
+pub struct TeImage<'a> {
+    pub(super) data: &'a [u8],
+    pub te_header: TeHeader,
+    pub sections: Vec<SectionHeader<'a>>,
+}
+
+impl<'a> TeImage<'a> {
+    /// Data at the given RVA, up to the end of the containing section.
+    pub fn get_data(&self, rva: u32, size: Option<u32>) -> Option<&'a [u8]> {
+        let section = self
+            .sections
+            .iter()
+            .find(|section| section.contains(rva as u64))?;
+        let section_data = self.section_data(section)?;
+        let data = section_data.get(section.offset(rva as u64)..)?;
+        match size {
+            Some(size) => data.get(..size as usize),
+            None => Some(data),
+        }
+    }
+
+    pub fn section_data(&self, section: &SectionHeader<'a>) -> Option<&'a [u8]> {
+        let offset = self.te_header.rva_to_offset(section.pointer_to_raw_data)?;
+        self.data
+            .get(offset..)?
+            .get(..section.size_of_raw_data as usize)
+    }
+
+    pub fn base_relocation_table(&self) -> Option<&'a [u8]> {
+        let data_dir = &self.te_header.base_relocation_table;
+        if data_dir.virtual_address == 0 {
+            return None;
+        }
+        self.get_data(data_dir.virtual_address, Some(data_dir.size))
+    }
+
+    pub fn debug_directory(&self) -> Option<&'a [u8]> {
+        let data_dir = &self.te_header.debug;
+        if data_dir.virtual_address == 0 {
+            return None;
+        }
+        self.get_data(data_dir.virtual_address, Some(data_dir.size))
+    }
+}
+
+impl<'a> fmt::Display for TeImage<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}te_header:\n{:width$}", self.te_header)?;
+        write!(f, "{offset}sections:\n")?;
+        for section in &self.sections {
+            write!(f, "{:width$}\n", section)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for TeImage<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, te_header) = TeHeader::parse(input)?;
+        let (_, sections) = context(
+            "TE section headers",
+            count(SectionHeader::parse, te_header.number_of_sections as usize),
+        )(rest)?;
+
+        Ok((
+            &[],
+            Self {
+                data: input,
+                te_header,
+                sections,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    /// TE image stripped of 0x1d8 bytes of headers, with a `.text` section that the original
+    /// image had at file offset 0x200 and RVA 0x1000.
+    fn image() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"VZ");
+        data.extend_from_slice(&0x8664u16.to_le_bytes());
+        data.push(1);
+        data.push(10);
+        data.extend_from_slice(&0x1d8u16.to_le_bytes());
+        data.extend_from_slice(&0x1000u32.to_le_bytes());
+        data.extend_from_slice(&0x1000u32.to_le_bytes());
+        data.extend_from_slice(&0x1_0000u64.to_le_bytes());
+        // Base relocation table, then debug directory
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&0x1010u32.to_le_bytes());
+        data.extend_from_slice(&0x10u32.to_le_bytes());
+
+        data.extend_from_slice(b".text\0\0\0");
+        data.extend_from_slice(&0x20u32.to_le_bytes());
+        data.extend_from_slice(&0x1000u32.to_le_bytes());
+        data.extend_from_slice(&0x20u32.to_le_bytes());
+        data.extend_from_slice(&0x200u32.to_le_bytes());
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(&0x6000_0020u32.to_le_bytes());
+
+        data.extend_from_slice(b"code code code !debug directory!");
+        data
+    }
+
+    #[test]
+    fn rva_to_offset() {
+        let data = image();
+        let (_, te) = TeImage::parse::<Error>(&data).unwrap();
+        assert_eq!(te.te_header.machine, FileMachine::MachineAMD64);
+        assert_eq!(te.te_header.stripped_size, 0x1d8);
+
+        // Offsets shift by the stripped size, minus the TE header replacing it
+        assert_eq!(te.te_header.rva_to_offset(0x200), Some(0x50));
+        assert_eq!(te.te_header.rva_to_offset(0x1b0), Some(0));
+        assert_eq!(te.te_header.rva_to_offset(0x1af), None);
+
+        let section = &te.sections[0];
+        assert_eq!(te.section_data(section), Some(&data[0x50..0x70]));
+        assert_eq!(te.get_data(0x1000, Some(4)), Some(&b"code"[..]));
+        assert_eq!(te.debug_directory(), Some(&b"debug directory!"[..]));
+        assert_eq!(te.base_relocation_table(), None);
+        assert_eq!(te.get_data(0x1020, None), None);
+    }
+
+    #[test]
+    fn truncated_section() {
+        let mut data = image();
+        data.truncate(0x60);
+        let (_, te) = TeImage::parse::<Error>(&data).unwrap();
+        assert_eq!(te.section_data(&te.sections[0]), None);
+        assert_eq!(te.debug_directory(), None);
+    }
+}
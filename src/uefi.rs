@@ -0,0 +1,229 @@
+use crate::structures::{Guid, Pe, TeImage};
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+mod file;
+pub use file::{FfsFile, FfsFileType};
+
+mod section;
+pub use section::{FfsSection, FfsSectionType, EFI_GUIDED_SECTION_PROCESSING_REQUIRED};
+
+mod volume;
+pub use volume::{FirmwareVolume, FirmwareVolumeHeader};
+
+/// How deep sections may nest through encapsulation sections and firmware volume images.
+/// Firmware images go no further than a compressed volume in a volume.
+const MAX_NESTING: usize = 8;
+
+pub enum UefiExecutable<'a> {
+    Pe(Pe<'a>),
+    Te(TeImage<'a>),
+}
+
+impl<'a> fmt::Display for UefiExecutable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default();
+        match self {
+            Self::Pe(pe) => write!(f, "{:width$}", pe),
+            Self::Te(te) => write!(f, "{:width$}", te),
+        }
+    }
+}
+
+/// PE32 or TE section found in a firmware volume.
+#[derive(Debug)]
+pub struct UefiImage<'a> {
+    /// Name of the FFS file holding the section.
+    pub file_name: Guid,
+    pub file_type: FfsFileType,
+    pub section_type: FfsSectionType,
+    pub data: &'a [u8],
+}
+
+impl<'a> UefiImage<'a> {
+    pub fn parse<E>(&self) -> Result<UefiExecutable<'a>, nom::Err<E>>
+    where
+        E: NomError<'a>,
+    {
+        if self.section_type == FfsSectionType::Te {
+            TeImage::parse(self.data).map(|(_, te)| UefiExecutable::Te(te))
+        } else {
+            Pe::parse(self.data).map(|(_, pe)| UefiExecutable::Pe(pe))
+        }
+    }
+}
+
+impl<'a> fmt::Display for UefiImage<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}{} {} {}: 0x{:x} bytes\n",
+            self.file_name,
+            self.file_type,
+            self.section_type,
+            self.data.len()
+        )
+    }
+}
+
+fn section_images<'a>(
+    file: &FfsFile<'a>,
+    sections: Vec<FfsSection<'a>>,
+    images: &mut Vec<UefiImage<'a>>,
+    depth: usize,
+) {
+    for section in sections {
+        match section.section_type {
+            FfsSectionType::Pe32 | FfsSectionType::Te => images.push(UefiImage {
+                file_name: file.name,
+                file_type: file.file_type,
+                section_type: section.section_type,
+                data: section.data,
+            }),
+            // Anything nested deeper is skipped, like malformed sections
+            FfsSectionType::Compression
+            | FfsSectionType::GuidDefined
+            | FfsSectionType::FirmwareVolumeImage
+                if depth >= MAX_NESTING => {}
+            FfsSectionType::Compression | FfsSectionType::GuidDefined => {
+                // Sections needing a decompressor or another decoder are skipped.
+                if let Some(data) = section.encapsulated_data() {
+                    if let Ok(sections) = section::sections::<nom::error::Error<&[u8]>>(data) {
+                        section_images(file, sections, images, depth + 1);
+                    }
+                }
+            }
+            FfsSectionType::FirmwareVolumeImage => {
+                if let Ok((_, volume)) =
+                    FirmwareVolume::parse::<nom::error::Error<&[u8]>>(section.data)
+                {
+                    volume_images(&volume, images, depth + 1);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn volume_images<'a>(volume: &FirmwareVolume<'a>, images: &mut Vec<UefiImage<'a>>, depth: usize) {
+    for file in &volume.files {
+        if let Ok(sections) = file.sections::<nom::error::Error<&[u8]>>() {
+            section_images(file, sections, images, depth);
+        }
+    }
+}
+
+/// Finds every firmware volume of a flash image, nested ones included, and returns the PE32
+/// and TE images they contain. Malformed volumes, files and sections are skipped.
+pub fn scan_images(data: &[u8]) -> Vec<UefiImage<'_>> {
+    // The signature is found 40 bytes in the volume header.
+    const SIGNATURE_OFFSET: usize = 40;
+
+    let mut images = Vec::new();
+    let mut offset = SIGNATURE_OFFSET;
+    while let Some(pos) = data
+        .get(offset..)
+        .and_then(|data| data.windows(4).position(|w| w == b"_FVH"))
+    {
+        let start = offset + pos - SIGNATURE_OFFSET;
+        match FirmwareVolume::parse::<nom::error::Error<&[u8]>>(&data[start..]) {
+            Ok((_, volume)) if volume.checksum_valid() => {
+                volume_images(&volume, &mut images, 0);
+                // Always move past the signature, whatever the volume length
+                offset = (start + volume.data.len() + SIGNATURE_OFFSET).max(offset + pos + 4);
+            }
+            _ => offset += pos + 4,
+        }
+    }
+    images
+}
+
+#[cfg(test)]
+mod tests {
+    use super::volume::tests::{file, volume};
+    use super::*;
+
+    const ERASE_POLARITY: u32 = 0x800;
+
+    fn section(section_type: u8, data: &[u8]) -> Vec<u8> {
+        let mut section = (4 + data.len() as u32).to_le_bytes()[..3].to_vec();
+        section.push(section_type);
+        section.extend_from_slice(data);
+        section
+    }
+
+    /// Volume image section wrapping `sections` in a file of its own.
+    fn volume_image(sections: &[u8]) -> Vec<u8> {
+        let file = file(0x0b, 0xf8, sections);
+        section(0x17, &volume(ERASE_POLARITY, &[file]))
+    }
+
+    #[test]
+    fn scan() {
+        let mut data = b"garbage".repeat(8);
+        data[50..54].copy_from_slice(b"_FVH");
+        data.extend(volume(
+            ERASE_POLARITY,
+            &[file(0x07, 0xf8, &section(0x10, b"MZ a"))],
+        ));
+        data.extend_from_slice(b"_FVH_FVH");
+        let mut sections = section(0x12, b"VZ b");
+        sections.extend(volume_image(&section(0x10, b"MZ c")));
+        data.extend(volume(0, &[file(0x09, 0x07, &sections)]));
+        data.extend_from_slice(b"trailing");
+
+        let images = scan_images(&data);
+        assert_eq!(images.len(), 3);
+        assert_eq!(images[0].file_type, FfsFileType::Driver);
+        assert_eq!(images[0].section_type, FfsSectionType::Pe32);
+        assert_eq!(images[0].data, b"MZ a");
+        assert_eq!(images[1].file_type, FfsFileType::Application);
+        assert_eq!(images[1].section_type, FfsSectionType::Te);
+        assert_eq!(images[1].data, b"VZ b");
+        // The nested volume is not found a second time by the scan
+        assert_eq!(images[2].file_type, FfsFileType::FirmwareVolumeImage);
+        assert_eq!(images[2].data, b"MZ c");
+    }
+
+    #[test]
+    fn empty_volume() {
+        // Zero volume and header lengths with a valid checksum
+        let mut data = vec![0; 40];
+        data.extend_from_slice(b"_FVH");
+        data.extend_from_slice(&[0; 28]);
+        let sum = data.chunks_exact(2).fold(0u16, |sum, word| {
+            sum.wrapping_add(u16::from_le_bytes([word[0], word[1]]))
+        });
+        data[50..52].copy_from_slice(&sum.wrapping_neg().to_le_bytes());
+        data.extend(volume(0, &[file(0x07, 0x07, &section(0x10, b"MZ"))]));
+
+        let images = scan_images(&data);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].data, b"MZ");
+    }
+
+    #[test]
+    fn nesting() {
+        let nested = |depth: usize| {
+            let mut sections = section(0x10, b"MZ");
+            for level in 0..depth {
+                sections = if level % 2 == 0 {
+                    // Uncompressed compression section
+                    let mut data = (sections.len() as u32).to_le_bytes().to_vec();
+                    data.push(0);
+                    data.extend(sections);
+                    section(0x01, &data)
+                } else {
+                    volume_image(&sections)
+                };
+            }
+            volume(0, &[file(0x07, 0x07, &sections)])
+        };
+
+        assert_eq!(scan_images(&nested(MAX_NESTING)).len(), 1);
+        assert!(scan_images(&nested(MAX_NESTING + 1)).is_empty());
+        assert!(scan_images(&nested(200)).is_empty());
+    }
+}
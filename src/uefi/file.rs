@@ -0,0 +1,162 @@
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u24, le_u64, le_u8};
+use nom::sequence::tuple;
+
+use crate::structures::Guid;
+use crate::{NomError, Parse};
+
+use super::section::{sections, FfsSection};
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FfsFileType {
+    Raw,
+    Freeform,
+    SecurityCore,
+    PeiCore,
+    DxeCore,
+    Peim,
+    Driver,
+    CombinedPeimDriver,
+    Application,
+    Mm,
+    FirmwareVolumeImage,
+    CombinedMmDxe,
+    MmCore,
+    MmStandalone,
+    MmCoreStandalone,
+    Pad,
+    Other(u8),
+}
+
+impl FfsFileType {
+    /// Whether the file content is made of sections.
+    pub fn has_sections(&self) -> bool {
+        !matches!(self, Self::Raw | Self::Pad | Self::Other(_))
+    }
+}
+
+impl From<u8> for FfsFileType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => Self::Raw,
+            0x02 => Self::Freeform,
+            0x03 => Self::SecurityCore,
+            0x04 => Self::PeiCore,
+            0x05 => Self::DxeCore,
+            0x06 => Self::Peim,
+            0x07 => Self::Driver,
+            0x08 => Self::CombinedPeimDriver,
+            0x09 => Self::Application,
+            0x0a => Self::Mm,
+            0x0b => Self::FirmwareVolumeImage,
+            0x0c => Self::CombinedMmDxe,
+            0x0d => Self::MmCore,
+            0x0e => Self::MmStandalone,
+            0x0f => Self::MmCoreStandalone,
+            0xf0 => Self::Pad,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for FfsFileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Raw => f.write_str("raw"),
+            Self::Freeform => f.write_str("freeform"),
+            Self::SecurityCore => f.write_str("SEC core"),
+            Self::PeiCore => f.write_str("PEI core"),
+            Self::DxeCore => f.write_str("DXE core"),
+            Self::Peim => f.write_str("PEIM"),
+            Self::Driver => f.write_str("driver"),
+            Self::CombinedPeimDriver => f.write_str("combined PEIM/driver"),
+            Self::Application => f.write_str("application"),
+            Self::Mm => f.write_str("MM"),
+            Self::FirmwareVolumeImage => f.write_str("firmware volume image"),
+            Self::CombinedMmDxe => f.write_str("combined MM/DXE"),
+            Self::MmCore => f.write_str("MM core"),
+            Self::MmStandalone => f.write_str("MM standalone"),
+            Self::MmCoreStandalone => f.write_str("MM core standalone"),
+            Self::Pad => f.write_str("pad"),
+            Self::Other(value) => write!(f, "0x{:02x}", value),
+        }
+    }
+}
+
+/// `FFS_ATTRIB_LARGE_FILE`: the header is followed by a 64-bit `ExtendedSize`.
+const FFS_ATTRIB_LARGE_FILE: u8 = 0x01;
+
+#[derive(Debug)]
+pub struct FfsFile<'a> {
+    pub name: Guid,
+    pub integrity_check: u16,
+    pub file_type: FfsFileType,
+    pub attributes: u8,
+    pub state: u8,
+    /// File content, following the header.
+    pub data: &'a [u8],
+}
+
+impl<'a> FfsFile<'a> {
+    pub fn sections<E>(&self) -> Result<Vec<FfsSection<'a>>, nom::Err<E>>
+    where
+        E: NomError<'a>,
+    {
+        if self.file_type.has_sections() {
+            sections(self.data)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+}
+
+impl<'a> fmt::Display for FfsFile<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}{} {}: 0x{:x} bytes\n",
+            self.name,
+            self.file_type,
+            self.data.len()
+        )
+    }
+}
+
+impl<'a> Parse<'a> for FfsFile<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (name, integrity_check, file_type, attributes, size, state)) = context(
+            "FFS file header",
+            tuple((Guid::parse, le_u16, le_u8, le_u8, le_u24, le_u8)),
+        )(input)?;
+        let (header_size, size) = if attributes & FFS_ATTRIB_LARGE_FILE != 0 {
+            let (_, size) = context("FFS file extended size", le_u64)(rest)?;
+            (32, size as usize)
+        } else {
+            (24, size as usize)
+        };
+        let (rest, raw) = context(
+            "FFS file",
+            verify(take(size), |_: &[u8]| size >= header_size),
+        )(input)?;
+
+        Ok((
+            rest,
+            Self {
+                name,
+                integrity_check,
+                file_type: file_type.into(),
+                attributes,
+                state,
+                data: &raw[header_size..],
+            },
+        ))
+    }
+}
@@ -0,0 +1,274 @@
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u24, le_u32, le_u8};
+use nom::sequence::tuple;
+
+use crate::structures::Guid;
+use crate::{NomError, Parse};
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FfsSectionType {
+    Compression,
+    GuidDefined,
+    Disposable,
+    Pe32,
+    Pic,
+    Te,
+    DxeDepex,
+    Version,
+    UserInterface,
+    Compatibility16,
+    FirmwareVolumeImage,
+    FreeformSubtypeGuid,
+    Raw,
+    PeiDepex,
+    MmDepex,
+    Other(u8),
+}
+
+impl From<u8> for FfsSectionType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => Self::Compression,
+            0x02 => Self::GuidDefined,
+            0x03 => Self::Disposable,
+            0x10 => Self::Pe32,
+            0x11 => Self::Pic,
+            0x12 => Self::Te,
+            0x13 => Self::DxeDepex,
+            0x14 => Self::Version,
+            0x15 => Self::UserInterface,
+            0x16 => Self::Compatibility16,
+            0x17 => Self::FirmwareVolumeImage,
+            0x18 => Self::FreeformSubtypeGuid,
+            0x19 => Self::Raw,
+            0x1b => Self::PeiDepex,
+            0x1c => Self::MmDepex,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl fmt::Display for FfsSectionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compression => f.write_str("compression"),
+            Self::GuidDefined => f.write_str("guid defined"),
+            Self::Disposable => f.write_str("disposable"),
+            Self::Pe32 => f.write_str("PE32"),
+            Self::Pic => f.write_str("PIC"),
+            Self::Te => f.write_str("TE"),
+            Self::DxeDepex => f.write_str("DXE depex"),
+            Self::Version => f.write_str("version"),
+            Self::UserInterface => f.write_str("user interface"),
+            Self::Compatibility16 => f.write_str("compatibility16"),
+            Self::FirmwareVolumeImage => f.write_str("firmware volume image"),
+            Self::FreeformSubtypeGuid => f.write_str("freeform subtype guid"),
+            Self::Raw => f.write_str("raw"),
+            Self::PeiDepex => f.write_str("PEI depex"),
+            Self::MmDepex => f.write_str("MM depex"),
+            Self::Other(value) => write!(f, "0x{:02x}", value),
+        }
+    }
+}
+
+/// Encapsulation data requires processing, i.e. decoding, before use.
+pub const EFI_GUIDED_SECTION_PROCESSING_REQUIRED: u16 = 0x01;
+
+#[derive(Debug)]
+pub struct FfsSection<'a> {
+    pub section_type: FfsSectionType,
+    /// Section data, following the common header.
+    pub data: &'a [u8],
+    /// Whole section, header included.
+    pub raw: &'a [u8],
+}
+
+impl<'a> FfsSection<'a> {
+    /// `UncompressedLength` and `CompressionType` of a compression section.
+    pub fn compression(&self) -> Option<(u32, u8)> {
+        if self.section_type != FfsSectionType::Compression {
+            return None;
+        }
+        tuple((le_u32::<_, nom::error::Error<&[u8]>>, le_u8))(self.data)
+            .ok()
+            .map(|(_, compression)| compression)
+    }
+
+    /// `SectionDefinitionGuid`, `DataOffset` and `Attributes` of a GUID-defined section.
+    pub fn guid_defined(&self) -> Option<(Guid, u16, u16)> {
+        if self.section_type != FfsSectionType::GuidDefined {
+            return None;
+        }
+        tuple((Guid::parse::<nom::error::Error<&[u8]>>, le_u16, le_u16))(self.data)
+            .ok()
+            .map(|(_, guid_defined)| guid_defined)
+    }
+
+    /// Sections encapsulated in this one, when they can be reached without a decoder: either
+    /// not compressed, or GUID-defined with no processing required.
+    pub fn encapsulated_data(&self) -> Option<&'a [u8]> {
+        match self.section_type {
+            FfsSectionType::Compression => match self.compression()? {
+                (_, 0) => self.data.get(5..),
+                _ => None,
+            },
+            FfsSectionType::GuidDefined => match self.guid_defined()? {
+                (_, data_offset, attributes)
+                    if attributes & EFI_GUIDED_SECTION_PROCESSING_REQUIRED == 0 =>
+                {
+                    self.raw.get(data_offset as usize..)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'a> fmt::Display for FfsSection<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(
+            f,
+            "{offset}{}: 0x{:x} bytes\n",
+            self.section_type,
+            self.data.len()
+        )
+    }
+}
+
+impl<'a> Parse<'a> for FfsSection<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (rest, (size, section_type)) =
+            context("FFS section header", tuple((le_u24, le_u8)))(input)?;
+        let (header_size, size) = if size == 0xffffff {
+            let (_, size) = context("FFS section extended size", le_u32)(rest)?;
+            (8, size as usize)
+        } else {
+            (4, size as usize)
+        };
+        let (rest, raw) = context(
+            "FFS section",
+            verify(take(size), |_: &[u8]| size >= header_size),
+        )(input)?;
+        let data = &raw[header_size..];
+
+        Ok((
+            rest,
+            Self {
+                section_type: section_type.into(),
+                data,
+                raw,
+            },
+        ))
+    }
+}
+
+/// Parses the sections of a file or encapsulation section, each one aligned on 4 bytes.
+pub(super) fn sections<'a, E>(input: &'a [u8]) -> Result<Vec<FfsSection<'a>>, nom::Err<E>>
+where
+    E: NomError<'a>,
+{
+    let mut sections = Vec::new();
+    let mut offset = 0;
+    while offset + 4 <= input.len() {
+        let (_, section) = FfsSection::parse(&input[offset..])?;
+        offset += (section.raw.len() + 3) & !3;
+        sections.push(section);
+    }
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    fn section(section_type: u8, data: &[u8]) -> Vec<u8> {
+        let mut section = (4 + data.len() as u32).to_le_bytes()[..3].to_vec();
+        section.push(section_type);
+        section.extend_from_slice(data);
+        section
+    }
+
+    #[test]
+    fn sections_are_aligned_on_4_bytes() {
+        let mut data = section(0x15, b"N\0a\0m\0e\0\0\0");
+        data.extend_from_slice(&[0; 2]);
+        data.extend(section(0x10, b"MZ"));
+        data.extend_from_slice(&[0; 2]);
+        data.extend(section(0x19, b""));
+
+        let sections = sections::<Error>(&data).unwrap();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].section_type, FfsSectionType::UserInterface);
+        assert_eq!(sections[0].data.len(), 10);
+        assert_eq!(sections[1].section_type, FfsSectionType::Pe32);
+        assert_eq!(sections[1].data, b"MZ");
+        assert_eq!(sections[1].raw.len(), 6);
+        assert_eq!(sections[2].section_type, FfsSectionType::Raw);
+        assert!(sections[2].data.is_empty());
+    }
+
+    #[test]
+    fn extended_size() {
+        let mut data = vec![0xff, 0xff, 0xff, 0x12];
+        data.extend_from_slice(&12u32.to_le_bytes());
+        data.extend_from_slice(b"VZ\0\0");
+
+        let sections = sections::<Error>(&data).unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].section_type, FfsSectionType::Te);
+        assert_eq!(sections[0].data, b"VZ\0\0");
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        // Smaller than its own header, and larger than the data
+        assert!(sections::<Error>(&[0x02, 0x00, 0x00, 0x19]).is_err());
+        assert!(sections::<Error>(&section(0x19, b"data")[..6]).is_err());
+    }
+
+    #[test]
+    fn encapsulation() {
+        let inner = section(0x10, b"MZ\0\0");
+
+        // Not compressed
+        let mut data = (inner.len() as u32).to_le_bytes().to_vec();
+        data.push(0);
+        data.extend_from_slice(&inner);
+        let raw = section(0x01, &data);
+        let (_, compression) = FfsSection::parse::<Error>(&raw).unwrap();
+        assert_eq!(compression.compression(), Some((8, 0)));
+        assert_eq!(compression.encapsulated_data(), Some(&inner[..]));
+
+        // Compressed with the standard compression, which needs a decoder
+        data[4] = 1;
+        let raw = section(0x01, &data);
+        let (_, compression) = FfsSection::parse::<Error>(&raw).unwrap();
+        assert_eq!(compression.encapsulated_data(), None);
+
+        // GUID-defined, with the data following a 24-byte header
+        for (attributes, expected) in [(0x0002u16, Some(&inner[..])), (0x0001, None)] {
+            let mut data = [0xaa; 16].to_vec();
+            data.extend_from_slice(&24u16.to_le_bytes());
+            data.extend_from_slice(&attributes.to_le_bytes());
+            data.extend_from_slice(&inner);
+            let raw = section(0x02, &data);
+            let (_, guid_defined) = FfsSection::parse::<Error>(&raw).unwrap();
+            assert_eq!(
+                guid_defined.guid_defined().map(|(_, o, a)| (o, a)),
+                Some((24, attributes))
+            );
+            assert_eq!(guid_defined.encapsulated_data(), expected);
+        }
+    }
+}
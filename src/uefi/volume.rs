@@ -0,0 +1,343 @@
+use nom::bytes::complete::{tag, take};
+use nom::error::context;
+use nom::number::complete::{le_u16, le_u32, le_u64, le_u8};
+use nom::sequence::tuple;
+
+use crate::parsers::at;
+use crate::structures::Guid;
+use crate::{NomError, Parse};
+
+use super::file::FfsFile;
+
+use std::fmt;
+
+/// `EFI_FVB2_ERASE_POLARITY`: erased flash reads as 0xff rather than 0x00.
+const EFI_FVB2_ERASE_POLARITY: u32 = 0x00000800;
+
+#[derive(Debug)]
+pub struct FirmwareVolumeHeader {
+    pub file_system_guid: Guid,
+    pub fv_length: u64,
+    pub attributes: u32,
+    pub header_length: u16,
+    pub checksum: u16,
+    pub ext_header_offset: u16,
+    pub revision: u8,
+    /// `(NumBlocks, Length)` pairs, without the terminating zero entry.
+    pub block_map: Vec<(u32, u32)>,
+}
+
+impl FirmwareVolumeHeader {
+    pub fn erase_polarity(&self) -> bool {
+        self.attributes & EFI_FVB2_ERASE_POLARITY != 0
+    }
+}
+
+impl fmt::Display for FirmwareVolumeHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = "  ".repeat(f.width().unwrap_or_default() + 1);
+        write!(f, "{offset}file_system_guid: {}\n", self.file_system_guid)?;
+        write!(f, "{offset}fv_length: 0x{:x}\n", self.fv_length)?;
+        write!(f, "{offset}attributes: 0x{:08x}\n", self.attributes)?;
+        write!(f, "{offset}header_length: 0x{:x}\n", self.header_length)?;
+        write!(f, "{offset}checksum: 0x{:04x}\n", self.checksum)?;
+        write!(f, "{offset}revision: {}\n", self.revision)
+    }
+}
+
+impl<'a> Parse<'a> for FirmwareVolumeHeader {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (
+            mut rest,
+            (
+                _zero_vector,
+                file_system_guid,
+                fv_length,
+                _,
+                attributes,
+                header_length,
+                checksum,
+                ext_header_offset,
+                _reserved,
+                revision,
+            ),
+        ) = context(
+            "Firmware volume header",
+            tuple((
+                take(16usize),
+                Guid::parse,
+                le_u64,
+                tag(b"_FVH"),
+                le_u32,
+                le_u16,
+                le_u16,
+                le_u16,
+                le_u8,
+                le_u8,
+            )),
+        )(input)?;
+
+        let mut block_map = Vec::new();
+        loop {
+            let (r, block) = context("Firmware volume block map", tuple((le_u32, le_u32)))(rest)?;
+            rest = r;
+            if block == (0, 0) {
+                break;
+            }
+            block_map.push(block);
+        }
+
+        Ok((
+            rest,
+            Self {
+                file_system_guid,
+                fv_length,
+                attributes,
+                header_length,
+                checksum,
+                ext_header_offset,
+                revision,
+                block_map,
+            },
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct FirmwareVolume<'a> {
+    /// Whole volume, header included.
+    pub data: &'a [u8],
+    pub header: FirmwareVolumeHeader,
+    /// `FvName` of the extended header, if any.
+    pub name: Option<Guid>,
+    pub files: Vec<FfsFile<'a>>,
+}
+
+impl<'a> FirmwareVolume<'a> {
+    /// The 16-bit sum of the header words must be 0.
+    pub fn checksum_valid(&self) -> bool {
+        let header = &self.data[..(self.header.header_length as usize).min(self.data.len())];
+        header.chunks_exact(2).fold(0u16, |sum, word| {
+            sum.wrapping_add(u16::from_le_bytes([word[0], word[1]]))
+        }) == 0
+    }
+}
+
+impl<'a> fmt::Display for FirmwareVolume<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or_default() + 1;
+        let offset = "  ".repeat(width);
+        write!(f, "{offset}header:\n{:width$}", self.header)?;
+        if let Some(name) = self.name {
+            write!(f, "{offset}name: {}\n", name)?;
+        }
+        write!(f, "{offset}files:\n")?;
+        for file in &self.files {
+            write!(f, "{:width$}", file)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for FirmwareVolume<'a> {
+    fn parse<E>(input: &'a [u8]) -> nom::IResult<&'a [u8], Self, E>
+    where
+        E: NomError<'a>,
+    {
+        let (header_rest, header) = FirmwareVolumeHeader::parse(input)?;
+        // The header length covers the block map, and the volume its header
+        let header_size = input.len() - header_rest.len();
+        if (header.header_length as usize) < header_size
+            || header.fv_length < header.header_length as u64
+        {
+            return Err(nom::Err::Error(E::add_context(
+                input,
+                "Invalid firmware volume length",
+                E::from_error_kind(input, nom::error::ErrorKind::Verify),
+            )));
+        }
+        let (rest, data) = context("Firmware volume", take(header.fv_length as usize))(input)?;
+
+        let (name, files_offset) = if header.ext_header_offset != 0 {
+            let ext_header_offset = header.ext_header_offset as usize;
+            let (_, (name, ext_header_size)) = context(
+                "Firmware volume extended header",
+                tuple((Guid::parse, le_u32)),
+            )(at(data, ext_header_offset)?)?;
+            (Some(name), ext_header_offset + ext_header_size as usize)
+        } else {
+            (None, header.header_length as usize)
+        };
+
+        // Files are aligned on 8 bytes, free space starts with an erased header.
+        let erased = if header.erase_polarity() { 0xff } else { 0x00 };
+        let mut files = Vec::new();
+        let mut offset = (files_offset + 7) & !7;
+        while let Some(file_header) = data.get(offset..offset + 24) {
+            if file_header.iter().all(|b| *b == erased) {
+                break;
+            }
+            // A corrupted file header leaves no way to find the next file
+            let (r, file) = match FfsFile::parse::<E>(&data[offset..]) {
+                Ok(file) => file,
+                Err(_) => break,
+            };
+            offset = (data.len() - r.len() + 7) & !7;
+            files.push(file);
+        }
+
+        Ok((
+            rest,
+            Self {
+                data,
+                header,
+                name,
+                files,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::uefi::FfsFileType;
+
+    type Error<'a> = nom::error::Error<&'a [u8]>;
+
+    pub(in crate::uefi) fn file(file_type: u8, state: u8, data: &[u8]) -> Vec<u8> {
+        let mut file = vec![file_type; 16];
+        file.extend_from_slice(&[0, 0, file_type, 0]);
+        file.extend_from_slice(&(24 + data.len() as u32).to_le_bytes()[..3]);
+        file.push(state);
+        file.extend_from_slice(data);
+        file
+    }
+
+    /// Volume with a single block and a valid checksum, files aligned on 8 bytes.
+    pub(in crate::uefi) fn volume(attributes: u32, files: &[Vec<u8>]) -> Vec<u8> {
+        let mut volume = vec![0; 16];
+        volume.extend_from_slice(&[0x11; 16]);
+        volume.extend_from_slice(&[0; 8]);
+        volume.extend_from_slice(b"_FVH");
+        volume.extend_from_slice(&attributes.to_le_bytes());
+        volume.extend_from_slice(&72u16.to_le_bytes());
+        volume.extend_from_slice(&[0; 6]);
+        volume.extend_from_slice(&[1, 0, 0, 0, 0, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        for file in files {
+            volume.extend_from_slice(file);
+            volume.resize((volume.len() + 7) & !7, 0xff);
+        }
+        // Free space
+        let erased = if attributes & EFI_FVB2_ERASE_POLARITY != 0 {
+            0xff
+        } else {
+            0
+        };
+        volume.extend_from_slice(&[erased; 24]);
+        let fv_length = volume.len() as u64;
+        volume[32..40].copy_from_slice(&fv_length.to_le_bytes());
+        let sum = volume[..72].chunks_exact(2).fold(0u16, |sum, word| {
+            sum.wrapping_add(u16::from_le_bytes([word[0], word[1]]))
+        });
+        volume[50..52].copy_from_slice(&sum.wrapping_neg().to_le_bytes());
+        volume
+    }
+
+    #[test]
+    fn files() {
+        let files = [
+            file(0x07, 0xf8, b"odd"),
+            file(0xf0, 0xf8, &[0xff; 8]),
+            file(0x01, 0xd8, b""),
+        ];
+        let mut data = volume(EFI_FVB2_ERASE_POLARITY, &files);
+        data.extend_from_slice(b"next");
+
+        let (rest, fv) = FirmwareVolume::parse::<Error>(&data).unwrap();
+        assert_eq!(rest, b"next");
+        assert!(fv.checksum_valid());
+        assert!(fv.header.erase_polarity());
+        assert_eq!(fv.header.block_map, [(1, 0x1000)]);
+        assert_eq!(fv.name, None);
+        assert_eq!(fv.files.len(), 3);
+        // The second file starts on the next 8-byte boundary after the first one
+        assert_eq!(fv.files[0].file_type, FfsFileType::Driver);
+        assert_eq!(fv.files[0].data, b"odd");
+        assert_eq!(fv.files[1].file_type, FfsFileType::Pad);
+        assert_eq!(fv.files[1].data, [0xff; 8]);
+        assert_eq!(fv.files[2].file_type, FfsFileType::Raw);
+        // State bits are kept as stored, inverted by the erase polarity
+        assert_eq!(fv.files[0].state, 0xf8);
+        assert_eq!(fv.files[2].state, 0xd8);
+
+        // Without erase polarity, free space is zeroed
+        let data = volume(0, &[file(0x07, 0x07, b"")]);
+        let (_, fv) = FirmwareVolume::parse::<Error>(&data).unwrap();
+        assert_eq!(fv.files.len(), 1);
+        assert_eq!(fv.files[0].state, 0x07);
+    }
+
+    #[test]
+    fn large_file() {
+        let mut large = vec![0x22; 16];
+        large.extend_from_slice(&[0, 0, 0x01, 0x01, 0, 0, 0, 0xf8]);
+        large.extend_from_slice(&36u64.to_le_bytes());
+        large.extend_from_slice(b"data");
+        let data = volume(EFI_FVB2_ERASE_POLARITY, &[large]);
+
+        let (_, fv) = FirmwareVolume::parse::<Error>(&data).unwrap();
+        assert_eq!(fv.files.len(), 1);
+        assert_eq!(fv.files[0].attributes, 0x01);
+        assert_eq!(fv.files[0].data, b"data");
+    }
+
+    #[test]
+    fn bad_file() {
+        // The file size runs past the volume, the files before it are kept
+        let mut bad = file(0x07, 0xf8, b"");
+        bad[20] = 0xff;
+        let data = volume(
+            EFI_FVB2_ERASE_POLARITY,
+            &[file(0x07, 0xf8, b"good"), bad, file(0x07, 0xf8, b"lost")],
+        );
+
+        let (_, fv) = FirmwareVolume::parse::<Error>(&data).unwrap();
+        assert_eq!(fv.files.len(), 1);
+        assert_eq!(fv.files[0].data, b"good");
+    }
+
+    #[test]
+    fn checksum() {
+        let mut data = volume(0, &[]);
+        let (_, fv) = FirmwareVolume::parse::<Error>(&data).unwrap();
+        assert!(fv.checksum_valid());
+
+        data[44] ^= 1;
+        let (_, fv) = FirmwareVolume::parse::<Error>(&data).unwrap();
+        assert!(!fv.checksum_valid());
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        let data = volume(0, &[]);
+
+        // Header length smaller than the header and its block map
+        let mut short_header = data.clone();
+        short_header[48..50].copy_from_slice(&64u16.to_le_bytes());
+        assert!(FirmwareVolume::parse::<Error>(&short_header).is_err());
+
+        // Volume smaller than its header, including the empty volume
+        for fv_length in [0u64, 71] {
+            let mut short_volume = data.clone();
+            short_volume[32..40].copy_from_slice(&fv_length.to_le_bytes());
+            assert!(FirmwareVolume::parse::<Error>(&short_volume).is_err());
+        }
+
+        // Volume larger than the data
+        assert!(FirmwareVolume::parse::<Error>(&data[..data.len() - 1]).is_err());
+    }
+}